@@ -79,7 +79,7 @@ pub const MAX_STACK_DEPTH: usize = 127;
 /// `EVENTS` ring buffer by eBPF and read by userspace in the main loop.
 ///
 /// **Memory Layout**: `#[repr(C)]` ensures consistent layout across kernel/userspace
-/// **Size**: Must be small to minimize ring buffer overhead (~72 bytes)
+/// **Size**: Must be small to minimize ring buffer overhead (112 bytes)
 #[repr(C)]
 #[derive(Clone, Copy)]
 pub struct TaskEvent {
@@ -192,6 +192,42 @@ pub struct TaskEvent {
     /// Padding for 8-byte alignment
     #[allow(clippy::pub_underscore_fields)]
     pub _padding: [u8; 5],
+
+    // ========================================================================
+    // Blocker (EVENT_SCHEDULER_DETECTED only)
+    // ========================================================================
+    /// Thread ID of the thread switched out as the worker got its CPU back
+    ///
+    /// This is the thread that was holding the worker's CPU at the end of the
+    /// wait. `0` if unknown, the idle task, or not a scheduler event.
+    pub blocker_tid: u32,
+
+    /// Process ID (TGID) of the blocker thread
+    ///
+    /// Userspace can only symbolize `blocker_stack_id` when this matches the
+    /// target process.
+    pub blocker_pid: u32,
+
+    /// User-space stack trace ID of the blocker (negative if not captured)
+    pub blocker_stack_id: i64,
+
+    /// Kernel stack trace ID of the blocker (negative if not captured)
+    ///
+    /// Resolved against `/proc/kallsyms`. Shares the `STACK_TRACES` map with
+    /// user-space stacks, so IDs never collide.
+    pub blocker_kernel_stack_id: i64,
+
+    /// Command name of the blocker thread (NUL-padded, from `prev_comm`)
+    pub blocker_comm: [u8; 16],
+}
+
+impl TaskEvent {
+    /// Blocker command name, trimmed at the first NUL (empty if unknown)
+    #[must_use]
+    pub fn blocker_comm_bytes(&self) -> &[u8] {
+        let len = self.blocker_comm.iter().position(|&b| b == 0).unwrap_or(16);
+        &self.blocker_comm[..len]
+    }
 }
 
 /// Thread execution state (for scheduler-based detection)
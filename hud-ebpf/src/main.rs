@@ -50,6 +50,12 @@ use hud_common::{
 /// - BPF_F_REUSE_STACKID (0x400): Overwrite existing entry on hash collision instead of returning -EEXIST
 const STACK_FLAGS: u64 = 0x100 | 0x200 | 0x400;
 
+/// Same as `STACK_FLAGS` without BPF_F_USER_STACK: capture the kernel stack
+const KERNEL_STACK_FLAGS: u64 = 0x200 | 0x400;
+
+/// Stack ID placeholder for events that carry no stack (never a valid ID)
+const NO_STACK: i64 = -1;
+
 // ============================================================================
 // eBPF Maps - Shared data structures between kernel and userspace
 // ============================================================================
@@ -178,6 +184,7 @@ fn try_sched_switch(ctx: &TracePointContext) -> Result<(), i64> {
     let args: *const SchedSwitchArgs = ctx.as_ptr() as *const SchedSwitchArgs;
     let prev_pid = unsafe { (*args).prev_pid as u32 };
     let prev_state = unsafe { (*args).prev_state };
+    let prev_comm = unsafe { (*args).prev_comm };
     let next_pid = unsafe { (*args).next_pid as u32 };
 
     let now = unsafe { bpf_ktime_get_ns() };
@@ -185,12 +192,19 @@ fn try_sched_switch(ctx: &TracePointContext) -> Result<(), i64> {
     // Handle thread going OFF CPU (prev_pid)
     handle_thread_off_cpu(prev_pid, prev_state, now)?;
 
-    // Handle thread going ON CPU (next_pid)
-    handle_thread_on_cpu(next_pid, now, ctx)?;
+    // Handle thread going ON CPU (next_pid). prev is the thread that held the
+    // CPU until now, i.e. the blocker if next waited too long.
+    handle_thread_on_cpu(next_pid, now, ctx, Blocker { tid: prev_pid, comm: prev_comm })?;
 
     Ok(())
 }
 
+/// The thread being switched out when a waiting worker gets its CPU back
+struct Blocker {
+    tid: u32,
+    comm: [u8; 16],
+}
+
 fn handle_thread_off_cpu(tid: u32, state: i64, now: u64) -> Result<(), i64> {
     // Update thread state for blocking detection
     let mut thread_state = unsafe { THREAD_STATE.get(&tid).copied().unwrap_or_default() };
@@ -205,7 +219,12 @@ fn handle_thread_off_cpu(tid: u32, state: i64, now: u64) -> Result<(), i64> {
     Ok(())
 }
 
-fn handle_thread_on_cpu(tid: u32, now: u64, _ctx: &TracePointContext) -> Result<(), i64> {
+fn handle_thread_on_cpu(
+    tid: u32,
+    now: u64,
+    ctx: &TracePointContext,
+    blocker: Blocker,
+) -> Result<(), i64> {
     // Early exit: Only process Tokio worker threads
     let is_worker = unsafe { TOKIO_WORKER_THREADS.get(&tid).is_some() };
     if !is_worker {
@@ -234,16 +253,13 @@ fn handle_thread_on_cpu(tid: u32, now: u64, _ctx: &TracePointContext) -> Result<
 
             let task_id = unsafe { THREAD_TASK_MAP.get(&tid).copied().unwrap_or(0) };
 
-            // Stack from sched_switch is the outgoing thread's, not ours.
-            // Perf_event samples provide correct stacks for blocking workers.
-            let stack_id: i64 = -1;
-
             report_scheduler_blocking(
                 tid,
                 task_id,
                 thread_state.off_cpu_duration,
-                stack_id,
                 thread_state.state_when_switched,
+                ctx,
+                &blocker,
             )?;
         }
     }
@@ -266,18 +282,34 @@ fn report_scheduler_blocking(
     tid: u32,
     task_id: u64,
     duration_ns: u64,
-    stack_id: i64,
     thread_state: i64,
+    ctx: &TracePointContext,
+    blocker: &Blocker,
 ) -> Result<(), i64> {
-    let pid_tgid = unsafe { bpf_get_current_pid_tgid() };
-    let pid = (pid_tgid >> 32) as u32;
+    // sched_switch runs in the context of the outgoing thread, so "current"
+    // is the blocker here, not the worker
+    let blocker_pid = unsafe { (bpf_get_current_pid_tgid() >> 32) as u32 };
+    let pid = unsafe { TOKIO_WORKER_THREADS.get(&tid).map(|info| info.pid).unwrap_or(0) };
+
+    // The victim's stack is not available here; capture the blocker's instead.
+    // The idle task (tid 0) has nothing worth capturing.
+    let (blocker_stack_id, blocker_kernel_stack_id) = if blocker.tid == 0 {
+        (NO_STACK, NO_STACK)
+    } else {
+        unsafe {
+            (
+                STACK_TRACES.get_stackid(ctx, STACK_FLAGS).unwrap_or_else(|e| e),
+                STACK_TRACES.get_stackid(ctx, KERNEL_STACK_FLAGS).unwrap_or_else(|e| e),
+            )
+        }
+    };
 
     let event = TaskEvent {
         pid,
         tid,
         timestamp_ns: unsafe { bpf_ktime_get_ns() },
         event_type: EVENT_SCHEDULER_DETECTED,
-        stack_id,
+        stack_id: NO_STACK,
         duration_ns,
         worker_id: get_worker_id(tid),
         cpu_id: get_cpu_id(),
@@ -287,6 +319,11 @@ fn report_scheduler_blocking(
         detection_method: DETECTION_SCHEDULER,
         is_tokio_worker: 1, // Only workers trigger scheduler detection
         _padding: [0u8; 5],
+        blocker_tid: blocker.tid,
+        blocker_pid,
+        blocker_stack_id,
+        blocker_kernel_stack_id,
+        blocker_comm: blocker.comm,
     };
 
     unsafe {
@@ -377,6 +414,11 @@ fn try_on_cpu_sample(ctx: &PerfEventContext) -> Result<(), i64> {
         detection_method: DETECTION_PERF_SAMPLE,
         is_tokio_worker: 1,
         _padding: [0u8; 5],
+        blocker_tid: 0,
+        blocker_pid: 0,
+        blocker_stack_id: NO_STACK,
+        blocker_kernel_stack_id: NO_STACK,
+        blocker_comm: [0u8; 16],
     };
 
     let output_result = unsafe { EVENTS.output(&event, 0) };
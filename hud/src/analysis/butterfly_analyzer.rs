@@ -0,0 +1,280 @@
+//! Caller/callee analysis for a single function.
+//!
+//! A hot function such as `serde_json::from_slice` is often reached from
+//! dozens of handlers. `HotspotStats` keeps only a few representative stacks
+//! per function, so this pass walks every sampled stack instead and
+//! aggregates, for one function name:
+//!
+//! - **Callers** - the frame directly outside it
+//! - **Callees** - the frame directly inside it, or "self" when it was the
+//!   innermost frame
+//!
+//! # Display in TUI
+//!
+//! ```text
+//! [ BUTTERFLY ]
+//!   41.2%  myapp::handlers::upload
+//!   33.0%  myapp::handlers::import
+//! ▶ serde_json::from_slice   212 samples  18.4%
+//!   61.3%  serde_json::de::Deserializer::parse_value
+//!   22.1%  (self)
+//! ```
+//!
+//! Percentages are of the samples passing through the function. A recursive
+//! function counts each caller and callee once per sample.
+//!
+//! The live TUI keeps a [`ButterflyStats`] for the function on screen and
+//! feeds it each event as it arrives; [`analyze_butterfly`] is the batch form
+//! for windowed, paused or ranged data.
+
+// Percentage calculations intentionally convert usize to f64
+#![allow(clippy::cast_precision_loss)]
+
+use crate::classification::FrameOrigin;
+use crate::trace_data::{TraceData, TraceEvent};
+use std::collections::{HashMap, HashSet};
+
+// =============================================================================
+// OUTPUT TYPES
+// =============================================================================
+
+/// A caller or callee of the analyzed function
+#[derive(Debug, Clone, PartialEq)]
+pub struct Neighbor {
+    pub name: String,
+    pub origin: FrameOrigin,
+    /// Samples with this frame next to the analyzed function
+    pub count: usize,
+    /// Share of the analyzed function's samples (0.0 - 100.0)
+    pub percentage: f64,
+}
+
+/// Callers and callees of one function across every sampled stack
+#[derive(Debug, Clone, PartialEq)]
+pub struct Butterfly {
+    pub function: String,
+    /// Samples whose stack contains the function
+    pub count: usize,
+    /// Samples where the function was the innermost frame
+    pub self_count: usize,
+    /// Samples where the function was the outermost frame (no caller)
+    pub root_count: usize,
+    /// Share of all samples with a stack (0.0 - 100.0)
+    pub percentage: f64,
+    /// Direct callers, most samples first
+    pub callers: Vec<Neighbor>,
+    /// Direct callees, most samples first
+    pub callees: Vec<Neighbor>,
+}
+
+// =============================================================================
+// ANALYSIS
+// =============================================================================
+
+/// Aggregate the callers and callees of `function` over `data`
+#[must_use]
+pub fn analyze_butterfly(data: &TraceData, function: &str) -> Butterfly {
+    ButterflyStats::from_data(data, function).to_butterfly()
+}
+
+/// Callers and callees of one function, updated one event at a time
+pub struct ButterflyStats {
+    function: String,
+    /// Samples with a call stack
+    total: usize,
+    count: usize,
+    self_count: usize,
+    root_count: usize,
+    callers: HashMap<String, (FrameOrigin, usize)>,
+    callees: HashMap<String, (FrameOrigin, usize)>,
+}
+
+impl ButterflyStats {
+    /// Empty stats for `function`
+    #[must_use]
+    pub fn new(function: &str) -> Self {
+        Self {
+            function: function.to_string(),
+            total: 0,
+            count: 0,
+            self_count: 0,
+            root_count: 0,
+            callers: HashMap::new(),
+            callees: HashMap::new(),
+        }
+    }
+
+    /// Stats for `function` over every event of `data`
+    #[must_use]
+    pub fn from_data(data: &TraceData, function: &str) -> Self {
+        let mut stats = Self::new(function);
+        for event in data.events.iter() {
+            stats.record_event(event);
+        }
+        stats
+    }
+
+    /// Function the stats are for
+    #[must_use]
+    pub fn function(&self) -> &str {
+        &self.function
+    }
+
+    /// Count one event; O(stack depth)
+    pub fn record_event(&mut self, event: &TraceEvent) {
+        let function = self.function.as_str();
+        if event.is_scheduler_event() || event.name == "execution" {
+            return;
+        }
+        let Some(ref stack) = event.call_stack else {
+            return;
+        };
+        if stack.is_empty() {
+            return;
+        }
+        self.total += 1;
+        if !stack.iter().any(|f| f.function == function) {
+            return;
+        }
+        self.count += 1;
+
+        // Stacks are innermost first: the caller of frame i is i + 1
+        let mut seen_outer = HashSet::new();
+        let mut seen_inner = HashSet::new();
+        let (mut is_self, mut is_root) = (false, false);
+        for (i, frame) in stack.iter().enumerate() {
+            if frame.function != function {
+                continue;
+            }
+            match stack.get(i + 1) {
+                Some(caller) if caller.function != function => {
+                    if seen_outer.insert(&caller.function) {
+                        bump(&mut self.callers, &caller.function, caller.origin);
+                    }
+                }
+                Some(_) => {}
+                None => is_root = true,
+            }
+            match i.checked_sub(1).map(|j| &stack[j]) {
+                Some(callee) if callee.function != function => {
+                    if seen_inner.insert(&callee.function) {
+                        bump(&mut self.callees, &callee.function, callee.origin);
+                    }
+                }
+                Some(_) => {}
+                None => is_self = true,
+            }
+        }
+        self.self_count += usize::from(is_self);
+        self.root_count += usize::from(is_root);
+    }
+
+    /// Current callers and callees, ranked
+    #[must_use]
+    pub fn to_butterfly(&self) -> Butterfly {
+        let count = self.count;
+        Butterfly {
+            function: self.function.clone(),
+            count,
+            self_count: self.self_count,
+            root_count: self.root_count,
+            percentage: percent(count, self.total),
+            callers: ranked(&self.callers, count),
+            callees: ranked(&self.callees, count),
+        }
+    }
+}
+
+fn bump(map: &mut HashMap<String, (FrameOrigin, usize)>, name: &str, origin: FrameOrigin) {
+    map.entry(name.to_string()).or_insert((origin, 0)).1 += 1;
+}
+
+/// Neighbors by samples, most first (ties by name, so the list is stable
+/// between refreshes)
+fn ranked(map: &HashMap<String, (FrameOrigin, usize)>, total: usize) -> Vec<Neighbor> {
+    let mut neighbors: Vec<Neighbor> = map
+        .iter()
+        .map(|(name, &(origin, count))| Neighbor {
+            name: name.clone(),
+            origin,
+            count,
+            percentage: percent(count, total),
+        })
+        .collect();
+    neighbors.sort_unstable_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+    neighbors
+}
+
+fn percent(count: usize, total: usize) -> f64 {
+    if total > 0 {
+        count as f64 / total as f64 * 100.0
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::stack_sample;
+    use std::sync::Arc;
+
+    fn data(events: Vec<TraceEvent>) -> TraceData {
+        TraceData { events: Arc::new(events), workers: Arc::new(vec![0]), duration: 1.0 }
+    }
+
+    #[test]
+    fn aggregates_every_caller_and_callee() {
+        let mut events = Vec::new();
+        // Ten distinct handlers, more than HotspotStats keeps stacks for
+        for i in 0..10 {
+            let handler = format!("handler_{i}");
+            events.push(stack_sample(&["main", &handler, "from_slice", "parse_value"]));
+        }
+        events.push(stack_sample(&["main", "handler_0", "from_slice"]));
+        events.push(stack_sample(&["main", "other"]));
+
+        let b = analyze_butterfly(&data(events), "from_slice");
+        assert_eq!(b.count, 11);
+        assert_eq!(b.self_count, 1);
+        assert_eq!(b.callers.len(), 10);
+        assert_eq!(b.callers[0].name, "handler_0");
+        assert_eq!(b.callers[0].count, 2);
+        assert_eq!(b.callees[0].name, "parse_value");
+        assert_eq!(b.callees[0].count, 10);
+        assert!((b.percentage - 11.0 / 12.0 * 100.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn recursion_counts_each_neighbor_once_per_sample() {
+        let events = vec![stack_sample(&["main", "walk", "walk", "walk", "visit"])];
+        let b = analyze_butterfly(&data(events), "walk");
+        assert_eq!(b.count, 1);
+        assert_eq!(
+            b.callers,
+            vec![Neighbor {
+                name: "main".into(),
+                origin: FrameOrigin::UserCode,
+                count: 1,
+                percentage: 100.0
+            }]
+        );
+        assert_eq!(b.callees.len(), 1);
+        assert_eq!(b.callees[0].name, "visit");
+        assert_eq!(b.self_count, 0);
+    }
+
+    #[test]
+    fn streaming_matches_batch() {
+        let events = vec![
+            stack_sample(&["main", "handler", "from_slice"]),
+            stack_sample(&["main", "other"]),
+            stack_sample(&["main", "from_slice", "parse_value"]),
+        ];
+        let mut stats = ButterflyStats::new("from_slice");
+        for event in &events {
+            stats.record_event(event);
+        }
+        assert_eq!(stats.to_butterfly(), analyze_butterfly(&data(events), "from_slice"));
+    }
+}
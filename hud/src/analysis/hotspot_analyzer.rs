@@ -8,6 +8,19 @@
 //! - **`HotspotStats`** - Efficient aggregation as events stream in
 //! - **`analyze_hotspots()`** - Batch analysis from a `TraceData` snapshot
 //!
+//! ## Attribution
+//!
+//! A sample can be charged to different frames of its stack, which answer
+//! different questions (see [`Attribution`]):
+//!
+//! - **First user frame** (default): the event's name, as the eBPF side
+//!   resolved it. "Which of *my* functions is blocking?"
+//! - **Leaf**: the innermost frame, often a library (`bcrypt::hash`).
+//!   "What code is actually on the CPU?"
+//! - **Inclusive**: every distinct frame of the stack. "How much blocking
+//!   happens *under* `my_handler`?" Shares sum to more than 100%; the
+//!   exclusive (self) share is the leaf count.
+//!
 //! ## Data Flow
 //!
 //! ```text
@@ -20,9 +33,12 @@
 //!
 //! # Performance
 //!
-//! - `record_event()`: O(1) amortized (`HashMap` insert/update)
+//! - `record_event()`: O(1) for the first user frame ranking; O(stack depth)
+//!   while the leaf or inclusive ranking is selected
+//! - `set_attribution()`: replays the session once to build the new ranking
 //! - `to_hotspots()`: O(n log n) where n = unique functions (sorting)
-//! - Memory: O(unique functions) × O(call stacks per function)
+//! - Memory: O(unique functions) × O(workers), plus up to
+//!   `MAX_CALL_STACKS_PER_HOTSPOT` shared stacks per function
 
 // Percentage calculations intentionally convert usize to f64
 // Count truncation on 32-bit is acceptable (won't overflow in practice)
@@ -43,6 +59,44 @@ use std::sync::Arc;
 /// call patterns without excessive memory usage.
 const MAX_CALL_STACKS_PER_HOTSPOT: usize = 5;
 
+// =============================================================================
+// ATTRIBUTION
+// =============================================================================
+
+/// Which frame(s) of a sample's stack a hotspot ranking charges it to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Attribution {
+    /// The first user-code frame (the event name)
+    #[default]
+    UserFrame,
+    /// The innermost frame, whatever crate it is in
+    Leaf,
+    /// Every frame on the stack, counted once per sample
+    Inclusive,
+}
+
+impl Attribution {
+    /// The next mode, for cycling through them with one key
+    #[must_use]
+    pub fn next(self) -> Self {
+        match self {
+            Self::UserFrame => Self::Leaf,
+            Self::Leaf => Self::Inclusive,
+            Self::Inclusive => Self::UserFrame,
+        }
+    }
+
+    /// Short name for display
+    #[must_use]
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::UserFrame => "user frame",
+            Self::Leaf => "leaf",
+            Self::Inclusive => "inclusive",
+        }
+    }
+}
+
 // =============================================================================
 // FUNCTION HOTSPOT (OUTPUT TYPE)
 // =============================================================================
@@ -69,6 +123,10 @@ pub struct FunctionHotspot {
     /// Total sample count for this function.
     pub count: usize,
 
+    /// Samples with this function as the innermost frame. Differs from
+    /// `count` only in the inclusive ranking.
+    pub self_count: usize,
+
     /// Percentage of total samples (0.0 - 100.0).
     pub percentage: f64,
 
@@ -88,6 +146,12 @@ pub struct FunctionHotspot {
     /// Sorted by frequency (most common call path first).
     /// Uses `Arc` for cheap cloning since stacks are shared.
     pub call_stacks: Vec<Arc<Vec<StackFrame>>>,
+
+    /// Scheduler latency events attributed to this function as the blocker.
+    pub sched_waits: usize,
+
+    /// Total worker wait time (nanoseconds) attributed to this function.
+    pub sched_wait_ns: u64,
 }
 
 // =============================================================================
@@ -96,14 +160,20 @@ pub struct FunctionHotspot {
 
 /// Efficient hotspot statistics aggregator.
 ///
-/// Tracks statistics as events stream in, allowing efficient O(1) updates
-/// rather than re-analyzing all events on each TUI refresh.
+/// Tracks statistics as events stream in rather than re-analyzing all events
+/// on each TUI refresh.
+///
+/// The first user frame ranking is always kept: reports, budgets and
+/// scheduler waits use it. The leaf and inclusive rankings are only kept for
+/// the selected [`Attribution`] (see [`set_attribution`](Self::set_attribution)),
+/// since they add an entry and a set of stacks for every frame of every
+/// distinct stack.
 ///
 /// # Memory Usage
 ///
 /// - O(unique function names) × O(workers) for the main `HashMap`
+/// - O(unique frames) × O(workers) for the selected leaf or inclusive ranking
 /// - O(unique call stacks) bounded by `MAX_CALL_STACKS_PER_HOTSPOT`
-/// - Typical: 100-500 functions × ~1 KB each ≈ 0.5 MB
 #[derive(Debug, Default)]
 pub struct HotspotStats {
     /// Per-function statistics, keyed by function name.
@@ -112,6 +182,21 @@ pub struct HotspotStats {
     /// Total samples processed (excluding "execution" events).
     /// Used as denominator for percentage calculations.
     total_samples: u64,
+
+    /// Duration of every scheduler latency event, attributed or not, in
+    /// nanoseconds. Only waits over `--threshold` arrive, so this stays small.
+    sched_wait_durations: Vec<u64>,
+
+    /// Ranking kept besides the first user frame one.
+    attribution: Attribution,
+
+    /// Per-function statistics keyed by the innermost stack frame. Kept for
+    /// the leaf and inclusive rankings (the latter shows it as self share).
+    leaf_functions: HashMap<String, FunctionStats>,
+
+    /// Per-function statistics for every frame on the stack. Kept for the
+    /// inclusive ranking only.
+    inclusive_functions: HashMap<String, FunctionStats>,
 }
 
 /// Internal statistics for a single function.
@@ -137,6 +222,12 @@ struct FunctionStats {
     /// Representative call stacks with their occurrence counts.
     /// Limited to `MAX_CALL_STACKS_PER_HOTSPOT` entries.
     call_stacks: Vec<(Arc<Vec<StackFrame>>, u64)>,
+
+    /// Scheduler latency events this function caused (as the blocker).
+    sched_waits: u64,
+
+    /// Total worker wait time this function caused, in nanoseconds.
+    sched_wait_ns: u64,
 }
 
 impl HotspotStats {
@@ -152,9 +243,23 @@ impl HotspotStats {
     ///
     /// # Performance
     ///
-    /// O(1) amortized - `HashMap` operations are constant time on average.
-    /// The stack tracking uses Arc pointer comparison which is also O(1).
+    /// O(1) for the first user frame ranking; the leaf and inclusive ones,
+    /// when selected, add O(stack depth). Nothing is allocated per event, and
+    /// the stack tracking uses Arc pointer comparison, O(1).
     pub fn record_event(&mut self, event: &TraceEvent) {
+        // Scheduler latency events are intervals, not samples. They never touch
+        // the percentage denominator; when correlation found a blocker, the
+        // wait is charged to the blocker's function instead.
+        if let Some(wait_ns) = event.duration_ns {
+            self.sched_wait_durations.push(wait_ns);
+            if event.blocker.is_some() && event.name != "execution" {
+                let stats = self.function_entry(event);
+                stats.sched_waits += 1;
+                stats.sched_wait_ns += wait_ns;
+            }
+            return;
+        }
+
         // Skip "execution" events - these represent scheduler/idle time,
         // not actual blocking functions. We only count real function samples.
         if event.name == "execution" {
@@ -165,56 +270,128 @@ impl HotspotStats {
         self.total_samples += 1;
 
         // Get or create stats entry for this function
-        let stats = self.functions.entry(event.name.clone()).or_insert_with(|| FunctionStats {
-            count: 0,
-            workers: HashMap::new(),
-            file: event.file.clone(),
-            line: event.line,
-            seen_stack_ids: HashSet::new(),
-            call_stacks: Vec::new(),
-        });
+        self.function_entry(event).record_sample(event);
+        self.record_frames(event);
+    }
 
-        stats.count += 1;
-        *stats.workers.entry(event.worker_id).or_insert(0) += 1;
+    /// Select the ranking kept besides the first user frame one, building it
+    /// from `events` (the session so far) when it changes.
+    pub fn set_attribution(&mut self, attribution: Attribution, events: &[TraceEvent]) {
+        if attribution == self.attribution {
+            return;
+        }
+        self.attribution = attribution;
+        self.leaf_functions = HashMap::new();
+        self.inclusive_functions = HashMap::new();
+        for event in events {
+            if event.duration_ns.is_none() && event.name != "execution" {
+                self.record_frames(event);
+            }
+        }
+    }
 
-        // Track unique call stacks if available
-        if let Some(ref stack) = event.call_stack {
-            // Use pointer address as a proxy for stack identity (Arc deduplication)
-            let stack_ptr = Arc::as_ptr(stack) as i64;
-            if stats.seen_stack_ids.insert(stack_ptr) {
-                // New unique stack - add to our collection if we have room
-                if stats.call_stacks.len() < MAX_CALL_STACKS_PER_HOTSPOT {
-                    stats.call_stacks.push((Arc::clone(stack), 1));
-                }
-            } else {
-                // Existing stack - increment its count
-                for (existing_stack, count) in &mut stats.call_stacks {
-                    if Arc::ptr_eq(existing_stack, stack) {
-                        *count += 1;
-                        break;
-                    }
-                }
+    /// Charge a sample to the frames of the selected leaf or inclusive
+    /// ranking. Without a stack, the event's own function is the only frame
+    /// there is.
+    fn record_frames(&mut self, event: &TraceEvent) {
+        if self.attribution == Attribution::UserFrame {
+            return;
+        }
+        let stack = event.call_stack.as_deref().filter(|stack| !stack.is_empty());
+        let (leaf, file, line) = stack
+            .map_or((event.name.as_str(), event.file.as_ref(), event.line), |stack| {
+                (stack[0].function.as_str(), stack[0].file.as_ref(), stack[0].line)
+            });
+        frame_entry(&mut self.leaf_functions, leaf, file, line).record_sample(event);
+
+        if self.attribution != Attribution::Inclusive {
+            return;
+        }
+        let Some(stack) = stack else {
+            frame_entry(&mut self.inclusive_functions, leaf, file, line).record_sample(event);
+            return;
+        };
+        for (i, frame) in stack.iter().enumerate() {
+            // Recursive frames count once per sample. Stacks are capped at 127
+            // frames (`perf_event_max_stack`), so scanning beats hashing them.
+            if stack[..i].iter().any(|f| f.function == frame.function) {
+                continue;
             }
+            frame_entry(
+                &mut self.inclusive_functions,
+                &frame.function,
+                frame.file.as_ref(),
+                frame.line,
+            )
+            .record_sample(event);
         }
     }
 
+    /// Get or create the stats entry for an event's function.
+    fn function_entry(&mut self, event: &TraceEvent) -> &mut FunctionStats {
+        self.functions
+            .entry(event.name.clone())
+            .or_insert_with(|| FunctionStats::new(event.file.clone(), event.line))
+    }
+
     /// Get total samples recorded
     #[must_use]
     pub fn total_samples(&self) -> u64 {
         self.total_samples
     }
 
+    /// Number of scheduler latency events longer than `min_ns`
+    #[must_use]
+    pub fn sched_waits_over(&self, min_ns: u64) -> usize {
+        self.sched_wait_durations.iter().filter(|&&ns| ns > min_ns).count()
+    }
+
+    /// Total samples per worker, across all functions
+    #[must_use]
+    pub fn worker_samples(&self) -> HashMap<u32, u64> {
+        let mut totals = HashMap::new();
+        for stats in self.functions.values() {
+            for (&worker, &count) in &stats.workers {
+                *totals.entry(worker).or_insert(0) += count;
+            }
+        }
+        totals
+    }
+
     /// Convert cumulative stats to hotspot list for display
     #[must_use]
     pub fn to_hotspots(&self) -> Vec<FunctionHotspot> {
+        self.to_hotspots_by(Attribution::UserFrame)
+    }
+
+    /// Convert cumulative stats to a hotspot list ranked by `attribution`.
+    ///
+    /// The leaf and inclusive rankings are empty unless selected with
+    /// [`set_attribution`](Self::set_attribution). Scheduler waits are charged by the event's name, so the leaf and
+    /// inclusive rankings show them for functions that are also first user
+    /// frames.
+    #[must_use]
+    pub fn to_hotspots_by(&self, attribution: Attribution) -> Vec<FunctionHotspot> {
         let total = self.total_samples;
+        let functions = match attribution {
+            Attribution::UserFrame => &self.functions,
+            Attribution::Leaf => &self.leaf_functions,
+            Attribution::Inclusive => &self.inclusive_functions,
+        };
 
-        let mut hotspots: Vec<FunctionHotspot> = self
-            .functions
+        let mut hotspots: Vec<FunctionHotspot> = functions
             .iter()
             .map(|(name, stats)| {
                 let percentage =
                     if total > 0 { (stats.count as f64 / total as f64) * 100.0 } else { 0.0 };
+                let self_count = match attribution {
+                    Attribution::Inclusive => self.leaf_functions.get(name).map_or(0, |s| s.count),
+                    _ => stats.count,
+                };
+                let waits = match attribution {
+                    Attribution::UserFrame => Some(stats),
+                    _ => self.functions.get(name),
+                };
 
                 // Sort call stacks by frequency
                 let mut sorted_stacks = stats.call_stacks.clone();
@@ -223,11 +400,14 @@ impl HotspotStats {
                 FunctionHotspot {
                     name: name.clone(),
                     count: stats.count as usize,
+                    self_count: self_count as usize,
                     percentage,
                     workers: stats.workers.iter().map(|(&k, &v)| (k, v as usize)).collect(),
                     file: stats.file.clone(),
                     line: stats.line,
                     call_stacks: sorted_stacks.into_iter().map(|(stack, _)| stack).collect(),
+                    sched_waits: waits.map_or(0, |s| s.sched_waits as usize),
+                    sched_wait_ns: waits.map_or(0, |s| s.sched_wait_ns),
                 }
             })
             .collect();
@@ -237,16 +417,81 @@ impl HotspotStats {
     }
 }
 
-/// Aggregated function data: (worker counts, file, line, `call_stacks`)
-type FunctionData = (HashMap<u32, usize>, Option<String>, Option<u32>, Vec<Arc<Vec<StackFrame>>>);
+/// Get or create the stats entry for a stack frame's function.
+fn frame_entry<'a>(
+    functions: &'a mut HashMap<String, FunctionStats>,
+    name: &str,
+    file: Option<&String>,
+    line: Option<u32>,
+) -> &'a mut FunctionStats {
+    // Look up before inserting: most frames repeat, and entry() needs an owned key
+    if !functions.contains_key(name) {
+        functions.insert(name.to_string(), FunctionStats::new(file.cloned(), line));
+    }
+    functions.get_mut(name).expect("entry inserted above")
+}
+
+impl FunctionStats {
+    fn new(file: Option<String>, line: Option<u32>) -> Self {
+        Self {
+            count: 0,
+            workers: HashMap::new(),
+            file,
+            line,
+            seen_stack_ids: HashSet::new(),
+            call_stacks: Vec::new(),
+            sched_waits: 0,
+            sched_wait_ns: 0,
+        }
+    }
+
+    /// Count one sample, and its call stack if it has one
+    fn record_sample(&mut self, event: &TraceEvent) {
+        self.count += 1;
+        *self.workers.entry(event.worker_id).or_insert(0) += 1;
+
+        // Track unique call stacks if available
+        if let Some(ref stack) = event.call_stack {
+            // Use pointer address as a proxy for stack identity (Arc deduplication)
+            let stack_ptr = Arc::as_ptr(stack) as i64;
+            if self.seen_stack_ids.insert(stack_ptr) {
+                // New unique stack - add to our collection if we have room
+                if self.call_stacks.len() < MAX_CALL_STACKS_PER_HOTSPOT {
+                    self.call_stacks.push((Arc::clone(stack), 1));
+                }
+            } else {
+                // Existing stack - increment its count
+                for (existing_stack, count) in &mut self.call_stacks {
+                    if Arc::ptr_eq(existing_stack, stack) {
+                        *count += 1;
+                        break;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Aggregated per-function data for batch analysis.
+#[derive(Default)]
+struct FunctionData {
+    workers: HashMap<u32, usize>,
+    file: Option<String>,
+    line: Option<u32>,
+    call_stacks: Vec<Arc<Vec<StackFrame>>>,
+    seen_stacks: HashSet<usize>,
+    sched_waits: usize,
+    sched_wait_ns: u64,
+}
 
 /// Analyze trace data to identify function hotspots (batch analysis).
 ///
 /// Aggregates trace events by function name, counts occurrences,
 /// calculates percentages, and sorts by frequency (descending).
 ///
-/// Note: "execution" events (scheduler/idle time) are filtered out to show
-/// only actual function samples.
+/// Note: "execution" events (scheduler/idle time) and scheduler latency
+/// events are filtered out of the sample counts. Latency events with an
+/// attributed blocker are charged to the blocker's `sched_waits` instead.
 ///
 /// For efficient incremental updates during live profiling, use `HotspotStats`.
 ///
@@ -258,42 +503,65 @@ type FunctionData = (HashMap<u32, usize>, Option<String>, Option<u32>, Vec<Arc<V
 #[must_use]
 pub fn analyze_hotspots(data: &TraceData) -> Vec<FunctionHotspot> {
     // Aggregate events by function name, capturing file/line from first occurrence
-    // Filter out "execution" events which represent scheduler/idle time
     let mut function_data: HashMap<String, FunctionData> = HashMap::new();
     let mut total_samples: usize = 0;
-    // Track seen stacks per function to avoid duplicates
-    let mut seen_stacks: HashMap<String, HashSet<usize>> = HashMap::new();
 
     for event in data.events.iter() {
         // Skip execution events - they're scheduler/idle time, not actual functions
         if event.name == "execution" {
             continue;
         }
-        total_samples += 1;
 
-        let entry = function_data
-            .entry(event.name.clone())
-            .or_insert_with(|| (HashMap::new(), event.file.clone(), event.line, Vec::new()));
-        *entry.0.entry(event.worker_id).or_insert(0) += 1;
+        let entry = function_data.entry(event.name.clone()).or_insert_with(|| FunctionData {
+            file: event.file.clone(),
+            line: event.line,
+            ..FunctionData::default()
+        });
+
+        // Scheduler latency events are intervals: charge the blocker, not the samples
+        if let Some(wait_ns) = event.duration_ns {
+            if event.blocker.is_some() {
+                entry.sched_waits += 1;
+                entry.sched_wait_ns += wait_ns;
+            }
+            continue;
+        }
+
+        total_samples += 1;
+        *entry.workers.entry(event.worker_id).or_insert(0) += 1;
 
         // Collect unique call stacks (limited to MAX_CALL_STACKS_PER_HOTSPOT)
         if let Some(ref stack) = event.call_stack {
-            let seen = seen_stacks.entry(event.name.clone()).or_default();
             let stack_ptr = Arc::as_ptr(stack) as usize;
-            if seen.insert(stack_ptr) && entry.3.len() < MAX_CALL_STACKS_PER_HOTSPOT {
-                entry.3.push(Arc::clone(stack));
+            if entry.seen_stacks.insert(stack_ptr)
+                && entry.call_stacks.len() < MAX_CALL_STACKS_PER_HOTSPOT
+            {
+                entry.call_stacks.push(Arc::clone(stack));
             }
         }
     }
 
-    // Convert to vector and calculate percentages
+    // Convert to vector and calculate percentages. Unattributed latency events
+    // create entries with nothing in them; drop those.
     let mut hotspots: Vec<FunctionHotspot> = function_data
         .into_iter()
-        .map(|(name, (workers, file, line, call_stacks))| {
-            let count: usize = workers.values().sum();
+        .filter(|(_, data)| !data.workers.is_empty() || data.sched_waits > 0)
+        .map(|(name, data)| {
+            let count: usize = data.workers.values().sum();
             let percentage =
                 if total_samples > 0 { (count as f64 / total_samples as f64) * 100.0 } else { 0.0 };
-            FunctionHotspot { name, count, percentage, workers, file, line, call_stacks }
+            FunctionHotspot {
+                name,
+                count,
+                self_count: count,
+                percentage,
+                workers: data.workers,
+                file: data.file,
+                line: data.line,
+                call_stacks: data.call_stacks,
+                sched_waits: data.sched_waits,
+                sched_wait_ns: data.sched_wait_ns,
+            }
         })
         .collect();
 
@@ -303,6 +571,23 @@ pub fn analyze_hotspots(data: &TraceData) -> Vec<FunctionHotspot> {
     hotspots
 }
 
+/// Analyze trace data ranked by `attribution` (batch analysis).
+///
+/// The default attribution is [`analyze_hotspots`]; the others replay the
+/// events through a `HotspotStats`, as the TUI does for its rolling window.
+#[must_use]
+pub fn analyze_hotspots_by(data: &TraceData, attribution: Attribution) -> Vec<FunctionHotspot> {
+    if attribution == Attribution::UserFrame {
+        return analyze_hotspots(data);
+    }
+    let mut stats = HotspotStats::new();
+    stats.set_attribution(attribution, &[]);
+    for event in data.events.iter() {
+        stats.record_event(event);
+    }
+    stats.to_hotspots_by(attribution)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -323,6 +608,9 @@ mod tests {
                     file: Some("src/a.rs".to_string()),
                     line: Some(10),
                     call_stack: None,
+                    duration_ns: None,
+                    task_id: None,
+                    blocker: None,
                 },
                 TraceEvent {
                     name: "function_a".to_string(),
@@ -334,6 +622,9 @@ mod tests {
                     file: Some("src/a.rs".to_string()),
                     line: Some(10),
                     call_stack: None,
+                    duration_ns: None,
+                    task_id: None,
+                    blocker: None,
                 },
                 TraceEvent {
                     name: "function_b".to_string(),
@@ -345,6 +636,9 @@ mod tests {
                     file: Some("src/b.rs".to_string()),
                     line: Some(20),
                     call_stack: None,
+                    duration_ns: None,
+                    task_id: None,
+                    blocker: None,
                 },
             ]),
             workers: Arc::new(vec![0, 1]),
@@ -398,4 +692,131 @@ mod tests {
         assert_eq!(hotspots[1].file, Some("src/b.rs".to_string()));
         assert_eq!(hotspots[1].line, Some(20));
     }
+
+    #[test]
+    fn test_scheduler_events_do_not_count_as_samples() {
+        let mut data = create_test_data();
+        let mut events = (*data.events).clone();
+        events.push(TraceEvent {
+            name: "function_a".to_string(),
+            worker_id: 0,
+            tid: 100,
+            timestamp: 4.0,
+            cpu: 0,
+            detection_method: Some(2),
+            file: None,
+            line: None,
+            call_stack: None,
+            duration_ns: Some(37_000_000),
+            task_id: None,
+            blocker: None,
+        });
+        data.events = Arc::new(events);
+
+        let hotspots = analyze_hotspots(&data);
+        assert_eq!(hotspots[0].count, 2);
+        assert!((hotspots[0].percentage - 66.666).abs() < 0.01);
+
+        let mut stats = HotspotStats::new();
+        for event in data.events.iter() {
+            stats.record_event(event);
+        }
+        assert_eq!(stats.total_samples(), 3);
+        // The TUI header counts paused or ranged data the same way
+        assert_eq!(data.sample_count(), 3);
+    }
+
+    #[test]
+    fn test_attributed_scheduler_events_charge_the_blocker() {
+        let mut data = create_test_data();
+        let mut events = (*data.events).clone();
+        for wait_ms in [20, 30] {
+            events.push(TraceEvent {
+                name: "function_b".to_string(),
+                worker_id: 1,
+                tid: 101,
+                timestamp: 4.0,
+                cpu: 1,
+                detection_method: Some(2),
+                file: Some("src/b.rs".to_string()),
+                line: Some(20),
+                call_stack: None,
+                duration_ns: Some(wait_ms * 1_000_000),
+                task_id: None,
+                blocker: Some(crate::trace_data::Blocker {
+                    tid: 100,
+                    samples: 2,
+                    comm: None,
+                    kernel_stack: None,
+                }),
+            });
+        }
+        data.events = Arc::new(events);
+
+        let hotspots = analyze_hotspots(&data);
+        let func_b = hotspots.iter().find(|h| h.name == "function_b").unwrap();
+        assert_eq!(func_b.count, 1);
+        assert_eq!(func_b.sched_waits, 2);
+        assert_eq!(func_b.sched_wait_ns, 50_000_000);
+
+        let mut stats = HotspotStats::new();
+        for event in data.events.iter() {
+            stats.record_event(event);
+        }
+        let streamed = stats.to_hotspots();
+        let func_b = streamed.iter().find(|h| h.name == "function_b").unwrap();
+        assert_eq!(func_b.sched_waits, 2);
+        assert_eq!(func_b.sched_wait_ns, 50_000_000);
+        assert_eq!(stats.total_samples(), 3);
+        assert_eq!(stats.sched_waits_over(20_000_000), 1);
+        assert_eq!(stats.worker_samples()[&0], 2);
+    }
+
+    #[test]
+    fn test_attribution_modes_charge_different_frames() {
+        use crate::classification::FrameOrigin;
+        use crate::testing::{frame, sample};
+
+        // Innermost first
+        let sample = |name: &str, stack: &[&str]| TraceEvent {
+            call_stack: Some(Arc::new(
+                stack.iter().map(|f| frame(f, FrameOrigin::UserCode)).collect(),
+            )),
+            ..sample(name, 0)
+        };
+        let data = TraceData {
+            events: Arc::new(vec![
+                sample("my_handler", &["bcrypt::hash", "my_handler", "main"]),
+                sample("my_handler", &["bcrypt::hash", "my_handler", "main"]),
+                sample("my_handler", &["my_handler", "main"]),
+                sample("walk", &["walk", "walk", "main"]),
+            ]),
+            workers: Arc::new(vec![0]),
+            duration: 1.0,
+        };
+
+        let user = analyze_hotspots_by(&data, Attribution::UserFrame);
+        assert_eq!((user[0].name.as_str(), user[0].count), ("my_handler", 3));
+
+        let leaf = analyze_hotspots_by(&data, Attribution::Leaf);
+        assert_eq!((leaf[0].name.as_str(), leaf[0].count), ("bcrypt::hash", 2));
+
+        let inclusive = analyze_hotspots_by(&data, Attribution::Inclusive);
+        let find = |name: &str| inclusive.iter().find(|h| h.name == name).unwrap();
+        assert_eq!(inclusive[0].name, "main");
+        assert!((inclusive[0].percentage - 100.0).abs() < 0.01);
+        assert_eq!((find("my_handler").count, find("my_handler").self_count), (3, 1));
+        // Recursion counts once per sample
+        assert_eq!((find("walk").count, find("walk").self_count), (1, 1));
+
+        // Switching the streaming ranking rebuilds it from the session
+        let mut stats = HotspotStats::new();
+        for event in data.events.iter() {
+            stats.record_event(event);
+        }
+        assert!(stats.to_hotspots_by(Attribution::Leaf).is_empty());
+        stats.set_attribution(Attribution::Leaf, &data.events);
+        let streamed = stats.to_hotspots_by(Attribution::Leaf);
+        assert_eq!((streamed[0].name.as_str(), streamed[0].count), ("bcrypt::hash", 2));
+    }
 }
@@ -3,6 +3,12 @@
 //! This module contains pure business logic for analyzing profiling traces,
 //! separated from the TUI presentation layer.
 
+pub mod butterfly_analyzer;
 pub mod hotspot_analyzer;
+pub mod task_analyzer;
 
-pub use hotspot_analyzer::{analyze_hotspots, FunctionHotspot, HotspotStats};
+pub use butterfly_analyzer::{analyze_butterfly, Butterfly, ButterflyStats, Neighbor};
+pub use hotspot_analyzer::{
+    analyze_hotspots, analyze_hotspots_by, Attribution, FunctionHotspot, HotspotStats,
+};
+pub use task_analyzer::{analyze_tasks, TaskHotspot, TaskStats};
@@ -0,0 +1,231 @@
+//! Per-task analysis for profiling data.
+//!
+//! Groups samples by Tokio task ID to tell a single runaway task apart from a
+//! systemic problem. If one task owns most blocking samples, fix that task; if
+//! blocking is spread across hundreds of tasks, the problem is in shared code
+//! (see hotspots).
+//!
+//! # Architecture
+//!
+//! Mirrors [`hotspot_analyzer`](super::hotspot_analyzer):
+//!
+//! - **`TaskStats`** - Efficient aggregation as events stream in
+//! - **`analyze_tasks()`** - Batch analysis from a `TraceData` snapshot
+//!
+//! Task IDs come from the `set_current_task_id` uprobe. When it could not be
+//! attached, every sample is unattributed and the task view stays empty.
+
+// Percentage calculations intentionally convert usize to f64
+#![allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+
+use crate::trace_data::{TraceData, TraceEvent};
+use std::collections::{HashMap, HashSet};
+
+// =============================================================================
+// TASK HOTSPOT (OUTPUT TYPE)
+// =============================================================================
+
+/// A Tokio task with aggregated blocking statistics.
+///
+/// # Display in TUI
+///
+/// ```text
+/// [ TASKS ]
+/// Task      Samples   Share  Top function
+/// #4127        312    61.2%  myapp::handlers::resize_image
+/// #88           41     8.0%  myapp::db::query
+/// ```
+#[derive(Debug, Clone)]
+pub struct TaskHotspot {
+    /// Tokio task ID (`tokio::task::Id`).
+    pub task_id: u64,
+
+    /// Blocking samples taken while this task was running.
+    pub count: usize,
+
+    /// Percentage of all blocking samples (0.0 - 100.0), including samples
+    /// with no known task.
+    pub percentage: f64,
+
+    /// Workers this task was sampled on (tasks migrate via work stealing).
+    pub workers: HashSet<u32>,
+
+    /// Function with the most samples in this task.
+    pub top_function: String,
+
+    /// Scheduler latency events where this task was the one kept waiting.
+    pub sched_waits: usize,
+}
+
+// =============================================================================
+// TASK STATS (STREAMING AGGREGATOR)
+// =============================================================================
+
+/// Aggregates per-task statistics as events stream in.
+#[derive(Debug, Default)]
+pub struct TaskStats {
+    /// Per-task statistics, keyed by task ID.
+    tasks: HashMap<u64, TaskEntry>,
+
+    /// Total blocking samples, with or without a task ID.
+    total_samples: u64,
+
+    /// Blocking samples with no task ID (uprobe missing, or between tasks).
+    unattributed_samples: u64,
+}
+
+/// Internal statistics for a single task.
+#[derive(Debug, Default)]
+struct TaskEntry {
+    count: u64,
+    workers: HashSet<u32>,
+    /// Function name → sample count, for picking the top function.
+    functions: HashMap<String, u64>,
+    sched_waits: u64,
+}
+
+impl TaskStats {
+    /// Create an empty aggregator.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a single event.
+    ///
+    /// Counts the same samples as `HotspotStats`: "execution" events carry no
+    /// blocking function and are skipped. Scheduler latency events are
+    /// intervals, so they only bump the waiting task's `sched_waits`.
+    pub fn record_event(&mut self, event: &TraceEvent) {
+        if event.is_scheduler_event() {
+            if let Some(task_id) = event.task_id {
+                self.tasks.entry(task_id).or_default().sched_waits += 1;
+            }
+            return;
+        }
+
+        if event.name == "execution" {
+            return;
+        }
+        self.total_samples += 1;
+
+        let Some(task_id) = event.task_id else {
+            self.unattributed_samples += 1;
+            return;
+        };
+
+        let entry = self.tasks.entry(task_id).or_default();
+        entry.count += 1;
+        entry.workers.insert(event.worker_id);
+        *entry.functions.entry(event.name.clone()).or_insert(0) += 1;
+    }
+
+    /// Total blocking samples recorded.
+    #[must_use]
+    pub fn total_samples(&self) -> u64 {
+        self.total_samples
+    }
+
+    /// Blocking samples that could not be attributed to a task.
+    #[must_use]
+    pub fn unattributed_samples(&self) -> u64 {
+        self.unattributed_samples
+    }
+
+    /// Convert to a sorted list of tasks (most blocking samples first).
+    #[must_use]
+    pub fn to_tasks(&self) -> Vec<TaskHotspot> {
+        let total = self.total_samples;
+
+        let mut tasks: Vec<TaskHotspot> = self
+            .tasks
+            .iter()
+            .map(|(&task_id, entry)| {
+                let percentage =
+                    if total > 0 { (entry.count as f64 / total as f64) * 100.0 } else { 0.0 };
+                // Ties broken by name so the display is stable between refreshes
+                let top_function = entry
+                    .functions
+                    .iter()
+                    .max_by(|a, b| a.1.cmp(b.1).then_with(|| b.0.cmp(a.0)))
+                    .map(|(name, _)| name.clone())
+                    .unwrap_or_default();
+                TaskHotspot {
+                    task_id,
+                    count: entry.count as usize,
+                    percentage,
+                    workers: entry.workers.clone(),
+                    top_function,
+                    sched_waits: entry.sched_waits as usize,
+                }
+            })
+            .collect();
+
+        tasks.sort_unstable_by(|a, b| {
+            b.count
+                .cmp(&a.count)
+                .then(b.sched_waits.cmp(&a.sched_waits))
+                .then(a.task_id.cmp(&b.task_id))
+        });
+        tasks
+    }
+}
+
+/// Analyze trace data by task (batch analysis).
+///
+/// Used when the TUI shows a rolling window and the streaming aggregator
+/// (which covers the whole session) does not apply.
+#[must_use]
+pub fn analyze_tasks(data: &TraceData) -> TaskStats {
+    let mut stats = TaskStats::new();
+    for event in data.events.iter() {
+        stats.record_event(event);
+    }
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::sample;
+
+    /// A sample on `worker_id` polled by `task_id`
+    fn task_sample(name: &str, worker_id: u32, task_id: Option<u64>) -> TraceEvent {
+        TraceEvent { task_id, ..sample(name, worker_id) }
+    }
+
+    #[test]
+    fn ranks_tasks_by_blocking_samples() {
+        let mut stats = TaskStats::new();
+        stats.record_event(&task_sample("resize", 0, Some(7)));
+        stats.record_event(&task_sample("resize", 1, Some(7)));
+        stats.record_event(&task_sample("encode", 1, Some(7)));
+        stats.record_event(&task_sample("query", 0, Some(3)));
+        stats.record_event(&task_sample("execution", 0, Some(3))); // not blocking
+        stats.record_event(&task_sample("parse", 0, None));
+
+        let tasks = stats.to_tasks();
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(tasks[0].task_id, 7);
+        assert_eq!(tasks[0].count, 3);
+        assert_eq!(tasks[0].top_function, "resize");
+        assert_eq!(tasks[0].workers.len(), 2);
+        assert!((tasks[0].percentage - 60.0).abs() < 0.01);
+        assert_eq!(stats.unattributed_samples(), 1);
+        assert_eq!(stats.total_samples(), 5);
+    }
+
+    #[test]
+    fn scheduler_events_count_as_waits_not_samples() {
+        let mut stats = TaskStats::new();
+        let mut wait = task_sample("execution", 0, Some(9));
+        wait.duration_ns = Some(20_000_000);
+        stats.record_event(&wait);
+
+        let tasks = stats.to_tasks();
+        assert_eq!(stats.total_samples(), 0);
+        assert_eq!(tasks[0].task_id, 9);
+        assert_eq!(tasks[0].count, 0);
+        assert_eq!(tasks[0].sched_waits, 1);
+    }
+}
@@ -17,6 +17,10 @@
 //!    - `tokio::`, `async_std::`, `futures::` → Async runtime
 //!
 //! 3. **Memory range** - Last resort for unresolved frames
+//!
+//! Frames outside the main executable come from shared libraries (libc,
+//! libssl, ...). They are classified by function prefix when they are Rust
+//! (a `dylib` build), and as `SystemLib` otherwise.
 
 use log::warn;
 use std::collections::HashSet;
@@ -39,6 +43,10 @@ pub enum FrameOrigin {
     RuntimeLib,
     /// Other third-party crates from cargo registry
     ThirdParty,
+    /// Native shared library outside the executable (libc, libssl, ...)
+    SystemLib,
+    /// Linux kernel (resolved via `/proc/kallsyms`)
+    Kernel,
     /// Could not determine origin (no debug info, raw address)
     #[default]
     Unknown,
@@ -53,6 +61,20 @@ impl FrameOrigin {
     pub fn is_user_code(&self) -> bool {
         matches!(self, FrameOrigin::UserCode)
     }
+
+    /// Short lowercase name, used as the frame category in exports
+    #[must_use]
+    pub fn category(&self) -> &'static str {
+        match self {
+            FrameOrigin::UserCode => "user",
+            FrameOrigin::StdLib => "std",
+            FrameOrigin::RuntimeLib => "runtime",
+            FrameOrigin::ThirdParty => "third_party",
+            FrameOrigin::SystemLib => "system_lib",
+            FrameOrigin::Kernel => "kernel",
+            FrameOrigin::Unknown => "unknown",
+        }
+    }
 }
 
 /// Classify a stack frame based on its function name and file path.
@@ -92,6 +114,13 @@ pub fn classify_frame(function: &str, file: Option<&str>, in_executable: bool) -
         return FrameOrigin::Unknown;
     }
 
+    // === SHARED LIBRARY ===
+    // Source paths of system libraries (e.g. glibc's relative `./nptl/...`)
+    // say nothing about ownership, so only trust the function prefix here
+    if !in_executable {
+        return classify_by_function_prefix(function).unwrap_or(FrameOrigin::SystemLib);
+    }
+
     // === FILE PATH CLASSIFICATION ===
     // File paths are the most reliable signal when debug info is available
     if let Some(path) = file {
@@ -156,15 +185,9 @@ pub fn classify_frame(function: &str, file: Option<&str>, in_executable: bool) -
     }
 
     // === MEMORY RANGE FALLBACK ===
-    // If we couldn't classify by name/path, use memory location
-    if in_executable {
-        // Inside main executable but couldn't identify - assume user code
-        // This is the "optimistic" fallback for statically linked code
-        return FrameOrigin::UserCode;
-    }
-
-    // Outside main executable (shared library)
-    FrameOrigin::Unknown
+    // Inside main executable but couldn't identify - assume user code
+    // This is the "optimistic" fallback for statically linked code
+    FrameOrigin::UserCode
 }
 
 // =============================================================================
@@ -391,6 +414,16 @@ mod tests {
         assert_eq!(origin, FrameOrigin::Unknown);
     }
 
+    #[test]
+    fn test_shared_library_frames() {
+        // glibc with debug info: relative source path must not read as user code
+        let origin = classify_frame("__lll_lock_wait", Some("./nptl/lowlevellock.c"), false);
+        assert_eq!(origin, FrameOrigin::SystemLib);
+        assert_eq!(classify_frame("SSL_read", None, false), FrameOrigin::SystemLib);
+        // Rust dylib still classified by prefix
+        assert_eq!(classify_frame("std::fs::read", None, false), FrameOrigin::StdLib);
+    }
+
     #[test]
     fn test_fallback_to_user_code() {
         // Unknown function but inside executable - assume user code
@@ -1,8 +1,13 @@
 //! CLI argument definitions
 
-use clap::Parser;
+use clap::{Args as ClapArgs, Parser, Subcommand};
 use std::path::PathBuf;
 
+use crate::diff::DiffFormat;
+use crate::export::{ExportFormat, ProfileGroup};
+use crate::report::budget::Assertion;
+use crate::report::ReportFormat;
+
 #[derive(Parser)]
 #[command(
     name = "hud",
@@ -12,6 +17,16 @@ EXAMPLES:
     sudo hud my-app                          Auto-detect PID and binary
     sudo hud --pid 1234                      Explicit PID, auto-detect binary
     sudo hud --pid 1234 --target ./myapp     Explicit PID and binary
+    sudo hud my-app --debug-dir /srv/debug   Stripped binary, debug files kept on the side
+    sudo hud my-app --export trace.pftrace --format perfetto
+                                             Perfetto protobuf trace for ui.perfetto.dev
+    sudo hud my-app --export out.folded --format folded --group-by worker
+                                             Per-worker flame graphs for inferno/flamegraph.pl
+    sudo hud my-app --headless --duration 60 --assert max-share=10
+                                             Exit with code 3 if any function exceeds 10% of samples
+    sudo hud record -o session.hud my-app    Profile and save the session
+    hud replay session.hud                   Browse a saved session (no root needed)
+    hud diff before.json after.json          Compare blocking before and after a fix
 
 THRESHOLD GUIDE:
     1ms     Low-latency (games, fintech, real-time APIs). At 50k req/s, 1ms blocks 50 requests.
@@ -20,10 +35,69 @@ THRESHOLD GUIDE:
     50ms+   Finding only severe blocks. Useful for initial debugging.
 
     Lower = more sensitive (more events, potential noise)
-    Higher = less sensitive (only obvious problems)"
+    Higher = less sensitive (only obvious problems)",
+    args_conflicts_with_subcommands = true
 )]
 pub struct Args {
-    /// Process name to profile (auto-detects PID and binary)
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    #[command(flatten)]
+    pub profile: ProfileArgs,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Profile a process and save the session for `hud replay`
+    Record {
+        /// Session file to write
+        #[arg(short, long, value_name = "FILE")]
+        output: PathBuf,
+
+        #[command(flatten)]
+        profile: ProfileArgs,
+    },
+
+    /// Open a recorded session in the TUI (no root or live process needed)
+    Replay {
+        /// Session file written by `hud record`
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+
+        /// Playback speed relative to the recording (0 = load instantly)
+        #[arg(long, default_value = "0", value_name = "FACTOR")]
+        speed: f64,
+
+        /// Rolling time window in seconds (omit for all data)
+        #[arg(long, default_value = "0", value_name = "SECS")]
+        window: u64,
+    },
+
+    /// Compare two captures (Chrome trace exports or recordings) per function
+    Diff {
+        /// Capture before the change
+        #[arg(value_name = "BEFORE")]
+        before: PathBuf,
+
+        /// Capture after the change
+        #[arg(value_name = "AFTER")]
+        after: PathBuf,
+
+        /// Output format
+        #[arg(long, value_enum, default_value_t, value_name = "FORMAT")]
+        format: DiffFormat,
+
+        /// Also write a differential folded file for flamegraph.pl or inferno
+        #[arg(long, value_name = "FILE")]
+        flamegraph: Option<PathBuf>,
+    },
+}
+
+/// Options for profiling a live process
+#[derive(ClapArgs)]
+pub struct ProfileArgs {
+    /// Process name to profile (auto-detects PID and binary); a process
+    /// named record, replay or diff is read as a subcommand, so pass its --pid
     #[arg(value_name = "PROCESS")]
     pub process: Option<String>,
 
@@ -39,12 +113,42 @@ pub struct Args {
     #[arg(long, value_name = "FILE")]
     pub export: Option<PathBuf>,
 
+    /// Format of the --export file
+    #[arg(long, value_enum, default_value_t, value_name = "FORMAT")]
+    pub format: ExportFormat,
+
+    /// Split folded/speedscope exports into one profile per worker or task
+    #[arg(long, value_enum, default_value_t, value_name = "GROUP")]
+    pub group_by: ProfileGroup,
+
+    /// Drop async runtime frames from folded/speedscope exports
+    #[arg(long)]
+    pub hide_runtime: bool,
+
+    /// Write a summary report (hotspot ranking, diagnostics) when profiling ends
+    #[arg(long, value_name = "FILE")]
+    pub report: Option<PathBuf>,
+
+    /// Format of the --report file
+    #[arg(long, value_enum, default_value_t, value_name = "FORMAT")]
+    pub report_format: ReportFormat,
+
+    /// With --report-format junit, fail hotspots with at least this share of samples
+    #[arg(long, default_value = "10", value_name = "PERCENT")]
+    pub junit_threshold: f64,
+
+    /// Blocking budget checked when profiling ends; exits with code 3 if any
+    /// fails (repeatable): max-share=PCT, forbid=PATTERN,
+    /// max-sched-waits=N@MS, max-imbalance=RATIO
+    #[arg(long = "assert", value_name = "RULE")]
+    pub assertions: Vec<Assertion>,
+
     /// Stop profiling after N seconds (omit for unlimited)
     #[arg(long, default_value = "0", value_name = "SECS")]
     pub duration: u64,
 
-    /// Run without TUI (requires --export)
-    #[arg(long, requires = "export")]
+    /// Run without TUI (requires --export, --report, --assert or `hud record`)
+    #[arg(long)]
     pub headless: bool,
 
     /// Suppress non-essential output
@@ -62,4 +166,8 @@ pub struct Args {
     /// Thread name prefix for worker discovery (auto-detected if omitted)
     #[arg(long, value_name = "PATTERN")]
     pub workers: Option<String>,
+
+    /// Directory of separate debug files, keyed by build ID (repeatable)
+    #[arg(long = "debug-dir", value_name = "DIR")]
+    pub debug_dirs: Vec<PathBuf>,
 }
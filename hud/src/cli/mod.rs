@@ -4,4 +4,4 @@
 
 pub mod args;
 
-pub use args::Args;
+pub use args::{Args, Command, ProfileArgs};
@@ -0,0 +1,266 @@
+//! Loading captures for `hud diff`
+//!
+//! Both inputs reduce to CPU samples per function plus folded stacks. A
+//! sample is named after its first user-code frame (the innermost frame when
+//! there is none), as the TUI and `--report` name hotspots, so a Chrome
+//! export and a recording of the same session produce the same functions.
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::Value as JsonValue;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{BufReader, Read, Seek};
+use std::path::Path;
+
+use crate::export::folded::push_frame;
+use crate::recording::{Record, SessionReader, MAGIC};
+
+/// Captures shorter than this count as this long, so a handful of events
+/// does not produce absurd rates
+const MIN_DURATION_SECS: f64 = 1.0;
+
+/// A function as matched between captures
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FunctionKey {
+    pub name: String,
+    pub file: Option<String>,
+}
+
+/// CPU samples of one capture
+#[derive(Debug, Default)]
+pub struct Capture {
+    /// Wall time from first to last event
+    pub duration_secs: f64,
+    pub total_samples: u64,
+    /// Samples per function
+    pub functions: HashMap<FunctionKey, u64>,
+    /// Samples per folded stack (outermost frame first, `;`-separated)
+    pub stacks: BTreeMap<String, u64>,
+}
+
+impl Capture {
+    /// Load a Chrome trace export (`--export`) or a recording (`hud record`)
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be read or is neither format
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut file =
+            File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+        let mut magic = [0; 6];
+        let is_recording = file.read_exact(&mut magic).is_ok() && &magic == MAGIC;
+
+        if is_recording {
+            return Self::from_recording(SessionReader::open(path)?);
+        }
+        file.rewind().with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::from_chrome_trace(BufReader::new(file)).with_context(|| {
+            format!(
+                "{} is neither a hud recording nor a Chrome trace export \
+                 (other --format exports cannot be compared)",
+                path.display()
+            )
+        })
+    }
+
+    /// Read the samples of a recording
+    ///
+    /// # Errors
+    /// Returns an error if a record is corrupt
+    pub fn from_recording<R: Read>(mut reader: SessionReader<R>) -> Result<Self> {
+        let mut capture = Self::default();
+        let mut span = Span::default();
+
+        while let Some(record) = reader.next_record()? {
+            let Record::Event { trace, .. } = record else {
+                continue;
+            };
+            span.add(trace.timestamp);
+            // Scheduler waits are intervals, not samples
+            if trace.duration_ns.is_some() || trace.name == "execution" {
+                continue;
+            }
+
+            let folded = trace
+                .call_stack
+                .as_ref()
+                .map(|stack| fold(stack.iter().rev().map(|f| &*f.function)));
+            capture.add_sample(FunctionKey { name: trace.name, file: trace.file }, folded);
+        }
+
+        capture.duration_secs = span.secs();
+        Ok(capture)
+    }
+
+    /// Read the samples of a Chrome trace export
+    ///
+    /// # Errors
+    /// Returns an error if the input is not valid trace JSON
+    pub fn from_chrome_trace<R: Read>(input: R) -> Result<Self> {
+        let trace: ChromeTrace = serde_json::from_reader(input).context("Invalid trace JSON")?;
+        let mut capture = Self::default();
+        let mut span = Span::default();
+        // Folded stack per `sf` leaf id
+        let mut folded_stacks: HashMap<u64, String> = HashMap::new();
+
+        for event in trace.trace_events {
+            // Metadata, counters and bookmarks are not samples
+            if event.ph == "M" || event.ph == "C" || event.cat == "bookmark" {
+                continue;
+            }
+            span.add(event.ts / 1_000_000.0);
+            if event.cat != "execution" || event.name == "execution" {
+                continue;
+            }
+
+            let file = event
+                .args
+                .as_ref()
+                .and_then(|args| args.get("file"))
+                .and_then(JsonValue::as_str)
+                .map(str::to_string);
+            // Events are named after the same frame as in recordings, and
+            // `args.file` is that frame's file
+            let key = FunctionKey { name: event.name, file };
+            let folded = event.sf.map(|sf| {
+                folded_stacks
+                    .entry(sf)
+                    .or_insert_with(|| fold_stack(&trace.stack_frames, sf))
+                    .clone()
+            });
+            capture.add_sample(key, folded);
+        }
+
+        capture.duration_secs = span.secs();
+        Ok(capture)
+    }
+
+    fn add_sample(&mut self, key: FunctionKey, folded: Option<String>) {
+        self.total_samples += 1;
+        *self.functions.entry(key).or_default() += 1;
+        if let Some(folded) = folded.filter(|f| !f.is_empty()) {
+            *self.stacks.entry(folded).or_default() += 1;
+        }
+    }
+}
+
+/// Fold the `stackFrames` chain ending at `leaf`
+fn fold_stack(frames: &BTreeMap<u64, ChromeStackFrame>, leaf: u64) -> String {
+    // Innermost first; the parent chain cannot loop in a file hud wrote, but
+    // cap it in case it was edited
+    let mut chain = Vec::new();
+    let mut id = Some(leaf);
+    while let Some(frame) = id.and_then(|id| frames.get(&id)) {
+        chain.push(frame);
+        id = frame.parent;
+        if chain.len() > frames.len() {
+            break;
+        }
+    }
+    fold(chain.iter().rev().map(|f| f.name.as_str()))
+}
+
+/// Join frames, outermost first, into a folded stack
+fn fold<'a>(frames: impl Iterator<Item = &'a str>) -> String {
+    let mut line = String::new();
+    for frame in frames {
+        push_frame(&mut line, frame);
+    }
+    line
+}
+
+/// First and last event time, in seconds
+#[derive(Default)]
+struct Span(Option<(f64, f64)>);
+
+impl Span {
+    fn add(&mut self, secs: f64) {
+        let (first, last) = self.0.get_or_insert((secs, secs));
+        *first = first.min(secs);
+        *last = last.max(secs);
+    }
+
+    fn secs(&self) -> f64 {
+        self.0.map_or(0.0, |(first, last)| last - first).max(MIN_DURATION_SECS)
+    }
+}
+
+/// The parts of a Chrome trace export `hud diff` reads
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ChromeTrace {
+    trace_events: Vec<ChromeEvent>,
+    #[serde(default)]
+    stack_frames: BTreeMap<u64, ChromeStackFrame>,
+}
+
+#[derive(Deserialize)]
+struct ChromeEvent {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    cat: String,
+    ph: String,
+    #[serde(default)]
+    ts: f64,
+    args: Option<HashMap<String, JsonValue>>,
+    sf: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct ChromeStackFrame {
+    name: String,
+    parent: Option<u64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::classification::FrameOrigin;
+    use crate::export::TraceEventExporter;
+    use crate::profiling::event_processor::build_trace_event;
+    use crate::recording::{SessionConfig, SessionRecorder};
+    use crate::testing::{frame_at, worker_event};
+    use hud_common::TRACE_EXECUTION_START;
+    use std::sync::Arc;
+
+    #[test]
+    fn recording_and_chrome_export_of_one_session_match() {
+        // The user frame is not the leaf: both name the sample after it
+        let stack = Arc::new(vec![
+            frame_at("write", "io.rs", 7, FrameOrigin::StdLib),
+            frame_at("app::flush", "src/flush.rs", 7, FrameOrigin::UserCode),
+            frame_at("main", "src/main.rs", 7, FrameOrigin::UserCode),
+        ]);
+        let config = SessionConfig {
+            hud_version: "0.0.0".into(),
+            pid: 1000,
+            target: "/srv/app".into(),
+            threshold_ms: 5,
+            window_secs: 0,
+            started_at_ms: 0,
+            workers: vec![],
+        };
+
+        let mut exporter = TraceEventExporter::new();
+        let mut recorder = SessionRecorder::new(Vec::new(), &config).unwrap();
+        for i in 0..10 {
+            let mut event = worker_event(TRACE_EXECUTION_START, 1_000_000_000 + i * 100_000_000, 0);
+            event.stack_id = 1;
+            exporter.add_event(&event, Some(&stack));
+            let trace = build_trace_event(&event, Some(Arc::clone(&stack)));
+            recorder.record_event(&event, &trace).unwrap();
+        }
+        let mut chrome = Vec::new();
+        exporter.export(&mut chrome).unwrap();
+        let recording = recorder.finish().unwrap();
+
+        let chrome = Capture::from_chrome_trace(chrome.as_slice()).unwrap();
+        let recording =
+            Capture::from_recording(SessionReader::new(recording.as_slice()).unwrap()).unwrap();
+        let key = FunctionKey { name: "app::flush".into(), file: Some("src/flush.rs".into()) };
+        assert_eq!(chrome.functions, HashMap::from([(key, 10)]));
+        assert_eq!(recording.functions, chrome.functions);
+        assert_eq!(recording.stacks, chrome.stacks);
+    }
+}
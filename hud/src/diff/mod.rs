@@ -0,0 +1,319 @@
+//! Compare two captures (`hud diff before after`)
+//!
+//! Each capture is a Chrome trace export or a recording (see [`capture`]).
+//! Functions are matched by name and source file and compared on two
+//! normalized measures:
+//!
+//! - **Rate**: samples per second of capture, so runs of different length
+//!   compare. Changes are classified on rate, since fixing one hotspot
+//!   should not make the others look worse.
+//! - **Share**: percentage of the capture's samples, as the TUI shows.
+//!
+//! The z-score treats both sample counts as Poisson; |z| >= 1.96 is a change
+//! unlikely to be noise at the 5% level. A differential folded file shows
+//! the same comparison per call stack.
+
+pub mod capture;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::fmt::Write as _;
+use std::io::Write;
+
+use crate::tui::truncate;
+
+pub use capture::{Capture, FunctionKey};
+
+/// |z| at or above this is significant (two-sided 5% level)
+pub const SIGNIFICANT_Z: f64 = 1.96;
+
+/// Output of `hud diff` (`--format`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum DiffFormat {
+    /// Table of changed functions
+    #[default]
+    Table,
+    /// Every function, as JSON
+    Json,
+}
+
+/// How a function changed between captures
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Change {
+    /// Only in the second capture
+    New,
+    /// Only in the first capture
+    Removed,
+    /// Significantly more samples per second
+    Grown,
+    /// Significantly fewer samples per second
+    Shrunk,
+    /// No significant change
+    Unchanged,
+}
+
+impl Change {
+    fn label(self) -> &'static str {
+        match self {
+            Change::New => "new",
+            Change::Removed => "removed",
+            Change::Grown => "grown",
+            Change::Shrunk => "shrunk",
+            Change::Unchanged => "unchanged",
+        }
+    }
+}
+
+/// Totals of one capture
+#[derive(Debug, Clone, Serialize)]
+pub struct CaptureSummary {
+    pub path: String,
+    pub duration_secs: f64,
+    pub samples: u64,
+    pub samples_per_sec: f64,
+}
+
+/// One function in both captures
+#[derive(Debug, Clone, Serialize)]
+pub struct FunctionDiff {
+    pub name: String,
+    pub file: Option<String>,
+    pub change: Change,
+    pub before: u64,
+    pub after: u64,
+    pub before_per_sec: f64,
+    pub after_per_sec: f64,
+    /// Share of the capture's samples, 0-100
+    pub before_pct: f64,
+    pub after_pct: f64,
+    /// Poisson z-score of the rate change; positive means more blocking
+    pub z: f64,
+    pub significant: bool,
+}
+
+/// Comparison of two captures
+#[derive(Debug, Clone, Serialize)]
+pub struct CaptureDiff {
+    pub before: CaptureSummary,
+    pub after: CaptureSummary,
+    /// Functions by size of the rate change, largest first
+    pub functions: Vec<FunctionDiff>,
+}
+
+impl CaptureDiff {
+    /// Compare `after` against `before`
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn new(before_path: &str, before: &Capture, after_path: &str, after: &Capture) -> Self {
+        let keys: BTreeSet<&FunctionKey> =
+            before.functions.keys().chain(after.functions.keys()).collect();
+
+        let mut functions: Vec<FunctionDiff> = keys
+            .into_iter()
+            .map(|key| {
+                let b = before.functions.get(key).copied().unwrap_or(0);
+                let a = after.functions.get(key).copied().unwrap_or(0);
+                let z = z_score(b, before.duration_secs, a, after.duration_secs);
+                let significant = z.abs() >= SIGNIFICANT_Z;
+                let change = match (b, a) {
+                    (0, _) => Change::New,
+                    (_, 0) => Change::Removed,
+                    _ if significant && z > 0.0 => Change::Grown,
+                    _ if significant => Change::Shrunk,
+                    _ => Change::Unchanged,
+                };
+                FunctionDiff {
+                    name: key.name.clone(),
+                    file: key.file.clone(),
+                    change,
+                    before: b,
+                    after: a,
+                    before_per_sec: b as f64 / before.duration_secs,
+                    after_per_sec: a as f64 / after.duration_secs,
+                    before_pct: percent(b, before.total_samples),
+                    after_pct: percent(a, after.total_samples),
+                    z,
+                    significant,
+                }
+            })
+            .collect();
+        functions.sort_by(|x, y| {
+            let dx = (x.after_per_sec - x.before_per_sec).abs();
+            let dy = (y.after_per_sec - y.before_per_sec).abs();
+            dy.total_cmp(&dx).then_with(|| x.name.cmp(&y.name))
+        });
+
+        Self { before: summary(before_path, before), after: summary(after_path, after), functions }
+    }
+
+    /// Write the comparison in `format`
+    ///
+    /// # Errors
+    /// Returns an error if writing to the output fails
+    pub fn write(&self, format: DiffFormat, writer: &mut dyn Write) -> Result<()> {
+        match format {
+            DiffFormat::Table => self.write_table(writer),
+            DiffFormat::Json => {
+                serde_json::to_writer_pretty(&mut *writer, self)
+                    .context("Failed to write diff JSON")?;
+                writeln!(writer).context("Failed to write diff JSON")?;
+                Ok(())
+            }
+        }
+    }
+
+    /// Changed functions as a table; unchanged ones are only counted
+    fn write_table(&self, writer: &mut dyn Write) -> Result<()> {
+        let changed: Vec<&FunctionDiff> =
+            self.functions.iter().filter(|f| f.change != Change::Unchanged).collect();
+        let name_width = changed.iter().map(|f| f.name.len()).max().unwrap_or(0).clamp(8, 60);
+
+        let mut out = String::new();
+        for (label, capture) in [("before", &self.before), ("after", &self.after)] {
+            let _ = writeln!(
+                out,
+                "{label:<7}{}  {:.1}s, {} samples ({:.1}/s)",
+                capture.path, capture.duration_secs, capture.samples, capture.samples_per_sec
+            );
+        }
+        out.push('\n');
+
+        if changed.is_empty() {
+            out += "no significant changes\n";
+        } else {
+            let _ = writeln!(
+                out,
+                "{:<9} {:<name_width$}  {:>9} {:>9} {:>8}  {:>15}  {:>6}",
+                "CHANGE", "FUNCTION", "BEFORE/s", "AFTER/s", "DELTA", "SHARE", "z"
+            );
+            for f in &changed {
+                let delta = if f.before_per_sec > 0.0 {
+                    format!("{:+.0}%", (f.after_per_sec / f.before_per_sec - 1.0) * 100.0)
+                } else {
+                    "-".to_string()
+                };
+                let share = format!("{:.1}% -> {:.1}%", f.before_pct, f.after_pct);
+                let mark = if f.significant { " *" } else { "" };
+                let _ = writeln!(
+                    out,
+                    "{:<9} {:<name_width$}  {:>9.2} {:>9.2} {delta:>8}  {share:>15}  {:>6.1}{mark}",
+                    f.change.label(),
+                    truncate(&f.name, name_width),
+                    f.before_per_sec,
+                    f.after_per_sec,
+                    f.z,
+                );
+            }
+        }
+
+        let unchanged = self.functions.len() - changed.len();
+        if unchanged > 0 {
+            let _ = writeln!(
+                out,
+                "\n{unchanged} unchanged functions not shown (--format json lists all)"
+            );
+        }
+        let _ = writeln!(out, "* significant: |z| >= {SIGNIFICANT_Z}");
+
+        writer.write_all(out.as_bytes()).context("Failed to write diff")?;
+        Ok(())
+    }
+}
+
+/// Write a differential folded file: each stack with its `before` and
+/// `after` sample counts, `before` scaled to the length of `after`.
+///
+/// `flamegraph.pl` and `inferno-flamegraph` draw it with widths from
+/// `after`, red where a stack grew and blue where it shrank.
+///
+/// # Errors
+/// Returns an error if writing to the output fails
+#[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+pub fn write_differential_folded(
+    before: &Capture,
+    after: &Capture,
+    writer: &mut dyn Write,
+) -> Result<()> {
+    let scale = after.duration_secs / before.duration_secs;
+    let stacks: BTreeSet<&String> = before.stacks.keys().chain(after.stacks.keys()).collect();
+    for stack in stacks {
+        let b = before.stacks.get(stack).map_or(0, |&n| (n as f64 * scale).round() as u64);
+        let a = after.stacks.get(stack).copied().unwrap_or(0);
+        writeln!(writer, "{stack} {b} {a}").context("Failed to write differential stacks")?;
+    }
+    Ok(())
+}
+
+/// z-score for the change from `b` events in `tb` seconds to `a` in `ta`,
+/// treating both counts as Poisson
+#[allow(clippy::cast_precision_loss)]
+fn z_score(b: u64, tb: f64, a: u64, ta: f64) -> f64 {
+    let (rb, ra) = (b as f64 / tb, a as f64 / ta);
+    let variance = b as f64 / (tb * tb) + a as f64 / (ta * ta);
+    if variance > 0.0 {
+        (ra - rb) / variance.sqrt()
+    } else {
+        0.0
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn percent(count: u64, total: u64) -> f64 {
+    if total > 0 {
+        count as f64 / total as f64 * 100.0
+    } else {
+        0.0
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn summary(path: &str, capture: &Capture) -> CaptureSummary {
+    CaptureSummary {
+        path: path.to_string(),
+        duration_secs: capture.duration_secs,
+        samples: capture.total_samples,
+        samples_per_sec: capture.total_samples as f64 / capture.duration_secs,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn capture(duration_secs: f64, functions: &[(&str, u64)]) -> Capture {
+        let mut capture = Capture { duration_secs, ..Capture::default() };
+        for &(name, samples) in functions {
+            capture.functions.insert(FunctionKey { name: name.to_string(), file: None }, samples);
+            capture.stacks.insert(format!("main;{name}"), samples);
+            capture.total_samples += samples;
+        }
+        capture
+    }
+
+    #[test]
+    fn test_diff_classifies_by_rate() {
+        // After ran twice as long: `steady` keeps its rate though its count doubles
+        let before = capture(60.0, &[("fixed", 300), ("steady", 100), ("slower", 50)]);
+        let after = capture(120.0, &[("steady", 200), ("slower", 400), ("added", 30)]);
+        let diff = CaptureDiff::new("before.json", &before, "after.json", &after);
+
+        let change = |name: &str| diff.functions.iter().find(|f| f.name == name).unwrap();
+        assert_eq!(change("fixed").change, Change::Removed);
+        assert_eq!(change("added").change, Change::New);
+        assert_eq!(change("slower").change, Change::Grown);
+        assert_eq!(change("steady").change, Change::Unchanged);
+        assert!((change("steady").z).abs() < 0.01);
+        assert_eq!(diff.functions[0].name, "fixed");
+    }
+
+    #[test]
+    fn test_differential_folded_scales_before() {
+        let before = capture(30.0, &[("a", 10)]);
+        let after = capture(60.0, &[("a", 5), ("b", 1)]);
+        let mut out = Vec::new();
+        write_differential_folded(&before, &after, &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "main;a 20 5\nmain;b 0 1\n");
+    }
+}
@@ -71,6 +71,11 @@ pub enum TuiError {
     Json(#[from] serde_json::Error),
 }
 
+/// Bad command-line arguments, reported with exit code 2
+#[derive(Error, Debug)]
+#[error("{0}")]
+pub struct UsageError(pub String);
+
 #[cfg(test)]
 mod tests {
     use super::*;
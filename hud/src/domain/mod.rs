@@ -11,4 +11,4 @@ pub mod types;
 // Re-export common types for convenience
 pub use types::{CpuId, Duration, FunctionName, Pid, StackId, Tid, Timestamp, WorkerId};
 
-pub use errors::{ExportError, ProfilerError, TuiError};
+pub use errors::{ExportError, ProfilerError, TuiError, UsageError};
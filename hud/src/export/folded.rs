@@ -0,0 +1,87 @@
+//! Folded stack export (`--format folded`)
+//!
+//! Brendan Gregg's collapsed format: one line per distinct stack, frames
+//! outermost first and separated by `;`, followed by the number of samples.
+//! Feed it to `inferno-flamegraph` or `flamegraph.pl`:
+//!
+//! ```text
+//! std::rt::lang_start;tokio::runtime::...;app::handler;app::hash 42
+//! ```
+//!
+//! With `--group-by`, the profile name (`Worker 0`, `Task 12`) is the root
+//! frame, so one file holds a flame graph per worker or task side by side.
+
+use anyhow::{Context, Result};
+use hud_common::{TaskEvent, TRACE_EXECUTION_START};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::sync::Arc;
+
+use super::{Exporter, StackOptions};
+use crate::trace_data::{Blocker, StackFrame};
+
+/// Folded stack exporter
+pub struct FoldedExporter {
+    options: StackOptions,
+    /// Sample count per folded stack, sorted so output is stable
+    counts: BTreeMap<String, u64>,
+    samples: usize,
+}
+
+impl FoldedExporter {
+    /// Create an empty folded stack exporter
+    #[must_use]
+    pub fn new(options: StackOptions) -> Self {
+        Self { options, counts: BTreeMap::new(), samples: 0 }
+    }
+}
+
+/// `;` separates frames, so it cannot appear inside one (array types such as
+/// `[u8; 32]` show up in Rust symbols)
+pub(crate) fn push_frame(line: &mut String, name: &str) {
+    if !line.is_empty() {
+        line.push(';');
+    }
+    line.extend(name.chars().map(|c| if c == ';' { ',' } else { c }));
+}
+
+impl Exporter for FoldedExporter {
+    fn add_event(&mut self, event: &TaskEvent, call_stack: Option<&Arc<Vec<StackFrame>>>) {
+        if event.event_type != TRACE_EXECUTION_START {
+            return;
+        }
+        let Some(stack) = call_stack else {
+            return;
+        };
+
+        let mut line = String::new();
+        if let Some(profile) = self.options.group_by.profile_name(event) {
+            push_frame(&mut line, &profile);
+        }
+        let root_len = line.len();
+        for frame in self.options.frames(stack) {
+            push_frame(&mut line, &frame.function);
+        }
+        // Nothing left once runtime frames are hidden
+        if line.len() == root_len {
+            return;
+        }
+
+        *self.counts.entry(line).or_default() += 1;
+        self.samples += 1;
+    }
+
+    /// Scheduler waits are not CPU samples and have no place in a flame graph
+    fn add_scheduler_event(&mut self, _event: &TaskEvent, _blocker: Option<(&Blocker, &str)>) {}
+
+    fn export(&self, writer: &mut dyn Write) -> Result<()> {
+        for (stack, count) in &self.counts {
+            writeln!(writer, "{stack} {count}").context("Failed to write folded stacks")?;
+        }
+        Ok(())
+    }
+
+    fn event_count(&self) -> usize {
+        self.samples
+    }
+}
@@ -1,8 +1,184 @@
 //! Trace export functionality
 //!
-//! This module provides functionality for exporting profiling data to various formats.
-//! Currently supports Trace Event Format for visualization in tools like Perfetto, Speedscope, and others.
+//! This module provides functionality for exporting profiling data to various formats:
+//!
+//! - [`trace_event`] - Chrome Trace Event Format (JSON), for Perfetto, Speedscope and `chrome://tracing`
+//! - [`perfetto`] - Perfetto native protobuf trace, compact for long sessions
+//! - [`pprof`] - Gzipped `profile.proto` for `go tool pprof` and profiling backends
+//! - [`folded`] - Folded stacks for `inferno` and `flamegraph.pl`
+//! - [`speedscope`] - Speedscope sampled profiles
+//!
+//! Every format implements [`Exporter`]; `--format` picks one via [`ExportFormat`].
 
+pub mod folded;
+pub mod perfetto;
+pub mod pprof;
+mod protobuf;
+pub mod speedscope;
 pub mod trace_event;
 
+pub use folded::FoldedExporter;
+pub use perfetto::PerfettoExporter;
+pub use pprof::PprofExporter;
+pub use speedscope::SpeedscopeExporter;
 pub use trace_event::TraceEventExporter;
+
+use anyhow::Result;
+use hud_common::TaskEvent;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::classification::FrameOrigin;
+use crate::trace_data::{Blocker, Bookmark, StackFrame};
+
+/// A sink for profiling events that writes one export file at the end
+pub trait Exporter {
+    /// Add an event; `call_stack` is its resolved stack, innermost frame first
+    fn add_event(&mut self, event: &TaskEvent, call_stack: Option<&Arc<Vec<StackFrame>>>);
+
+    /// Add a scheduler latency event and the blocker it was attributed to
+    ///
+    /// `blocker` is the culprit thread and the function it was running.
+    fn add_scheduler_event(&mut self, event: &TaskEvent, blocker: Option<(&Blocker, &str)>);
+
+    /// Add a bookmark set in the live view
+    ///
+    /// Only the trace formats have a time axis to put it on; the others
+    /// ignore it.
+    fn add_bookmark(&mut self, bookmark: &Bookmark) {
+        let _ = bookmark;
+    }
+
+    /// Write everything not yet drained and complete the file
+    ///
+    /// # Errors
+    /// Returns an error if encoding or writing to the output fails
+    fn export(&self, writer: &mut dyn Write) -> Result<()>;
+
+    /// Write out what can be written before the end, and stop holding it
+    ///
+    /// Called periodically with the writer [`export`](Self::export) will
+    /// complete, so long sessions stream to disk. Formats that can only be
+    /// written whole keep everything until `export`.
+    ///
+    /// # Errors
+    /// Returns an error if writing to the output fails
+    fn drain(&mut self, writer: &mut dyn Write) -> Result<()> {
+        let _ = writer;
+        Ok(())
+    }
+
+    /// Snapshot of what [`export`](Self::export) writes after the drained
+    /// events but a reader needs to make sense of them, if it changed since
+    /// the last call
+    ///
+    /// Written to [`checkpoint_path`] before each [`drain`](Self::drain), so
+    /// the file of a killed session can be repaired. Formats whose drained
+    /// output stands on its own return `None`.
+    ///
+    /// # Errors
+    /// Returns an error if encoding the snapshot fails
+    fn checkpoint(&mut self) -> Result<Option<Vec<u8>>> {
+        Ok(None)
+    }
+
+    /// Number of events collected
+    fn event_count(&self) -> usize;
+}
+
+/// Side file holding an export's latest [`Exporter::checkpoint`]
+#[must_use]
+pub fn checkpoint_path(export: &Path) -> PathBuf {
+    let mut path = export.as_os_str().to_owned();
+    path.push(".frames");
+    PathBuf::from(path)
+}
+
+/// Export file format (`--format`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum ExportFormat {
+    /// Chrome Trace Event JSON
+    #[default]
+    Chrome,
+    /// Perfetto protobuf trace (open in ui.perfetto.dev)
+    Perfetto,
+    /// Folded stacks, one `frame;frame;frame count` line per distinct stack
+    Folded,
+    /// Speedscope JSON (open in speedscope.app)
+    Speedscope,
+    /// Gzipped pprof `profile.proto` (for `go tool pprof`, Pyroscope, ...)
+    Pprof,
+}
+
+impl ExportFormat {
+    /// Create an empty exporter for this format
+    ///
+    /// `options` shape the flame graph formats (folded, speedscope); the
+    /// trace formats keep every event as-is.
+    #[must_use]
+    pub fn exporter(self, options: StackOptions) -> Box<dyn Exporter> {
+        match self {
+            ExportFormat::Chrome => Box::new(TraceEventExporter::new()),
+            ExportFormat::Perfetto => Box::new(PerfettoExporter::new()),
+            ExportFormat::Folded => Box::new(FoldedExporter::new(options)),
+            ExportFormat::Speedscope => Box::new(SpeedscopeExporter::new(options)),
+            ExportFormat::Pprof => Box::new(PprofExporter::new()),
+        }
+    }
+
+    /// True for the formats [`StackOptions`] apply to
+    #[must_use]
+    pub fn takes_stack_options(self) -> bool {
+        matches!(self, ExportFormat::Folded | ExportFormat::Speedscope)
+    }
+}
+
+/// How samples are split into profiles (`--group-by`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum ProfileGroup {
+    /// One profile for the whole process
+    #[default]
+    None,
+    /// One profile per Tokio worker
+    Worker,
+    /// One profile per task (samples without a task ID are grouped together)
+    Task,
+}
+
+impl ProfileGroup {
+    /// Name of the profile `event` belongs to, `None` when not grouping
+    #[must_use]
+    pub fn profile_name(self, event: &TaskEvent) -> Option<String> {
+        match self {
+            ProfileGroup::None => None,
+            ProfileGroup::Worker if event.worker_id == u32::MAX => {
+                Some("Other threads".to_string())
+            }
+            ProfileGroup::Worker => Some(format!("Worker {}", event.worker_id)),
+            ProfileGroup::Task if event.task_id == 0 => Some("No task".to_string()),
+            ProfileGroup::Task => Some(format!("Task {}", event.task_id)),
+        }
+    }
+}
+
+/// Options for the flame graph exporters
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StackOptions {
+    /// Split samples into per-worker or per-task profiles
+    pub group_by: ProfileGroup,
+    /// Drop async runtime frames (`FrameOrigin::RuntimeLib`), so user code
+    /// sits directly under the worker's entry point
+    pub hide_runtime: bool,
+}
+
+impl StackOptions {
+    /// Frames of `stack` to export, outermost first
+    pub(crate) fn frames(self, stack: &[StackFrame]) -> impl Iterator<Item = &StackFrame> {
+        let hide_runtime = self.hide_runtime;
+        stack
+            .iter()
+            .rev()
+            .filter(move |frame| !(hide_runtime && frame.origin == FrameOrigin::RuntimeLib))
+    }
+}
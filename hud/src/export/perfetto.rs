@@ -0,0 +1,468 @@
+//! Perfetto protobuf trace export (`--format perfetto`)
+//!
+//! Writes a `perfetto.protos.Trace`, the native format of ui.perfetto.dev.
+//! Packets are encoded as events arrive and streamed to disk on each
+//! [`drain`](Exporter::drain), and function names, frames and call stacks
+//! are interned once per trace, so hour-long sessions stay small and load
+//! fast.
+//!
+//! - **Threads**: a `TrackDescriptor` per thread, written before its first
+//!   packet and named "Worker N" for workers
+//! - **CPU samples**: `PerfSample` packets referencing an interned callstack
+//!   (Perfetto builds flame graphs from these)
+//! - **Scheduler waits**: `TrackEvent` slices on the waiting thread's track,
+//!   with the blocker as debug annotations
+//! - **Bookmarks**: instant `TrackEvent`s on a "Bookmarks" track
+//!
+//! Field numbers are from `protos/perfetto/trace/` in the Perfetto repository.
+
+// Timestamps are converted to milliseconds for display only
+#![allow(clippy::cast_precision_loss)]
+
+use anyhow::{Context, Result};
+use hud_common::{TaskEvent, EVENT_SCHEDULER_DETECTED, TRACE_EXECUTION_START};
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::sync::Arc;
+
+use super::protobuf::ProtoWriter;
+use super::Exporter;
+use crate::trace_data::{Blocker, Bookmark, StackFrame};
+
+/// Every packet is written on this sequence
+const SEQUENCE_ID: u64 = 1;
+
+/// `TracePacket.sequence_flags`
+const SEQ_INCREMENTAL_STATE_CLEARED: u64 = 1;
+const SEQ_NEEDS_INCREMENTAL_STATE: u64 = 2;
+
+/// `TrackEvent.Type`
+const TYPE_SLICE_BEGIN: u64 = 1;
+const TYPE_SLICE_END: u64 = 2;
+const TYPE_INSTANT: u64 = 3;
+
+/// Thread track UUIDs are the TID in this namespace
+const THREAD_TRACK_BASE: u64 = 0x6875_6400_0000_0000;
+
+/// UUID of the track bookmarks are drawn on, outside the thread namespace
+const BOOKMARK_TRACK: u64 = 0x6875_6401_0000_0000;
+
+/// Perfetto protobuf trace exporter
+#[derive(Default)]
+pub struct PerfettoExporter {
+    /// Encoded `Trace.packet` entries not yet drained, in arrival order
+    packets: ProtoWriter,
+    /// `InternedData` for entries first used by the next packet
+    interned: ProtoWriter,
+    /// Set once a packet has reset the sequence's interned state
+    state_cleared: bool,
+    /// Threads whose track descriptor has been written
+    threads: HashSet<(u32, u32)>,
+    /// Interning tables, each with its own ids starting at 1
+    function_ids: HashMap<String, u64>,
+    mapping_ids: HashMap<&'static str, u64>,
+    frame_ids: HashMap<(u64, u64), u64>,
+    callstack_ids: HashMap<Vec<u64>, u64>,
+    event_name_ids: HashMap<&'static str, u64>,
+    category_ids: HashMap<&'static str, u64>,
+    /// Callstack id by `Arc` address of a resolved stack. The `Arc` is held
+    /// so the address cannot be reused by a different stack.
+    stack_ids: HashMap<usize, (u64, Arc<Vec<StackFrame>>)>,
+    /// Set once the bookmark track's descriptor is written
+    has_bookmarks: bool,
+    event_count: usize,
+}
+
+impl PerfettoExporter {
+    /// Create an empty Perfetto exporter
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a `TracePacket`, carrying any interned data it introduces
+    fn write_packet(&mut self, timestamp_ns: u64, build: impl FnOnce(&mut ProtoWriter)) {
+        let interned = std::mem::take(&mut self.interned);
+        let flags = if self.state_cleared {
+            SEQ_NEEDS_INCREMENTAL_STATE
+        } else {
+            SEQ_INCREMENTAL_STATE_CLEARED | SEQ_NEEDS_INCREMENTAL_STATE
+        };
+        self.state_cleared = true;
+
+        self.packets.message(1, |packet| {
+            packet.varint(8, timestamp_ns); // timestamp
+            packet.varint(10, SEQUENCE_ID); // trusted_packet_sequence_id
+            packet.varint(13, flags); // sequence_flags
+            if !interned.is_empty() {
+                packet.bytes(12, interned.as_bytes()); // interned_data
+            }
+            build(packet);
+        });
+    }
+
+    fn intern_function(&mut self, name: &str) -> u64 {
+        if let Some(&iid) = self.function_ids.get(name) {
+            return iid;
+        }
+        let iid = self.function_ids.len() as u64 + 1;
+        self.function_ids.insert(name.to_string(), iid);
+        // InternedData.function_names: InternedString { iid, str }
+        self.interned.message(5, |s| {
+            s.varint(1, iid);
+            s.string(2, name);
+        });
+        iid
+    }
+
+    /// One pseudo-mapping per frame origin, so Perfetto can group frames
+    /// by user code, std, runtime, ...
+    fn intern_mapping(&mut self, category: &'static str) -> u64 {
+        if let Some(&iid) = self.mapping_ids.get(category) {
+            return iid;
+        }
+        let iid = self.mapping_ids.len() as u64 + 1;
+        self.mapping_ids.insert(category, iid);
+        // InternedData.mapping_paths: InternedString { iid, str }
+        self.interned.message(17, |s| {
+            s.varint(1, iid);
+            s.string(2, category);
+        });
+        // InternedData.mappings: Mapping { iid, path_string_ids }
+        self.interned.message(19, |m| {
+            m.varint(1, iid);
+            m.repeated_varints(7, &[iid]);
+        });
+        iid
+    }
+
+    fn intern_frame(&mut self, frame: &StackFrame) -> u64 {
+        let function = self.intern_function(&frame.function);
+        let mapping = self.intern_mapping(frame.origin.category());
+        if let Some(&iid) = self.frame_ids.get(&(function, mapping)) {
+            return iid;
+        }
+        let iid = self.frame_ids.len() as u64 + 1;
+        self.frame_ids.insert((function, mapping), iid);
+        // InternedData.frames: Frame { iid, function_name_id, mapping_id }
+        self.interned.message(6, |f| {
+            f.varint(1, iid);
+            f.varint(2, function);
+            f.varint(3, mapping);
+        });
+        iid
+    }
+
+    fn intern_stack(&mut self, stack: &Arc<Vec<StackFrame>>) -> u64 {
+        let key = Arc::as_ptr(stack) as usize;
+        if let Some((iid, _)) = self.stack_ids.get(&key) {
+            return *iid;
+        }
+
+        // Perfetto callstacks list the outermost frame first
+        let frames: Vec<u64> = stack.iter().rev().map(|frame| self.intern_frame(frame)).collect();
+        let iid = if let Some(&iid) = self.callstack_ids.get(&frames) {
+            iid
+        } else {
+            let iid = self.callstack_ids.len() as u64 + 1;
+            // InternedData.callstacks: Callstack { iid, frame_ids }
+            self.interned.message(7, |c| {
+                c.varint(1, iid);
+                c.repeated_varints(2, &frames);
+            });
+            self.callstack_ids.insert(frames, iid);
+            iid
+        };
+
+        self.stack_ids.insert(key, (iid, Arc::clone(stack)));
+        iid
+    }
+
+    fn intern_event_name(&mut self, name: &'static str) -> u64 {
+        if let Some(&iid) = self.event_name_ids.get(name) {
+            return iid;
+        }
+        let iid = self.event_name_ids.len() as u64 + 1;
+        self.event_name_ids.insert(name, iid);
+        // InternedData.event_names: EventName { iid, name }
+        self.interned.message(2, |n| {
+            n.varint(1, iid);
+            n.string(2, name);
+        });
+        iid
+    }
+
+    fn intern_category(&mut self, name: &'static str) -> u64 {
+        if let Some(&iid) = self.category_ids.get(name) {
+            return iid;
+        }
+        let iid = self.category_ids.len() as u64 + 1;
+        self.category_ids.insert(name, iid);
+        // InternedData.event_categories: EventCategory { iid, name }
+        self.interned.message(1, |c| {
+            c.varint(1, iid);
+            c.string(2, name);
+        });
+        iid
+    }
+
+    /// Describe `event`'s thread the first time it shows up, so its track
+    /// is named before use
+    fn see_thread(&mut self, event: &TaskEvent) {
+        if !self.threads.insert((event.pid, event.tid)) {
+            return;
+        }
+        self.packets.message(1, |packet| {
+            packet.varint(10, SEQUENCE_ID);
+            // TrackDescriptor { uuid, thread: ThreadDescriptor { pid, tid, thread_name } }
+            packet.message(60, |track| {
+                track.varint(1, thread_track(event.tid));
+                track.message(4, |thread| {
+                    thread.varint(1, u64::from(event.pid));
+                    thread.varint(2, u64::from(event.tid));
+                    if event.worker_id != u32::MAX {
+                        thread.string(5, &format!("Worker {}", event.worker_id));
+                    }
+                });
+            });
+        });
+    }
+}
+
+fn thread_track(tid: u32) -> u64 {
+    THREAD_TRACK_BASE | u64::from(tid)
+}
+
+/// `TrackEvent.debug_annotations`: `DebugAnnotation { name, <value> }`
+fn annotation(event: &mut ProtoWriter, name: &str, value: impl FnOnce(&mut ProtoWriter)) {
+    event.message(4, |a| {
+        a.string(10, name);
+        value(a);
+    });
+}
+
+impl Exporter for PerfettoExporter {
+    fn add_event(&mut self, event: &TaskEvent, call_stack: Option<&Arc<Vec<StackFrame>>>) {
+        match event.event_type {
+            TRACE_EXECUTION_START => {
+                self.see_thread(event);
+                self.event_count += 1;
+                // A sample without a stack has nothing to show in a flame graph
+                let Some(stack) = call_stack.filter(|stack| !stack.is_empty()) else {
+                    return;
+                };
+                let callstack_iid = self.intern_stack(stack);
+                self.write_packet(event.timestamp_ns, |packet| {
+                    // PerfSample { cpu, pid, tid, callstack_iid }
+                    packet.message(66, |sample| {
+                        sample.varint(1, u64::from(event.cpu_id));
+                        sample.varint(2, u64::from(event.pid));
+                        sample.varint(3, u64::from(event.tid));
+                        sample.varint(4, callstack_iid);
+                    });
+                });
+            }
+            EVENT_SCHEDULER_DETECTED => self.add_scheduler_event(event, None),
+            _ => {}
+        }
+    }
+
+    fn add_scheduler_event(&mut self, event: &TaskEvent, blocker: Option<(&Blocker, &str)>) {
+        self.see_thread(event);
+        self.event_count += 1;
+
+        let name = self.intern_event_name("sched_wait");
+        let category = self.intern_category("scheduler");
+        let track = thread_track(event.tid);
+
+        // The event fires when the worker gets the CPU back
+        let wait_began_ns = event.timestamp_ns.saturating_sub(event.duration_ns);
+        self.write_packet(wait_began_ns, |packet| {
+            packet.message(11, |e| {
+                e.repeated_varints(3, &[category]); // category_iids
+                e.varint(10, name); // name_iid
+                e.varint(9, TYPE_SLICE_BEGIN);
+                e.varint(11, track); // track_uuid
+
+                annotation(e, "worker_id", |a| a.varint(3, u64::from(event.worker_id)));
+                annotation(e, "duration_ms", |a| {
+                    a.double(5, event.duration_ns as f64 / 1_000_000.0);
+                });
+                annotation(e, "thread_state", |a| a.int64(4, event.thread_state));
+                if event.task_id != 0 {
+                    annotation(e, "task_id", |a| a.varint(3, event.task_id));
+                }
+                if let Some((blocker, function)) = blocker {
+                    annotation(e, "blocker_tid", |a| a.varint(3, u64::from(blocker.tid)));
+                    annotation(e, "blocker_samples", |a| {
+                        a.varint(3, u64::from(blocker.samples));
+                    });
+                    if let Some(ref comm) = blocker.comm {
+                        annotation(e, "blocker_comm", |a| a.string(6, comm));
+                    }
+                    if let Some(ref kernel_stack) = blocker.kernel_stack {
+                        let frames: Vec<&str> =
+                            kernel_stack.iter().map(|f| f.function.as_str()).collect();
+                        annotation(e, "blocker_kernel_stack", |a| {
+                            a.string(6, &frames.join(" <- "));
+                        });
+                    }
+                    if function != "execution" {
+                        annotation(e, "blocker", |a| a.string(6, function));
+                    }
+                }
+            });
+        });
+        self.write_packet(event.timestamp_ns, |packet| {
+            packet.message(11, |e| {
+                e.varint(9, TYPE_SLICE_END);
+                e.varint(11, track);
+            });
+        });
+    }
+
+    fn add_bookmark(&mut self, bookmark: &Bookmark) {
+        if !self.has_bookmarks {
+            self.has_bookmarks = true;
+            self.packets.message(1, |packet| {
+                packet.varint(10, SEQUENCE_ID);
+                // TrackDescriptor { uuid, name }
+                packet.message(60, |track| {
+                    track.varint(1, BOOKMARK_TRACK);
+                    track.string(2, "Bookmarks");
+                });
+            });
+        }
+        let category = self.intern_category("bookmark");
+        self.write_packet(bookmark.timestamp_ns(), |packet| {
+            packet.message(11, |e| {
+                e.repeated_varints(3, &[category]);
+                e.varint(9, TYPE_INSTANT);
+                e.varint(11, BOOKMARK_TRACK);
+                e.string(23, &bookmark.label); // name
+            });
+        });
+    }
+
+    fn export(&self, writer: &mut dyn Write) -> Result<()> {
+        writer.write_all(self.packets.as_bytes()).context("Failed to write Perfetto trace")
+    }
+
+    /// A trace is a plain sequence of packets, so everything written so far
+    /// can go out; interning state stays for the packets that follow
+    fn drain(&mut self, writer: &mut dyn Write) -> Result<()> {
+        let packets = std::mem::take(&mut self.packets);
+        writer.write_all(packets.as_bytes()).context("Failed to write Perfetto trace")
+    }
+
+    fn event_count(&self) -> usize {
+        self.event_count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::protobuf::decode::{all_bytes, bytes, decode, int, ints};
+    use super::*;
+    use crate::classification::FrameOrigin;
+    use crate::testing::{frame, worker_event as event};
+
+    #[test]
+    fn writes_threads_interned_samples_and_slices() {
+        let stack = Arc::new(vec![
+            frame("app::hash", FrameOrigin::UserCode),
+            frame("tokio::runtime::poll", FrameOrigin::RuntimeLib),
+        ]);
+        let mut exporter = PerfettoExporter::new();
+        exporter.add_event(&event(TRACE_EXECUTION_START, 1_000, 0), Some(&stack));
+        exporter.add_event(&event(TRACE_EXECUTION_START, 2_000, 0), Some(&stack));
+        exporter.add_event(&event(EVENT_SCHEDULER_DETECTED, 10_000_000, 6_000_000), None);
+        assert_eq!(exporter.event_count(), 3);
+
+        let mut out = Vec::new();
+        exporter.export(&mut out).unwrap();
+        let packets: Vec<_> = all_bytes(&decode(&out), 1).into_iter().map(decode).collect();
+        // Descriptor, two samples, slice begin and end
+        assert_eq!(packets.len(), 5);
+
+        let track = decode(bytes(&packets[0], 60).unwrap());
+        let thread = decode(bytes(&track, 4).unwrap());
+        assert_eq!(int(&thread, 2), Some(1001));
+        assert_eq!(bytes(&thread, 5), Some(&b"Worker 0"[..]));
+
+        // The first packet clears incremental state and carries the interned stack
+        assert_eq!(int(&packets[1], 13), Some(3));
+        let interned = decode(bytes(&packets[1], 12).unwrap());
+        let names: Vec<_> =
+            all_bytes(&interned, 5).into_iter().map(|s| bytes(&decode(s), 2).unwrap()).collect();
+        assert_eq!(names, [&b"tokio::runtime::poll"[..], b"app::hash"]);
+        let callstack = decode(bytes(&interned, 7).unwrap());
+        assert_eq!(ints(&callstack, 2), [1, 2]);
+
+        // The second sample reuses it
+        assert_eq!(int(&packets[2], 13), Some(2));
+        assert!(bytes(&packets[2], 12).is_none());
+        let sample = decode(bytes(&packets[2], 66).unwrap());
+        assert_eq!(int(&sample, 3), Some(1001));
+        assert_eq!(int(&sample, 4), Some(1));
+
+        // The wait is a slice that ends when the event fires
+        assert_eq!(int(&packets[3], 8), Some(4_000_000));
+        let begin = decode(bytes(&packets[3], 11).unwrap());
+        assert_eq!(int(&begin, 9), Some(TYPE_SLICE_BEGIN));
+        assert_eq!(int(&begin, 11), int(&track, 1));
+        assert_eq!(int(&packets[4], 8), Some(10_000_000));
+        let end = decode(bytes(&packets[4], 11).unwrap());
+        assert_eq!(int(&end, 9), Some(TYPE_SLICE_END));
+    }
+
+    #[test]
+    fn writes_bookmarks_on_their_own_track() {
+        let mut exporter = PerfettoExporter::new();
+        exporter.add_bookmark(&Bookmark { timestamp: 2.5, label: "deployed here".into() });
+
+        let mut out = Vec::new();
+        exporter.export(&mut out).unwrap();
+        let packets: Vec<_> = all_bytes(&decode(&out), 1).into_iter().map(decode).collect();
+        assert_eq!(packets.len(), 2);
+
+        let track = decode(bytes(&packets[0], 60).unwrap());
+        assert_eq!(bytes(&track, 2), Some(&b"Bookmarks"[..]));
+        assert_eq!(int(&packets[1], 8), Some(2_500_000_000));
+        let instant = decode(bytes(&packets[1], 11).unwrap());
+        assert_eq!(int(&instant, 9), Some(TYPE_INSTANT));
+        assert_eq!(int(&instant, 11), int(&track, 1));
+        assert_eq!(bytes(&instant, 23), Some(&b"deployed here"[..]));
+    }
+
+    #[test]
+    fn drained_trace_matches_whole_export() {
+        let stack = Arc::new(vec![frame("app::hash", FrameOrigin::UserCode)]);
+        let feed = |exporter: &mut PerfettoExporter, from: u64| {
+            for ts in from..from + 3 {
+                exporter.add_event(&event(TRACE_EXECUTION_START, ts * 1_000, 0), Some(&stack));
+            }
+        };
+
+        let mut whole = PerfettoExporter::new();
+        feed(&mut whole, 0);
+        feed(&mut whole, 3);
+        let mut expected = Vec::new();
+        whole.export(&mut expected).unwrap();
+
+        let mut streamed = PerfettoExporter::new();
+        let mut out = Vec::new();
+        feed(&mut streamed, 0);
+        streamed.drain(&mut out).unwrap();
+        assert!(streamed.packets.is_empty());
+        feed(&mut streamed, 3);
+        streamed.drain(&mut out).unwrap();
+        streamed.export(&mut out).unwrap();
+        assert_eq!(out, expected);
+
+        // One descriptor for the thread, before its first sample
+        let packets: Vec<_> = all_bytes(&decode(&out), 1).into_iter().map(decode).collect();
+        assert_eq!(packets.iter().filter(|p| bytes(p, 60).is_some()).count(), 1);
+        assert!(bytes(&packets[0], 60).is_some());
+    }
+}
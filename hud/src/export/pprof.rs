@@ -0,0 +1,384 @@
+//! pprof export (`--format pprof`)
+//!
+//! Writes a gzipped `profile.proto`, the format of `go tool pprof` and of
+//! continuous-profiling backends such as Pyroscope. Every sample carries two
+//! values, selectable with `-sample_index`:
+//!
+//! - **samples** (count): CPU samples, on the sampled call stack
+//! - **`off_cpu`** (nanoseconds): scheduler waits, on the function that held
+//!   the worker's CPU (`[unattributed]` when no culprit was found)
+//!
+//! Samples are labelled with `worker_id`, `tid` and `task_id`, so
+//! `-tagfocus worker_id=2` narrows a profile to one worker.
+//!
+//! Field numbers are from `proto/profile.proto` in the pprof repository.
+
+use anyhow::{Context, Result};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use hud_common::{TaskEvent, EVENT_SCHEDULER_DETECTED, TRACE_EXECUTION_START};
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::Arc;
+
+use super::protobuf::ProtoWriter;
+use super::Exporter;
+use crate::classification::FrameOrigin;
+use crate::trace_data::{Blocker, StackFrame};
+
+/// The perf sampler runs at 99 Hz
+const SAMPLE_PERIOD_NS: u64 = 1_000_000_000 / 99;
+
+/// Leaf of off-CPU samples whose culprit is unknown
+const UNATTRIBUTED: &str = "[unattributed]";
+
+/// Index of each value in `Sample.value`
+const VALUE_SAMPLES: usize = 0;
+const VALUE_OFF_CPU_NS: usize = 1;
+
+/// Samples with the same stack and labels are merged
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct SampleKey {
+    /// `Location` ids, leaf first
+    locations: Vec<u64>,
+    worker_id: Option<u32>,
+    tid: u32,
+    task_id: Option<u64>,
+}
+
+/// pprof exporter
+pub struct PprofExporter {
+    /// `Profile.string_table`; index 0 is the empty string
+    strings: Vec<String>,
+    string_ids: HashMap<String, u64>,
+    /// One mapping per frame origin, by `FrameOrigin::category()`
+    mappings: Vec<&'static str>,
+    /// `Function` ids keyed by (name, file) string indices
+    functions: HashMap<(u64, u64), u64>,
+    /// `Location` ids keyed by (function id, line, mapping id)
+    locations: HashMap<(u64, u64, u64), u64>,
+    /// Location ids of a resolved stack by `Arc` address. The `Arc` is held
+    /// so the address cannot be reused by a different stack.
+    stacks: HashMap<usize, (Vec<u64>, Arc<Vec<StackFrame>>)>,
+    /// Encoded `Function` and `Location` messages, in id order
+    function_table: ProtoWriter,
+    location_table: ProtoWriter,
+    /// Merged samples in first-seen order
+    samples: Vec<(SampleKey, [u64; 2])>,
+    sample_ids: HashMap<SampleKey, usize>,
+    /// Event timestamps covered by the profile
+    first_ns: Option<u64>,
+    last_ns: u64,
+    event_count: usize,
+}
+
+impl Default for PprofExporter {
+    fn default() -> Self {
+        Self {
+            strings: vec![String::new()],
+            string_ids: HashMap::from([(String::new(), 0)]),
+            mappings: Vec::new(),
+            functions: HashMap::new(),
+            locations: HashMap::new(),
+            stacks: HashMap::new(),
+            function_table: ProtoWriter::new(),
+            location_table: ProtoWriter::new(),
+            samples: Vec::new(),
+            sample_ids: HashMap::new(),
+            first_ns: None,
+            last_ns: 0,
+            event_count: 0,
+        }
+    }
+}
+
+impl PprofExporter {
+    /// Create an empty pprof exporter
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn string(&mut self, s: &str) -> u64 {
+        if let Some(&id) = self.string_ids.get(s) {
+            return id;
+        }
+        let id = self.strings.len() as u64;
+        self.strings.push(s.to_string());
+        self.string_ids.insert(s.to_string(), id);
+        id
+    }
+
+    fn mapping(&mut self, origin: FrameOrigin) -> u64 {
+        let category = origin.category();
+        let index = self.mappings.iter().position(|&m| m == category).unwrap_or_else(|| {
+            self.mappings.push(category);
+            self.mappings.len() - 1
+        });
+        index as u64 + 1
+    }
+
+    fn function(&mut self, name: &str, file: Option<&str>) -> u64 {
+        let name = self.string(name);
+        let file = file.map_or(0, |file| self.string(file));
+        if let Some(&id) = self.functions.get(&(name, file)) {
+            return id;
+        }
+        let id = self.functions.len() as u64 + 1;
+        self.functions.insert((name, file), id);
+        // Profile.function: Function { id, name, system_name, filename }
+        self.function_table.message(5, |f| {
+            f.varint(1, id);
+            f.varint(2, name);
+            f.varint(3, name);
+            f.varint(4, file);
+        });
+        id
+    }
+
+    fn frame_location(&mut self, frame: &StackFrame) -> u64 {
+        let function = self.function(&frame.function, frame.file.as_deref());
+        let mapping = self.mapping(frame.origin);
+        self.location(function, u64::from(frame.line.unwrap_or(0)), mapping)
+    }
+
+    /// `line` 0 means unknown
+    fn location(&mut self, function: u64, line: u64, mapping: u64) -> u64 {
+        if let Some(&id) = self.locations.get(&(function, line, mapping)) {
+            return id;
+        }
+        let id = self.locations.len() as u64 + 1;
+        self.locations.insert((function, line, mapping), id);
+        // Profile.location: Location { id, mapping_id, line: Line { function_id, line } }
+        self.location_table.message(4, |l| {
+            l.varint(1, id);
+            l.varint(2, mapping);
+            l.message(4, |ln| {
+                ln.varint(1, function);
+                ln.varint(2, line);
+            });
+        });
+        id
+    }
+
+    fn stack_locations(&mut self, stack: &Arc<Vec<StackFrame>>) -> Vec<u64> {
+        let key = Arc::as_ptr(stack) as usize;
+        if let Some((locations, _)) = self.stacks.get(&key) {
+            return locations.clone();
+        }
+        let locations: Vec<u64> = stack.iter().map(|frame| self.frame_location(frame)).collect();
+        self.stacks.insert(key, (locations.clone(), Arc::clone(stack)));
+        locations
+    }
+
+    fn add_sample(&mut self, event: &TaskEvent, locations: Vec<u64>, value: usize, amount: u64) {
+        // Events from different CPUs arrive slightly out of order
+        let first_ns = self.first_ns.map_or(event.timestamp_ns, |ns| ns.min(event.timestamp_ns));
+        self.first_ns = Some(first_ns);
+        self.last_ns = self.last_ns.max(event.timestamp_ns);
+        self.event_count += 1;
+
+        let key = SampleKey {
+            locations,
+            worker_id: (event.worker_id != u32::MAX).then_some(event.worker_id),
+            tid: event.tid,
+            task_id: (event.task_id != 0).then_some(event.task_id),
+        };
+        let index = if let Some(&index) = self.sample_ids.get(&key) {
+            index
+        } else {
+            self.samples.push((key.clone(), [0; 2]));
+            self.sample_ids.insert(key, self.samples.len() - 1);
+            self.samples.len() - 1
+        };
+        self.samples[index].1[value] += amount;
+    }
+}
+
+impl Exporter for PprofExporter {
+    fn add_event(&mut self, event: &TaskEvent, call_stack: Option<&Arc<Vec<StackFrame>>>) {
+        match event.event_type {
+            TRACE_EXECUTION_START => {
+                let Some(stack) = call_stack.filter(|stack| !stack.is_empty()) else {
+                    return;
+                };
+                let locations = self.stack_locations(stack);
+                self.add_sample(event, locations, VALUE_SAMPLES, 1);
+            }
+            EVENT_SCHEDULER_DETECTED => self.add_scheduler_event(event, None),
+            _ => {}
+        }
+    }
+
+    fn add_scheduler_event(&mut self, event: &TaskEvent, blocker: Option<(&Blocker, &str)>) {
+        // The culprit's function is all that is known of its stack
+        let culprit = blocker
+            .map(|(_, function)| function)
+            .filter(|&function| function != "execution")
+            .unwrap_or(UNATTRIBUTED);
+        let function = self.function(culprit, None);
+        let mapping = self.mapping(FrameOrigin::Unknown);
+        let location = self.location(function, 0, mapping);
+        self.add_sample(event, vec![location], VALUE_OFF_CPU_NS, event.duration_ns);
+    }
+
+    #[allow(clippy::cast_possible_wrap)]
+    fn export(&self, writer: &mut dyn Write) -> Result<()> {
+        // Strings used below but not by any event; interned on a copy so
+        // export can take `&self`
+        let mut strings = self.strings.clone();
+        let mut string_ids = self.string_ids.clone();
+        let mut string = |s: &str| {
+            *string_ids.entry(s.to_string()).or_insert_with(|| {
+                strings.push(s.to_string());
+                strings.len() as u64 - 1
+            })
+        };
+        let value_types =
+            [(string("samples"), string("count")), (string("off_cpu"), string("nanoseconds"))];
+        let period_type = (string("cpu"), string("nanoseconds"));
+        let labels = [string("worker_id"), string("tid"), string("task_id")];
+        let mappings: Vec<u64> = self.mappings.iter().map(|m| string(m)).collect();
+
+        let mut profile = ProtoWriter::new();
+        for (kind, unit) in value_types {
+            // Profile.sample_type: ValueType { type, unit }
+            profile.message(1, |t| {
+                t.varint(1, kind);
+                t.varint(2, unit);
+            });
+        }
+
+        for (key, values) in &self.samples {
+            // Profile.sample: Sample { location_id, value, label }
+            profile.message(2, |s| {
+                s.repeated_varints(1, &key.locations);
+                s.repeated_varints(2, values);
+                let numeric = [key.worker_id.map(u64::from), Some(u64::from(key.tid)), key.task_id];
+                for (label, value) in labels.iter().zip(numeric) {
+                    if let Some(value) = value {
+                        // Label { key, num }
+                        s.message(3, |l| {
+                            l.varint(1, *label);
+                            l.varint(3, value);
+                        });
+                    }
+                }
+            });
+        }
+
+        for (index, filename) in mappings.iter().enumerate() {
+            // Profile.mapping: Mapping { id, filename, has_functions,
+            // has_filenames, has_line_numbers }. Frames are symbolized
+            // already, so pprof must not try to symbolize them again.
+            profile.message(3, |m| {
+                m.varint(1, index as u64 + 1);
+                m.varint(5, *filename);
+                m.varint(7, 1);
+                m.varint(8, 1);
+                m.varint(9, 1);
+            });
+        }
+
+        // Profile.location and Profile.function, pre-encoded with their
+        // field numbers
+        profile.extend(&self.location_table);
+        profile.extend(&self.function_table);
+
+        for s in &strings {
+            profile.string(6, s); // string_table
+        }
+
+        let duration = self.first_ns.map_or(0, |first| self.last_ns - first);
+        profile.int64(10, duration as i64); // duration_nanos
+        profile.message(11, |t| {
+            t.varint(1, period_type.0);
+            t.varint(2, period_type.1);
+        });
+        profile.varint(12, SAMPLE_PERIOD_NS); // period
+        profile.varint(14, value_types[VALUE_SAMPLES].0); // default_sample_type
+
+        let mut gz = GzEncoder::new(writer, Compression::default());
+        gz.write_all(profile.as_bytes()).context("Failed to write pprof profile")?;
+        gz.finish().context("Failed to write pprof profile")?;
+        Ok(())
+    }
+
+    fn event_count(&self) -> usize {
+        self.event_count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::protobuf::decode::{all_bytes, bytes, decode, int, ints};
+    use super::*;
+    use crate::testing::{frame_at, worker_event};
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+
+    #[test]
+    fn writes_gzipped_profile_with_both_values_and_labels() {
+        let stack = Arc::new(vec![
+            frame_at("app::hash", "src/lib.rs", 12, FrameOrigin::UserCode),
+            frame_at("tokio::runtime::poll", "src/lib.rs", 80, FrameOrigin::RuntimeLib),
+        ]);
+        let blocker = Blocker { tid: 1005, samples: 3, comm: None, kernel_stack: None };
+
+        let mut exporter = PprofExporter::new();
+        let event = |event_type, timestamp_ns, duration_ns| TaskEvent {
+            task_id: 7,
+            ..worker_event(event_type, timestamp_ns, duration_ns)
+        };
+        exporter.add_event(&event(TRACE_EXECUTION_START, 1_000, 0), Some(&stack));
+        exporter.add_event(&event(TRACE_EXECUTION_START, 2_000, 0), Some(&stack));
+        let wait = event(EVENT_SCHEDULER_DETECTED, 9_000, 6_000);
+        exporter.add_scheduler_event(&wait, Some((&blocker, "app::hash")));
+        assert_eq!(exporter.event_count(), 3);
+
+        let mut out = Vec::new();
+        exporter.export(&mut out).unwrap();
+        let mut raw = Vec::new();
+        GzDecoder::new(&out[..]).read_to_end(&mut raw).unwrap();
+        let profile = decode(&raw);
+
+        let strings: Vec<_> =
+            all_bytes(&profile, 6).into_iter().map(|s| std::str::from_utf8(s).unwrap()).collect();
+        assert_eq!(strings[0], "");
+        let string = |id: Option<u64>| strings[usize::try_from(id.unwrap()).unwrap()];
+
+        let sample_types: Vec<_> = all_bytes(&profile, 1).into_iter().map(decode).collect();
+        assert_eq!(string(int(&sample_types[0], 1)), "samples");
+        assert_eq!(string(int(&sample_types[1], 2)), "nanoseconds");
+        assert_eq!(int(&profile, 10), Some(8_000)); // duration_nanos
+
+        // Identical samples merge; the wait lands on the culprit's function
+        let samples: Vec<_> = all_bytes(&profile, 2).into_iter().map(decode).collect();
+        assert_eq!(samples.len(), 2);
+        assert_eq!(ints(&samples[0], 1), [1, 2]);
+        assert_eq!(ints(&samples[0], 2), [2, 0]);
+        assert_eq!(ints(&samples[1], 2), [0, 6_000]);
+
+        let labels: Vec<_> = all_bytes(&samples[0], 3)
+            .into_iter()
+            .map(|l| {
+                let l = decode(l);
+                (string(int(&l, 1)), int(&l, 3).unwrap())
+            })
+            .collect();
+        assert_eq!(labels, [("worker_id", 0), ("tid", 1001), ("task_id", 7)]);
+
+        // Leaf location resolves to a symbolized function and line
+        let locations: Vec<_> = all_bytes(&profile, 4).into_iter().map(decode).collect();
+        let line = decode(bytes(&locations[0], 4).unwrap());
+        assert_eq!(int(&line, 2), Some(12));
+        let functions: Vec<_> = all_bytes(&profile, 5).into_iter().map(decode).collect();
+        assert_eq!(int(&line, 1), int(&functions[0], 1));
+        assert_eq!(string(int(&functions[0], 2)), "app::hash");
+        assert_eq!(string(int(&functions[0], 4)), "src/lib.rs");
+
+        let mappings: Vec<_> = all_bytes(&profile, 3).into_iter().map(decode).collect();
+        assert_eq!(string(int(&mappings[0], 5)), "user");
+        assert_eq!(int(&mappings[0], 7), Some(1));
+    }
+}
@@ -0,0 +1,201 @@
+//! Minimal protobuf wire-format writer
+//!
+//! Just enough of the encoding for the Perfetto and pprof exporters: varints,
+//! doubles, length-delimited strings/bytes and nested messages. Field numbers
+//! live next to each message in the exporter that writes it.
+
+/// Wire type of varint fields (`int32`, `uint64`, `bool`, enums)
+const WIRE_VARINT: u64 = 0;
+/// Wire type of `double` fields
+const WIRE_FIXED64: u64 = 1;
+/// Wire type of strings, bytes, nested messages and packed repeated fields
+const WIRE_LEN: u64 = 2;
+
+/// Appends protobuf fields to a buffer
+#[derive(Debug, Default, Clone)]
+pub(crate) struct ProtoWriter {
+    buf: Vec<u8>,
+}
+
+impl ProtoWriter {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Encoded bytes so far
+    pub(crate) fn as_bytes(&self) -> &[u8] {
+        &self.buf
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn raw_varint(&mut self, mut v: u64) {
+        while v >= 0x80 {
+            self.buf.push((v as u8) | 0x80);
+            v >>= 7;
+        }
+        self.buf.push(v as u8);
+    }
+
+    fn key(&mut self, field: u32, wire_type: u64) {
+        self.raw_varint((u64::from(field) << 3) | wire_type);
+    }
+
+    /// `uint32`/`uint64`/`bool`/enum field
+    pub(crate) fn varint(&mut self, field: u32, v: u64) {
+        self.key(field, WIRE_VARINT);
+        self.raw_varint(v);
+    }
+
+    /// `int32`/`int64` field (negative values take ten bytes, as in protobuf)
+    pub(crate) fn int64(&mut self, field: u32, v: i64) {
+        self.varint(field, v.cast_unsigned());
+    }
+
+    pub(crate) fn double(&mut self, field: u32, v: f64) {
+        self.key(field, WIRE_FIXED64);
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    pub(crate) fn bytes(&mut self, field: u32, v: &[u8]) {
+        self.key(field, WIRE_LEN);
+        self.raw_varint(v.len() as u64);
+        self.buf.extend_from_slice(v);
+    }
+
+    pub(crate) fn string(&mut self, field: u32, v: &str) {
+        self.bytes(field, v.as_bytes());
+    }
+
+    /// Unpacked `repeated uint64` field: one key per value, which is what
+    /// Perfetto's decoder expects for fields not declared `[packed = true]`
+    pub(crate) fn repeated_varints(&mut self, field: u32, values: &[u64]) {
+        for &v in values {
+            self.varint(field, v);
+        }
+    }
+
+    /// Fields encoded by another writer, as if written here
+    pub(crate) fn extend(&mut self, fields: &ProtoWriter) {
+        self.buf.extend_from_slice(&fields.buf);
+    }
+
+    /// Nested message built by `build`
+    pub(crate) fn message(&mut self, field: u32, build: impl FnOnce(&mut ProtoWriter)) {
+        let mut nested = ProtoWriter::new();
+        build(&mut nested);
+        self.bytes(field, &nested.buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_reference_messages() {
+        // Examples from the protobuf encoding guide
+        let mut w = ProtoWriter::new();
+        w.varint(1, 150);
+        assert_eq!(w.as_bytes(), [0x08, 0x96, 0x01]);
+
+        let mut w = ProtoWriter::new();
+        w.string(2, "testing");
+        assert_eq!(w.as_bytes(), b"\x12\x07testing");
+
+        let mut w = ProtoWriter::new();
+        w.message(3, |m| m.varint(1, 150));
+        assert_eq!(w.as_bytes(), [0x1a, 0x03, 0x08, 0x96, 0x01]);
+
+        let mut w = ProtoWriter::new();
+        w.repeated_varints(4, &[3, 270]);
+        assert_eq!(w.as_bytes(), [0x20, 0x03, 0x20, 0x8e, 0x02]);
+
+        let mut w = ProtoWriter::new();
+        w.int64(1, -1);
+        assert_eq!(w.as_bytes().len(), 11);
+    }
+}
+
+/// Decoder for checking encoded messages in tests
+#[cfg(test)]
+pub(crate) mod decode {
+    /// Field value as far as these tests care: varints and byte payloads
+    #[derive(Debug)]
+    pub(crate) enum Value<'a> {
+        Int(u64),
+        Bytes(&'a [u8]),
+    }
+
+    fn read_varint(buf: &mut &[u8]) -> u64 {
+        let mut v = 0;
+        for shift in (0..64).step_by(7) {
+            let (&byte, rest) = buf.split_first().unwrap();
+            *buf = rest;
+            v |= u64::from(byte & 0x7f) << shift;
+            if byte < 0x80 {
+                break;
+            }
+        }
+        v
+    }
+
+    pub(crate) fn decode(mut buf: &[u8]) -> Vec<(u64, Value<'_>)> {
+        let mut fields = Vec::new();
+        while !buf.is_empty() {
+            let key = read_varint(&mut buf);
+            let value = match key & 7 {
+                0 => Value::Int(read_varint(&mut buf)),
+                1 => {
+                    let (bytes, rest) = buf.split_at(8);
+                    buf = rest;
+                    Value::Bytes(bytes)
+                }
+                2 => {
+                    let len = usize::try_from(read_varint(&mut buf)).unwrap();
+                    let (bytes, rest) = buf.split_at(len);
+                    buf = rest;
+                    Value::Bytes(bytes)
+                }
+                wire => panic!("unexpected wire type {wire}"),
+            };
+            fields.push((key >> 3, value));
+        }
+        fields
+    }
+
+    pub(crate) fn int(fields: &[(u64, Value)], field: u64) -> Option<u64> {
+        fields.iter().find_map(|(f, v)| match v {
+            Value::Int(v) if *f == field => Some(*v),
+            _ => None,
+        })
+    }
+
+    pub(crate) fn all_bytes<'a>(fields: &[(u64, Value<'a>)], field: u64) -> Vec<&'a [u8]> {
+        fields
+            .iter()
+            .filter_map(|(f, v)| match v {
+                Value::Bytes(b) if *f == field => Some(*b),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub(crate) fn bytes<'a>(fields: &[(u64, Value<'a>)], field: u64) -> Option<&'a [u8]> {
+        all_bytes(fields, field).into_iter().next()
+    }
+
+    /// All varint values of a (repeated) field
+    pub(crate) fn ints(fields: &[(u64, Value)], field: u64) -> Vec<u64> {
+        fields
+            .iter()
+            .filter_map(|(f, v)| match v {
+                Value::Int(v) if *f == field => Some(*v),
+                _ => None,
+            })
+            .collect()
+    }
+}
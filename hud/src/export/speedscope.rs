@@ -0,0 +1,153 @@
+//! Speedscope export (`--format speedscope`)
+//!
+//! Writes speedscope's file format with one "sampled" profile per group
+//! (see `--group-by`), or a single profile for the whole process. Samples
+//! keep their order, so speedscope's "Time Order" view replays the session;
+//! "Left Heavy" merges them into a flame graph.
+//!
+//! Schema: <https://www.speedscope.app/file-format-schema.json>
+
+use anyhow::{Context, Result};
+use hud_common::{TaskEvent, TRACE_EXECUTION_START};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+use std::sync::Arc;
+
+use super::{Exporter, StackOptions};
+use crate::trace_data::{Blocker, StackFrame};
+
+const SCHEMA: &str = "https://www.speedscope.app/file-format-schema.json";
+
+/// Profile name when samples are not grouped
+const ALL_SAMPLES: &str = "All threads";
+
+#[derive(Serialize)]
+struct SpeedscopeFile<'a> {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    shared: Shared<'a>,
+    profiles: Vec<SampledProfile<'a>>,
+    name: &'static str,
+    exporter: String,
+}
+
+#[derive(Serialize)]
+struct Shared<'a> {
+    frames: &'a [Frame],
+}
+
+#[derive(Serialize, Clone, PartialEq, Eq, Hash)]
+struct Frame {
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SampledProfile<'a> {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    name: &'a str,
+    /// Weights are sample counts
+    unit: &'static str,
+    start_value: u64,
+    end_value: u64,
+    /// Frame indices of each sample, outermost first
+    samples: &'a [Vec<usize>],
+    weights: Vec<u64>,
+}
+
+/// Speedscope exporter
+pub struct SpeedscopeExporter {
+    options: StackOptions,
+    /// Shared frame table; frames are keyed by function and file, so one
+    /// function is one node however many lines of it were sampled
+    frames: Vec<Frame>,
+    frame_ids: HashMap<Frame, usize>,
+    /// Samples per profile name
+    profiles: BTreeMap<String, Vec<Vec<usize>>>,
+    samples: usize,
+}
+
+impl SpeedscopeExporter {
+    /// Create an empty speedscope exporter
+    #[must_use]
+    pub fn new(options: StackOptions) -> Self {
+        Self {
+            options,
+            frames: Vec::new(),
+            frame_ids: HashMap::new(),
+            profiles: BTreeMap::new(),
+            samples: 0,
+        }
+    }
+
+    fn frame_id(&mut self, frame: &StackFrame) -> usize {
+        let key = Frame { name: frame.function.clone(), file: frame.file.clone() };
+        if let Some(&id) = self.frame_ids.get(&key) {
+            return id;
+        }
+        let id = self.frames.len();
+        self.frames.push(key.clone());
+        self.frame_ids.insert(key, id);
+        id
+    }
+}
+
+impl Exporter for SpeedscopeExporter {
+    fn add_event(&mut self, event: &TaskEvent, call_stack: Option<&Arc<Vec<StackFrame>>>) {
+        if event.event_type != TRACE_EXECUTION_START {
+            return;
+        }
+        let Some(stack) = call_stack else {
+            return;
+        };
+
+        let options = self.options;
+        let sample: Vec<usize> = options.frames(stack).map(|frame| self.frame_id(frame)).collect();
+        // Nothing left once runtime frames are hidden
+        if sample.is_empty() {
+            return;
+        }
+
+        let profile =
+            options.group_by.profile_name(event).unwrap_or_else(|| ALL_SAMPLES.to_string());
+        self.profiles.entry(profile).or_default().push(sample);
+        self.samples += 1;
+    }
+
+    /// Scheduler waits are not CPU samples and have no place in a flame graph
+    fn add_scheduler_event(&mut self, _event: &TaskEvent, _blocker: Option<(&Blocker, &str)>) {}
+
+    fn export(&self, writer: &mut dyn Write) -> Result<()> {
+        let profiles = self
+            .profiles
+            .iter()
+            .map(|(name, samples)| SampledProfile {
+                kind: "sampled",
+                name,
+                unit: "none",
+                start_value: 0,
+                end_value: samples.len() as u64,
+                samples,
+                weights: vec![1; samples.len()],
+            })
+            .collect();
+
+        let file = SpeedscopeFile {
+            schema: SCHEMA,
+            shared: Shared { frames: &self.frames },
+            profiles,
+            name: "hud",
+            exporter: format!("hud {}", env!("CARGO_PKG_VERSION")),
+        };
+        serde_json::to_writer(writer, &file).context("Failed to write speedscope JSON")?;
+        Ok(())
+    }
+
+    fn event_count(&self) -> usize {
+        self.samples
+    }
+}
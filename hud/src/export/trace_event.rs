@@ -2,13 +2,15 @@
 #![allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
 
 use anyhow::{Context, Result};
-use hud_common::{TaskEvent, TRACE_EXECUTION_END, TRACE_EXECUTION_START};
+use hud_common::{TaskEvent, EVENT_SCHEDULER_DETECTED, TRACE_EXECUTION_START};
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::io::Write;
+use std::sync::Arc;
 
-use crate::symbolization::{MemoryRange, Symbolizer};
+use super::Exporter;
+use crate::trace_data::{attribution_frame, Blocker, Bookmark, StackFrame};
 
 /// Trace Event format (compatible with Perfetto, Speedscope, Chrome tracing)
 /// Spec: <https://docs.google.com/document/d/1CvAClvFfyA5R-PhYUmn5OOQtYMH4h6I0nSsKchNAySU/preview>
@@ -18,10 +20,13 @@ struct ChromeTraceEvent {
     name: String,
     /// Category for filtering/coloring
     cat: String,
-    /// Phase: "B" = begin, "E" = end, "X" = complete, "I" = instant
+    /// Phase: "P" = sample, "i" = instant, "X" = complete, "C" = counter, "M" = metadata
     ph: String,
     /// Timestamp in microseconds
     ts: f64,
+    /// Duration in microseconds (complete "X" events only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dur: Option<f64>,
     /// Process ID
     pid: u32,
     /// Thread ID
@@ -29,96 +34,124 @@ struct ChromeTraceEvent {
     /// Optional arguments (metadata)
     #[serde(skip_serializing_if = "Option::is_none")]
     args: Option<HashMap<String, JsonValue>>,
+    /// Leaf of the call stack in the `stackFrames` dictionary
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sf: Option<u64>,
+    /// Instant event scope: "t" = thread, "g" = global
+    #[serde(skip_serializing_if = "Option::is_none")]
+    s: Option<String>,
 }
 
-/// Chrome Trace Format container
-#[derive(Debug, Serialize)]
-struct ChromeTrace {
-    #[serde(rename = "traceEvents")]
-    trace_events: Vec<ChromeTraceEvent>,
-    #[serde(rename = "displayTimeUnit")]
-    display_time_unit: String,
+/// Node of the `stackFrames` tree: one function under one caller
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ChromeStackFrame {
+    /// Function name
+    name: String,
+    /// Frame origin (`user`, `std`, `runtime`, ...)
+    category: String,
+    /// Source file, when debug info has one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file: Option<String>,
+    /// Calling frame (absent for the outermost frame)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parent: Option<u64>,
 }
 
+/// Start of the Chrome Trace Format container. Events follow one per line,
+/// each after the first prefixed with `,`, so a file cut short can be
+/// repaired by dropping its last line and appending `]}`. Its samples'
+/// `sf` ids then need the `stackFrames` of the last
+/// [`checkpoint`](TraceEventExporter::checkpoint).
+const TRACE_HEADER: &[u8] = b"{\"traceEvents\":[\n";
+
 /// Trace event exporter for timeline visualization
+///
+/// Stacks arrive already resolved (the `EventProcessor`'s `StackCache`), so
+/// the exporter only interns them into the `stackFrames` tree.
+///
+/// Events are held until [`drain`](Self::drain) writes them out, so long
+/// sessions can stream to disk; [`export`](Self::export) writes the rest and
+/// completes the document. What does stay in memory grows with distinct
+/// stacks and threads and with seconds of worker activity (the counter
+/// tracks), not with the number of events.
+#[derive(Default)]
 pub struct TraceEventExporter {
-    /// Collected trace events
+    /// Trace events not yet drained
     events: Vec<ChromeTraceEvent>,
-    /// Symbolizer for resolving stack traces
-    symbolizer: Symbolizer,
-    /// Cache for resolved symbols (`stack_id` -> (function name, file, line))
-    symbol_cache: HashMap<i64, (String, Option<String>, Option<u32>)>,
-    /// Memory range for address adjustment
-    memory_range: Option<MemoryRange>,
+    /// Events already written by `drain` (the header too, when non-zero)
+    drained: usize,
+    /// Worker ID of every thread seen, for the thread name metadata
+    threads: BTreeMap<(u32, u32), u32>,
+    /// `stackFrames` dictionary, keyed by frame id (ids start at 1)
+    stack_frames: BTreeMap<u64, ChromeStackFrame>,
+    /// Size of `stack_frames` at the last checkpoint
+    checkpointed_frames: usize,
+    /// Frame id by (parent id or 0, function name)
+    frame_ids: HashMap<(u64, String), u64>,
+    /// Leaf frame id by `Arc` address of a resolved stack. The `Arc` is held
+    /// so the address cannot be reused by a different stack.
+    leaf_ids: HashMap<usize, (u64, Arc<Vec<StackFrame>>)>,
     /// Start timestamp for relative timing (in nanoseconds)
     start_timestamp_ns: Option<u64>,
+    /// Blocking activity per (worker, second since start), for counter tracks
+    rates: BTreeMap<(u32, u64), BlockingRate>,
+    /// Process the counter tracks belong to
+    pid: u32,
+}
+
+/// One second of a worker's blocking activity
+#[derive(Debug, Clone, Copy, Default)]
+struct BlockingRate {
+    /// On-CPU samples (99 Hz)
+    samples: u64,
+    /// Scheduler wait time that ended in this second
+    wait_ns: u64,
 }
 
+/// Width of a counter track bucket
+const RATE_BUCKET_NS: u64 = 1_000_000_000;
+
 impl TraceEventExporter {
     /// Create a new trace event exporter
-    pub fn new(symbolizer: Symbolizer) -> Self {
-        Self {
-            events: Vec::new(),
-            symbolizer,
-            symbol_cache: HashMap::new(),
-            memory_range: None,
-            start_timestamp_ns: None,
-        }
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
     }
 
-    /// Set the memory range for PIE address adjustment
-    pub fn set_memory_range(&mut self, range: MemoryRange) {
-        self.memory_range = Some(range);
-    }
-
-    /// Resolve a symbol from a stack trace
-    /// Returns (`function_name`, file, line)
-    fn resolve_symbol(
-        &mut self,
-        stack_id: i64,
-        addr: u64,
-    ) -> (String, Option<String>, Option<u32>) {
-        // Check cache first
-        if let Some(cached) = self.symbol_cache.get(&stack_id) {
-            return cached.clone();
+    /// Intern a resolved stack (innermost frame first) and return its leaf id
+    fn stack_frame_id(&mut self, stack: &Arc<Vec<StackFrame>>) -> u64 {
+        let key = Arc::as_ptr(stack) as usize;
+        if let Some((leaf, _)) = self.leaf_ids.get(&key) {
+            return *leaf;
         }
 
-        // Determine if address is in main executable and adjust accordingly
-        let (file_offset, in_executable) = self.memory_range.map_or((addr, true), |range| {
-            if range.contains(addr) {
-                (addr - range.start, true)
-            } else {
-                (addr, false)
+        // Walk from the outermost caller down so each node knows its parent
+        let mut parent = 0;
+        for frame in stack.iter().rev() {
+            let next_id = self.stack_frames.len() as u64 + 1;
+            let id = *self.frame_ids.entry((parent, frame.function.clone())).or_insert(next_id);
+            if id == next_id {
+                self.stack_frames.insert(
+                    id,
+                    ChromeStackFrame {
+                        name: frame.function.clone(),
+                        category: frame.origin.category().to_string(),
+                        file: frame.file.clone(),
+                        parent: (parent != 0).then_some(parent),
+                    },
+                );
             }
-        });
-
-        let (function_name, file, line) = if in_executable {
-            // Resolve the symbol
-            let resolved = self.symbolizer.resolve(file_offset);
-
-            // Get the outermost (non-inlined) function name and location
-            if let Some(frame) = resolved.frames.first() {
-                let func = frame.function.clone();
-                let file_path = frame.location.as_ref().and_then(|loc| loc.file.clone());
-                let line_num = frame.location.as_ref().and_then(|loc| loc.line);
-
-                (func, file_path, line_num)
-            } else {
-                (format!("0x{addr:x}"), None, None)
-            }
-        } else {
-            // For shared libraries, just show address
-            (format!("<shared:0x{addr:x}>"), None, None)
-        };
+            parent = id;
+        }
 
-        // Cache the complete result (function name, file, line)
-        let result = (function_name, file, line);
-        self.symbol_cache.insert(stack_id, result.clone());
-        result
+        self.leaf_ids.insert(key, (parent, Arc::clone(stack)));
+        parent
     }
 
     /// Add a task event to the trace
-    pub fn add_event(&mut self, event: &TaskEvent, top_frame_addr: Option<u64>) {
+    ///
+    /// `call_stack` is the event's resolved stack, innermost frame first.
+    pub fn add_event(&mut self, event: &TaskEvent, call_stack: Option<&Arc<Vec<StackFrame>>>) {
         // Initialize start timestamp on first event, or get existing
         let start_ts = *self.start_timestamp_ns.get_or_insert(event.timestamp_ns);
 
@@ -130,16 +163,27 @@ impl TraceEventExporter {
         };
 
         match event.event_type {
+            // Each perf sample is a point in time, not a slice: "P" with its
+            // call stack, or a thread-scoped instant when it has none
             TRACE_EXECUTION_START => {
-                // Resolve function name and source location from stack trace
-                let (function_name, file, line) = match (event.stack_id < 0, top_frame_addr) {
+                self.rate_mut(event).samples += 1;
+                self.threads.insert((event.pid, event.tid), event.worker_id);
+
+                // Name the event after the frame the TUI and reports attribute
+                // the sample to, so per-function counts match theirs
+                let frame = call_stack.and_then(|stack| attribution_frame(stack));
+                let (function_name, file, line) = match (event.stack_id < 0, frame) {
                     // Stack capture failed (from sched_switch which can't capture user stacks)
                     (true, _) => ("execution".to_string(), None, None),
-                    // Valid stack ID with frame address - resolve symbol
-                    (false, Some(addr)) => self.resolve_symbol(event.stack_id, addr),
-                    // Valid stack ID but no frame address
+                    (false, Some(frame)) => {
+                        (frame.function.clone(), frame.file.clone(), frame.line)
+                    }
+                    // Valid stack ID but the stack could not be resolved
                     (false, None) => (format!("trace_{}", event.stack_id), None, None),
                 };
+                let sf = call_stack
+                    .filter(|stack| !stack.is_empty())
+                    .map(|stack| self.stack_frame_id(stack));
 
                 // Create metadata args
                 let mut args = HashMap::new();
@@ -167,44 +211,150 @@ impl TraceEventExporter {
                     args.insert("line".to_string(), serde_json::json!(line_num));
                 }
 
+                let (ph, s) = if sf.is_some() { ("P", None) } else { ("i", Some("t".to_string())) };
                 self.events.push(ChromeTraceEvent {
                     name: function_name,
                     cat: "execution".to_string(),
-                    ph: "B".to_string(), // Begin
+                    ph: ph.to_string(),
                     ts: ts_us,
+                    dur: None,
                     pid: event.pid,
                     tid: event.tid,
                     args: Some(args),
+                    sf,
+                    s,
                 });
             }
-            TRACE_EXECUTION_END => {
-                // For end events, we use a generic name since we don't have stack trace
-                // The Chrome viewer will match it with the corresponding Begin event
-                // Add worker_id to help identify which worker this belongs to
-                let mut args = HashMap::new();
-                args.insert("worker_id".to_string(), serde_json::json!(event.worker_id));
-                args.insert("cpu_id".to_string(), serde_json::json!(event.cpu_id));
-                if event.detection_method != 0 {
-                    args.insert(
-                        "detection_method".to_string(),
-                        serde_json::json!(event.detection_method),
-                    );
-                }
+            EVENT_SCHEDULER_DETECTED => self.add_scheduler_event(event, None),
+            _ => {
+                // Ignore other event types, including TRACE_EXECUTION_END:
+                // samples are instants, so there is no slice to close
+            }
+        }
+    }
 
-                self.events.push(ChromeTraceEvent {
-                    name: "execution".to_string(),
-                    cat: "execution".to_string(),
-                    ph: "E".to_string(), // End
-                    ts: ts_us,
-                    pid: event.pid,
-                    tid: event.tid,
+    /// Add a scheduler latency event as a complete ("X") interval.
+    ///
+    /// `blocker` is the correlated culprit thread and the function it was
+    /// running, if any (see `LatencyCorrelator`).
+    pub fn add_scheduler_event(&mut self, event: &TaskEvent, blocker: Option<(&Blocker, &str)>) {
+        let start_ts = *self.start_timestamp_ns.get_or_insert(event.timestamp_ns);
+        self.rate_mut(event).wait_ns += event.duration_ns;
+        self.threads.insert((event.pid, event.tid), event.worker_id);
+
+        // The event fires when the worker gets the CPU back, so the
+        // wait interval ends at the event timestamp
+        let wait_began_ns = event.timestamp_ns.saturating_sub(event.duration_ns);
+        let wait_ts_us = wait_began_ns.saturating_sub(start_ts) as f64 / 1000.0;
+
+        let mut args = HashMap::new();
+        args.insert("worker_id".to_string(), serde_json::json!(event.worker_id));
+        args.insert("cpu_id".to_string(), serde_json::json!(event.cpu_id));
+        args.insert(
+            "duration_ms".to_string(),
+            serde_json::json!(event.duration_ns as f64 / 1_000_000.0),
+        );
+        args.insert("thread_state".to_string(), serde_json::json!(event.thread_state));
+        if event.task_id != 0 {
+            args.insert("task_id".to_string(), serde_json::json!(event.task_id));
+        }
+        args.insert("detection_method".to_string(), serde_json::json!(event.detection_method));
+        if let Some((blocker, function)) = blocker {
+            args.insert("blocker_tid".to_string(), serde_json::json!(blocker.tid));
+            args.insert("blocker_samples".to_string(), serde_json::json!(blocker.samples));
+            if let Some(ref comm) = blocker.comm {
+                args.insert("blocker_comm".to_string(), serde_json::json!(comm));
+            }
+            if let Some(ref kernel_stack) = blocker.kernel_stack {
+                let frames: Vec<&str> = kernel_stack.iter().map(|f| f.function.as_str()).collect();
+                args.insert("blocker_kernel_stack".to_string(), serde_json::json!(frames));
+            }
+            if function != "execution" {
+                args.insert("blocker".to_string(), serde_json::json!(function));
+            }
+        }
+
+        self.events.push(ChromeTraceEvent {
+            name: "sched_wait".to_string(),
+            cat: "scheduler".to_string(),
+            ph: "X".to_string(), // Complete (begin + duration)
+            ts: wait_ts_us,
+            dur: Some(event.duration_ns as f64 / 1000.0),
+            pid: event.pid,
+            tid: event.tid,
+            args: Some(args),
+            sf: None,
+            s: None,
+        });
+    }
+
+    /// Add a bookmark as a global instant event, drawn across every track
+    pub fn add_bookmark(&mut self, bookmark: &Bookmark) {
+        let timestamp_ns = bookmark.timestamp_ns();
+        let start_ts = *self.start_timestamp_ns.get_or_insert(timestamp_ns);
+        self.events.push(ChromeTraceEvent {
+            name: bookmark.label.clone(),
+            cat: "bookmark".to_string(),
+            ph: "i".to_string(),
+            ts: timestamp_ns.saturating_sub(start_ts) as f64 / 1000.0,
+            dur: None,
+            pid: self.pid,
+            tid: 0,
+            args: None,
+            sf: None,
+            s: Some("g".to_string()),
+        });
+    }
+
+    /// Counter bucket of the event's worker and second.
+    ///
+    /// Events outside a worker land in a bucket that is never exported.
+    fn rate_mut(&mut self, event: &TaskEvent) -> &mut BlockingRate {
+        let start_ts = self.start_timestamp_ns.unwrap_or(event.timestamp_ns);
+        let second = event.timestamp_ns.saturating_sub(start_ts) / RATE_BUCKET_NS;
+        self.pid = event.pid;
+        self.rates.entry((event.worker_id, second)).or_default()
+    }
+
+    /// Per-worker "C" counter events: samples and scheduler wait per second.
+    ///
+    /// Every second from a worker's first to last activity gets a value, and
+    /// the track drops back to zero after the last one.
+    fn counter_events(&self) -> Vec<ChromeTraceEvent> {
+        let mut events = Vec::new();
+        let workers: BTreeSet<u32> = self.rates.keys().map(|&(worker, _)| worker).collect();
+
+        for worker in workers.into_iter().filter(|&w| w != u32::MAX) {
+            let seconds = self.rates.range((worker, 0)..=(worker, u64::MAX));
+            let (Some(first), Some(last)) =
+                (seconds.clone().next().map(|(k, _)| k.1), seconds.last().map(|(k, _)| k.1))
+            else {
+                continue;
+            };
+
+            for second in first..=last + 1 {
+                let rate = self.rates.get(&(worker, second)).copied().unwrap_or_default();
+                let mut args = HashMap::new();
+                args.insert("samples_per_sec".to_string(), serde_json::json!(rate.samples));
+                args.insert(
+                    "wait_ms_per_sec".to_string(),
+                    serde_json::json!(rate.wait_ns as f64 / 1_000_000.0),
+                );
+                events.push(ChromeTraceEvent {
+                    name: format!("Worker {worker} blocking"),
+                    cat: "blocking".to_string(),
+                    ph: "C".to_string(), // Counter
+                    ts: (second * RATE_BUCKET_NS) as f64 / 1000.0,
+                    dur: None,
+                    pid: self.pid,
+                    tid: 0,
                     args: Some(args),
+                    sf: None,
+                    s: None,
                 });
             }
-            _ => {
-                // Ignore other event types for now
-            }
         }
+        events
     }
 
     /// Export the trace to any writer (file, stdout, buffer, etc.)
@@ -218,14 +368,11 @@ impl TraceEventExporter {
     /// # Example
     /// ```
     /// use hud::export::TraceEventExporter;
-    /// use hud::symbolization::Symbolizer;
     /// use std::fs::File;
     /// use std::io::BufWriter;
     ///
     /// # fn example() -> anyhow::Result<()> {
-    /// let binary_path = "/bin/ls";
-    /// let symbolizer = Symbolizer::new(binary_path)?;
-    /// let exporter = TraceEventExporter::new(symbolizer);
+    /// let exporter = TraceEventExporter::new();
     ///
     /// // Write to file
     /// let file = File::create("trace.json")?;
@@ -241,45 +388,128 @@ impl TraceEventExporter {
     /// # Ok(())
     /// # }
     /// ```
-    pub fn export<W: Write>(&self, writer: W) -> Result<()> {
-        // Add metadata events for thread names
-        let mut all_events = self.events.clone();
-
-        // Collect unique (pid, tid, worker_id) tuples
-        let mut threads: HashMap<(u32, u32), u32> = HashMap::new();
-        for event in &self.events {
-            if let Some(ref args) = event.args {
-                if let Some(worker_id) = args.get("worker_id").and_then(serde_json::Value::as_u64) {
-                    threads.insert((event.pid, event.tid), worker_id as u32);
-                }
-            }
-        }
-
-        // Generate thread name metadata events
-        for ((pid, tid), worker_id) in threads {
+    pub fn export<W: Write>(&self, mut writer: W) -> Result<()> {
+        // Counter tracks and thread names need the whole session
+        let mut tail = self.counter_events();
+        for (&(pid, tid), worker_id) in &self.threads {
             let mut args = HashMap::new();
             args.insert("name".to_string(), serde_json::json!(format!("Worker {}", worker_id)));
 
-            all_events.push(ChromeTraceEvent {
+            tail.push(ChromeTraceEvent {
                 name: "thread_name".to_string(),
                 cat: String::new(),
                 ph: "M".to_string(), // Metadata
                 ts: 0.0,
+                dur: None,
                 pid,
                 tid,
                 args: Some(args),
+                sf: None,
+                s: None,
             });
         }
 
-        let trace = ChromeTrace { trace_events: all_events, display_time_unit: "ms".to_string() };
+        if self.drained == 0 {
+            writer.write_all(TRACE_HEADER).context("Failed to write trace JSON")?;
+        }
+        write_events(&mut writer, self.events.iter().chain(&tail), self.drained)?;
 
-        serde_json::to_writer_pretty(writer, &trace).context("Failed to write trace JSON")?;
+        writer.write_all(b"\n],\n\"stackFrames\":").context("Failed to write trace JSON")?;
+        serde_json::to_writer(&mut writer, &self.stack_frames)
+            .context("Failed to write trace JSON")?;
+        writer
+            .write_all(b",\n\"displayTimeUnit\":\"ms\"}\n")
+            .context("Failed to write trace JSON")?;
 
         Ok(())
     }
 
+    /// Write the events held so far to `writer` and stop holding them
+    ///
+    /// Call this periodically on the writer that [`export`](Self::export)
+    /// will complete, so memory does not grow with the number of events.
+    ///
+    /// # Errors
+    /// Returns an error if writing to the output fails
+    pub fn drain<W: Write>(&mut self, mut writer: W) -> Result<()> {
+        if self.events.is_empty() {
+            return Ok(());
+        }
+        if self.drained == 0 {
+            writer.write_all(TRACE_HEADER).context("Failed to write trace JSON")?;
+        }
+        write_events(&mut writer, &self.events, self.drained)?;
+        self.drained += self.events.len();
+        self.events.clear();
+        Ok(())
+    }
+
+    /// The `stackFrames` dictionary as a `{"stackFrames":{...}}` document,
+    /// if frames were added since the last call
+    ///
+    /// Merged into a repaired file cut short after a drain, it resolves the
+    /// `sf` ids of every drained sample.
+    ///
+    /// # Errors
+    /// Returns an error if JSON serialization fails
+    pub fn checkpoint(&mut self) -> Result<Option<Vec<u8>>> {
+        if self.stack_frames.len() == self.checkpointed_frames {
+            return Ok(None);
+        }
+        self.checkpointed_frames = self.stack_frames.len();
+        let table = serde_json::json!({ "stackFrames": &self.stack_frames });
+        Ok(Some(serde_json::to_vec(&table).context("Failed to encode stack frames")?))
+    }
+
     /// Get the number of events collected
+    #[must_use]
     pub fn event_count(&self) -> usize {
-        self.events.len()
+        self.drained + self.events.len()
+    }
+}
+
+/// Write `events` one per line; `written` events precede them in the array
+fn write_events<'a, W: Write>(
+    writer: &mut W,
+    events: impl IntoIterator<Item = &'a ChromeTraceEvent>,
+    written: usize,
+) -> Result<()> {
+    for (i, event) in events.into_iter().enumerate() {
+        if written + i > 0 {
+            writer.write_all(b",").context("Failed to write trace JSON")?;
+        }
+        serde_json::to_writer(&mut *writer, event).context("Failed to write trace JSON")?;
+        writer.write_all(b"\n").context("Failed to write trace JSON")?;
+    }
+    Ok(())
+}
+
+impl Exporter for TraceEventExporter {
+    fn add_event(&mut self, event: &TaskEvent, call_stack: Option<&Arc<Vec<StackFrame>>>) {
+        TraceEventExporter::add_event(self, event, call_stack);
+    }
+
+    fn add_scheduler_event(&mut self, event: &TaskEvent, blocker: Option<(&Blocker, &str)>) {
+        TraceEventExporter::add_scheduler_event(self, event, blocker);
+    }
+
+    fn add_bookmark(&mut self, bookmark: &Bookmark) {
+        TraceEventExporter::add_bookmark(self, bookmark);
+    }
+
+    fn export(&self, writer: &mut dyn Write) -> Result<()> {
+        TraceEventExporter::export(self, writer)
+    }
+
+    fn drain(&mut self, writer: &mut dyn Write) -> Result<()> {
+        TraceEventExporter::drain(self, writer)
+    }
+
+    fn checkpoint(&mut self) -> Result<Option<Vec<u8>>> {
+        TraceEventExporter::checkpoint(self)
+    }
+
+    fn event_count(&self) -> usize {
+        TraceEventExporter::event_count(self)
     }
 }
@@ -22,6 +22,9 @@
 //! - [`tui`] - Terminal UI with hotspot, timeline, and worker views
 //! - [`analysis`] - Hotspot detection and aggregation
 //! - [`export`] - Chrome Trace Event Format (JSON) export
+//! - [`recording`] - Session files for `hud record` / `hud replay`
+//! - [`report`] - Summary reports and blocking budgets for CI
+//! - [`diff`] - Before/after comparison for `hud diff`
 //! - [`cli`] - Command-line argument parsing
 //! - [`trace_data`] - Event data structures
 //! - [`domain`] - Core types (Pid, Tid, `StackId`, `CpuId`)
@@ -30,11 +33,16 @@
 pub mod analysis;
 pub mod classification;
 pub mod cli;
+pub mod diff;
 pub mod domain;
 pub mod export;
 pub mod preflight;
 pub mod process_lookup;
 pub mod profiling;
+pub mod recording;
+pub mod report;
 pub mod symbolization;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 pub mod trace_data;
 pub mod tui;
@@ -4,6 +4,9 @@
 //! - **Live TUI** (`--pid <PID>` or `hud <PROCESS>`): Real-time profiling with interactive UI
 //! - **Headless** (`--headless --export trace.json`): Non-interactive profiling for CI/CD
 //!
+//! `hud record -o session.hud` additionally saves the session, and
+//! `hud replay session.hud` opens a saved session in the TUI without root.
+//!
 //! See [Architecture docs](../docs/ARCHITECTURE.md) for detailed program flow.
 
 // Main function is intentionally long for clarity; time conversions lose precision for display
@@ -18,32 +21,46 @@ use anyhow::{Context, Result};
 use aya::maps::{RingBuf, StackTraceMap};
 use clap::Parser;
 use crossbeam_channel::bounded;
-use hud::export::TraceEventExporter;
-use hud::symbolization::{parse_memory_maps, Symbolizer};
+use hud::recording::{Record, SessionConfig, SessionReader, SessionRecorder};
+use hud::symbolization::{parse_executable_mappings, ModuleMap, Symbolizer, SymbolizerSet};
 use hud_common::TaskEvent;
 use log::{info, warn};
 use std::fs::File;
-use std::io::BufWriter;
-use std::time::{Duration, Instant};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+use tokio::signal::unix::{signal, SignalKind};
 
 // Import modules
-use hud::cli::Args;
-use hud::domain::Pid;
+use hud::cli::{Args, Command, ProfileArgs};
+use hud::diff::{write_differential_folded, Capture, CaptureDiff, DiffFormat};
+use hud::domain::{Pid, UsageError};
+use hud::export::{checkpoint_path, ProfileGroup, StackOptions};
 use hud::preflight::{check_proc_access, check_process_exists, run_preflight_checks};
 use hud::process_lookup::{find_process_by_name, resolve_exe_path};
 use hud::profiling::{
     attach_sched_switch, attach_task_id_uprobe, discover_workers_from_stacks, display_statistics,
     init_ebpf_logger, load_ebpf_program, print_perf_event_diagnostics, register_workers_in_ebpf,
-    start_perf_sampling, EventProcessor, StackResolver,
+    spawn_maps_refresher, start_perf_sampling, EbpfWorkerRegistry, EventProcessor, StackResolver,
+    WorkerChanges, WorkerTracker,
 };
+use hud::report::{budget, Report};
 use hud::tui;
 
 // Exit codes
 const EXIT_SUCCESS: i32 = 0;
 const EXIT_ERROR: i32 = 1;
 const EXIT_USAGE: i32 = 2;
+const EXIT_ASSERT: i32 = 3;
 const EXIT_NOPERM: i32 = 77;
 
+/// How often `/proc/<pid>/task` is re-scanned for workers of new runtimes
+const WORKER_REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// How often export events and the session recording are written to disk
+/// during the session
+const EXPORT_FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
 fn main() {
     env_logger::init();
     std::process::exit(match run() {
@@ -58,10 +75,12 @@ fn main() {
 
 fn exit_code_for(err: &anyhow::Error) -> i32 {
     let msg = err.to_string().to_lowercase();
-    if msg.contains("permission denied") || msg.contains("requires root") {
-        EXIT_NOPERM
-    } else if msg.contains("missing required argument") {
+    if err.downcast_ref::<UsageError>().is_some() {
         EXIT_USAGE
+    } else if msg.contains("permission denied") || msg.contains("requires root") {
+        EXIT_NOPERM
+    } else if msg.contains("blocking budget exceeded") {
+        EXIT_ASSERT
     } else {
         EXIT_ERROR
     }
@@ -73,16 +92,18 @@ fn exit_code_for(err: &anyhow::Error) -> i32 {
 /// - `hud my-app` - find process by name, auto-detect binary
 /// - `hud --pid 1234` - explicit PID, auto-detect binary from /proc
 /// - `hud --pid 1234 --target ./app` - explicit PID and binary
-fn resolve_pid_and_target(args: &Args) -> Result<(i32, String)> {
+fn resolve_pid_and_target(args: &ProfileArgs) -> Result<(i32, String)> {
     // Mode A: Process name provided - auto-detect both
     if let Some(ref name) = args.process {
         if args.pid.is_some() || args.target.is_some() {
-            anyhow::bail!(
+            return Err(UsageError(
                 "Cannot use PROCESS argument with --pid or --target.\n\n\
                  Use either:\n  \
                  hud my-app              (auto-detect)\n  \
                  hud --pid 1234          (explicit PID)"
-            );
+                    .to_string(),
+            )
+            .into());
         }
         let info = find_process_by_name(name)?;
         let target = info.exe_path.to_string_lossy().into_owned();
@@ -105,13 +126,15 @@ fn resolve_pid_and_target(args: &Args) -> Result<(i32, String)> {
     }
 
     // No PID or process name - show usage
-    anyhow::bail!(
+    Err(UsageError(
         "Missing required argument: PROCESS or --pid\n\n\
          Usage:\n  \
          hud my-app              Auto-detect PID and binary\n  \
          hud --pid 1234          Explicit PID, auto-detect binary\n\n\
          Run 'hud --help' for more options"
+            .to_string(),
     )
+    .into())
 }
 
 /// Discover Tokio worker threads using a 4-step fallback chain and register
@@ -121,6 +144,9 @@ fn resolve_pid_and_target(args: &Args) -> Result<(i32, String)> {
 /// 2. Try the default prefixes (`tokio-runtime-w`, `tokio-rt-worker`).
 /// 3. Stack-based discovery: sample stack traces for 500ms and classify threads.
 /// 4. Largest thread group heuristic (original fallback).
+///
+/// Returns a tracker that keeps matching the same thread names, so workers
+/// of runtimes started later are picked up by [`refresh_workers`].
 fn discover_and_register_workers(
     bpf: &mut aya::Ebpf,
     pid: i32,
@@ -128,10 +154,13 @@ fn discover_and_register_workers(
     ring_buf: &mut RingBuf<aya::maps::MapData>,
     stack_traces: &StackTraceMap<aya::maps::MapData>,
     symbolizer: &Symbolizer,
-    memory_range: Option<hud::symbolization::MemoryRange>,
-) -> Result<usize> {
+    module_map: Option<&ModuleMap>,
+) -> Result<WorkerTracker> {
     use hud::profiling::worker_discovery;
 
+    let default_prefixes =
+        || worker_discovery::DEFAULT_PREFIXES.iter().map(ToString::to_string).collect();
+
     // Step (a): If --workers given, use that prefix only (no fallback)
     if let Some(prefix) = worker_prefix {
         let threads = worker_discovery::list_process_threads(Pid(pid))?;
@@ -139,7 +168,8 @@ fn discover_and_register_workers(
         if workers.is_empty() {
             warn!("No workers found matching prefix \"{prefix}\"");
         }
-        return register_workers_in_ebpf(bpf, pid, &workers);
+        register_workers_in_ebpf(bpf, pid, &workers)?;
+        return Ok(WorkerTracker::new(vec![prefix.to_string()], &workers));
     }
 
     // Step (b): Try default prefixes (covers old and new Tokio naming)
@@ -147,7 +177,8 @@ fn discover_and_register_workers(
     for prefix in worker_discovery::DEFAULT_PREFIXES {
         let workers = worker_discovery::collect_workers(&threads, prefix);
         if !workers.is_empty() {
-            return register_workers_in_ebpf(bpf, pid, &workers);
+            register_workers_in_ebpf(bpf, pid, &workers)?;
+            return Ok(WorkerTracker::new(default_prefixes(), &workers));
         }
     }
 
@@ -157,13 +188,14 @@ fn discover_and_register_workers(
         ring_buf,
         stack_traces,
         symbolizer,
-        memory_range,
+        module_map,
         Pid(pid),
         Duration::from_millis(500),
     )?;
     if !stack_workers.is_empty() {
         info!("Stack-based discovery found {} worker threads", stack_workers.len());
-        return register_workers_in_ebpf(bpf, pid, &stack_workers);
+        register_workers_in_ebpf(bpf, pid, &stack_workers)?;
+        return Ok(WorkerTracker::from_workers(&stack_workers));
     }
 
     // Step (d): Fall back to largest thread group heuristic.
@@ -173,26 +205,203 @@ fn discover_and_register_workers(
         let workers = worker_discovery::collect_workers(&threads, &disc_prefix);
         if !workers.is_empty() {
             info!("Auto-discovered {} workers with prefix \"{}\"", workers.len(), disc_prefix);
-            return register_workers_in_ebpf(bpf, pid, &workers);
+            register_workers_in_ebpf(bpf, pid, &workers)?;
+            return Ok(WorkerTracker::new(vec![disc_prefix], &workers));
         }
     }
 
+    // Keep watching for a default-named runtime that starts later
     warn!("No Tokio worker threads found! Make sure the target is a Tokio app.");
-    Ok(0)
+    Ok(WorkerTracker::new(default_prefixes(), &[]))
+}
+
+/// Re-scan `/proc` and sync the eBPF worker map with runtimes that started
+/// or stopped since the last scan.
+///
+/// Map failures are logged and never stop profiling: workers that could not
+/// be registered are retried on the next scan.
+///
+/// Returns `None` if nothing changed (or the process is gone).
+fn refresh_workers(
+    bpf: &mut aya::Ebpf,
+    pid: i32,
+    tracker: &mut WorkerTracker,
+) -> Option<WorkerChanges> {
+    let threads = hud::profiling::worker_discovery::list_process_threads(Pid(pid)).ok()?;
+    let mut registry = match EbpfWorkerRegistry::new(bpf, pid) {
+        Ok(registry) => registry,
+        Err(e) => {
+            warn!("Cannot update worker map: {e:#}");
+            return None;
+        }
+    };
+
+    let unregistered_before = tracker.unregistered();
+    let changes = tracker.refresh(&threads, &mut registry);
+    let unregistered = tracker.unregistered();
+    if unregistered > 0 && unregistered != unregistered_before {
+        warn!(
+            "{unregistered} worker threads could not be registered (worker map full?); \
+             retrying on the next scan"
+        );
+    }
+    if changes.is_empty() {
+        return None;
+    }
+
+    info!(
+        "Workers changed: +{} -{} ({} active)",
+        changes.added.len(),
+        changes.removed.len(),
+        changes.active
+    );
+    Some(changes)
+}
+
+/// Write the export's checkpoint, then the events held since the last drain.
+///
+/// The checkpoint goes first, and replaces the old one in a single rename, so
+/// the side file always covers every event already in the export.
+fn drain_export(processor: &mut EventProcessor, out: &mut dyn Write, export: &Path) -> Result<()> {
+    if let Some(checkpoint) = processor.checkpoint_exporter()? {
+        let path = checkpoint_path(export);
+        let mut tmp = path.clone().into_os_string();
+        tmp.push(".tmp");
+        std::fs::write(&tmp, checkpoint)
+            .and_then(|()| std::fs::rename(&tmp, &path))
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+    processor.drain_exporter(out)
+}
+
+/// Feed a session saved by `hud record` into the TUI.
+///
+/// `speed` paces events by their recorded timestamps (2.0 = twice as fast);
+/// 0 loads the whole session at once.
+fn replay_session(path: &Path, speed: f64, window_secs: u64) -> Result<()> {
+    let mut reader = SessionReader::open(path)?;
+    let pid = reader.config().pid;
+
+    let (event_tx, event_rx) = bounded(1000);
+    let (worker_tx, worker_rx) = bounded(16);
+    let _ = worker_tx
+        .try_send(WorkerChanges { active: reader.config().workers.len(), ..Default::default() });
+
+    std::thread::spawn(move || {
+        let replay_start = Instant::now();
+        let mut first_timestamp = None;
+        loop {
+            let record = match reader.next_record() {
+                Ok(Some(record)) => record,
+                Ok(None) => break,
+                Err(e) => {
+                    warn!("Replay stopped: {e:#}");
+                    break;
+                }
+            };
+            let delivered = match record {
+                Record::Event { trace, .. } => {
+                    if speed > 0.0 {
+                        let first = *first_timestamp.get_or_insert(trace.timestamp);
+                        let due =
+                            Duration::from_secs_f64((trace.timestamp - first).max(0.0) / speed);
+                        if let Some(wait) = due.checked_sub(replay_start.elapsed()) {
+                            std::thread::sleep(wait);
+                        }
+                    }
+                    event_tx.send(trace).is_ok()
+                }
+                Record::Workers(changes) => worker_tx.send(changes).is_ok(),
+            };
+            // The TUI was closed
+            if !delivered {
+                break;
+            }
+        }
+    });
+
+    tui::run_live(event_rx, worker_rx, None, Some(pid), window_secs)
+}
+
+/// Compare two captures and print the per-function changes
+fn diff_captures(
+    before_path: &Path,
+    after_path: &Path,
+    format: DiffFormat,
+    flamegraph: Option<&Path>,
+) -> Result<()> {
+    let before = Capture::load(before_path)?;
+    let after = Capture::load(after_path)?;
+
+    let diff = CaptureDiff::new(
+        &before_path.display().to_string(),
+        &before,
+        &after_path.display().to_string(),
+        &after,
+    );
+    let mut stdout = std::io::stdout().lock();
+    diff.write(format, &mut stdout)?;
+
+    if let Some(path) = flamegraph {
+        let mut writer = File::create(path)
+            .map(BufWriter::new)
+            .with_context(|| format!("Failed to create {}", path.display()))?;
+        write_differential_folded(&before, &after, &mut writer)?;
+        writer.flush().context("Failed to write differential stacks")?;
+        eprintln!("saved: {}", path.display());
+    }
+    Ok(())
 }
 
 #[tokio::main]
 async fn run() -> Result<()> {
     let args = Args::parse();
 
+    match args.command {
+        Some(Command::Record { output, profile }) => profile_process(profile, Some(output)).await,
+        Some(Command::Replay { file, speed, window }) => replay_session(&file, speed, window),
+        Some(Command::Diff { before, after, format, flamegraph }) => {
+            diff_captures(&before, &after, format, flamegraph.as_deref())
+        }
+        None => profile_process(args.profile, None).await,
+    }
+}
+
+/// Profile a live process, optionally recording the session to `record`.
+async fn profile_process(args: ProfileArgs, record: Option<PathBuf>) -> Result<()> {
     let quiet = args.quiet;
 
+    if args.headless
+        && args.export.is_none()
+        && args.report.is_none()
+        && args.assertions.is_empty()
+        && record.is_none()
+    {
+        return Err(UsageError(
+            "Missing required argument: --headless needs --export FILE, --report FILE \
+             or --assert RULE (or use `hud record`)"
+                .to_string(),
+        )
+        .into());
+    }
+
+    if (args.group_by != ProfileGroup::None || args.hide_runtime)
+        && !(args.export.is_some() && args.format.takes_stack_options())
+    {
+        return Err(UsageError(
+            "Invalid argument: --group-by and --hide-runtime only apply to --export with \
+             --format folded or speedscope"
+                .to_string(),
+        )
+        .into());
+    }
+
     // Live profiling (with or without TUI)
     // Resolve PID and target path from arguments
     let (pid, target_path) = resolve_pid_and_target(&args)?;
 
     // Run pre-flight checks before anything else
-    run_preflight_checks(&target_path, quiet)?;
+    let task_id_symbol = run_preflight_checks(&target_path, &args.debug_dirs, quiet)?;
     check_process_exists(pid)?;
     check_proc_access(pid)?;
 
@@ -206,9 +415,11 @@ async fn run() -> Result<()> {
     let mut bpf = load_ebpf_program()?;
     init_ebpf_logger(&mut bpf);
 
-    let task_id_attached = attach_task_id_uprobe(&mut bpf, &target_path, Some(pid))?;
-    if !task_id_attached {
-        eprintln!("warning: task IDs unavailable (symbol inlined in release build)");
+    // A missing symbol was already reported by the pre-flight checks
+    let task_ids = task_id_symbol
+        .is_some_and(|symbol| attach_task_id_uprobe(&mut bpf, &target_path, &symbol, Some(pid)));
+    if !task_ids {
+        info!("Task ID tracking disabled");
     }
 
     // Start perf sampling early so stack-based discovery can collect samples
@@ -221,31 +432,33 @@ async fn run() -> Result<()> {
     )?;
 
     // ── Symbolization setup (needed for stack-based discovery) ──────────
-    let memory_range = match parse_memory_maps(pid, &target_path) {
-        Ok(range) => {
+    let module_map = match ModuleMap::load(pid, &target_path) {
+        Ok(module) => {
+            let range = module.range();
             info!("Found memory range: 0x{:x} - 0x{:x}", range.start, range.end);
-            Some(range)
+            Some(module)
         }
         Err(e) => {
             warn!("Failed to get memory range: {e}. Symbol resolution may not work.");
             None
         }
     };
-    let symbolizer = Symbolizer::new(&target_path).context("Failed to create symbolizer")?;
+    let symbolizer = Symbolizer::with_debug_dirs(&target_path, &args.debug_dirs)
+        .context("Failed to create symbolizer")?;
 
     // ── Worker discovery: 4-step fallback chain ─────────────────────────
-    let worker_count = discover_and_register_workers(
+    let mut worker_tracker = discover_and_register_workers(
         &mut bpf,
         pid,
         args.workers.as_deref(),
         &mut ring_buf,
         &stack_traces,
         &symbolizer,
-        memory_range,
+        module_map.as_ref(),
     )?;
 
     if !quiet {
-        println!("workers: {worker_count}");
+        println!("workers: {}", worker_tracker.len());
     }
 
     // ── Phase 2: Attach sched_switch (after workers are registered) ─────
@@ -259,20 +472,21 @@ async fn run() -> Result<()> {
     while ring_buf.next().is_some() {}
 
     // ── Rest of setup (unchanged) ───────────────────────────────────────
-    let stack_resolver = StackResolver::new(&symbolizer, memory_range);
+    let stack_resolver = StackResolver::new(&symbolizer, module_map.clone());
 
     // Initialize trace event exporter if export requested
-    let trace_exporter = args
+    // Stacks are resolved once by the event processor and shared with it
+    let stack_options = StackOptions { group_by: args.group_by, hide_runtime: args.hide_runtime };
+    let trace_exporter = args.export.as_ref().map(|_| args.format.exporter(stack_options));
+
+    // Created up front: events stream into it during the session
+    let mut export_out = args
         .export
         .as_ref()
-        .map(|_| -> Result<_> {
-            let export_symbolizer = Symbolizer::new(&target_path)
-                .context("Failed to create symbolizer for trace export")?;
-            let mut exporter = TraceEventExporter::new(export_symbolizer);
-            if let Some(range) = memory_range {
-                exporter.set_memory_range(range);
-            }
-            Ok(exporter)
+        .map(|path| {
+            File::create(path)
+                .map(BufWriter::new)
+                .with_context(|| format!("Failed to create trace output file {}", path.display()))
         })
         .transpose()?;
 
@@ -282,38 +496,108 @@ async fn run() -> Result<()> {
         }
     }
 
+    // Session metadata for the recording header and the report
+    let started_at = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH);
+    let mut session = SessionConfig {
+        hud_version: env!("CARGO_PKG_VERSION").to_string(),
+        pid,
+        target: target_path.clone(),
+        threshold_ms: args.threshold,
+        window_secs: args.window,
+        started_at_ms: started_at.map_or(0, |d| d.as_secs() * 1000 + d.subsec_millis() as u64),
+        workers: worker_tracker.workers().cloned().collect(),
+    };
+
+    // Open output files before profiling so a bad path fails early
+    let recorder =
+        record.as_ref().map(|path| SessionRecorder::create(path, &session)).transpose()?;
+    let report_out = args
+        .report
+        .as_ref()
+        .map(|path| {
+            File::create(path)
+                .map(BufWriter::new)
+                .with_context(|| format!("Failed to create report file {}", path.display()))
+        })
+        .transpose()?;
+
+    if !quiet {
+        if let Some(ref record_path) = record {
+            println!("record: {}", record_path.display());
+        }
+    }
+
     // Launch TUI in separate thread if not headless
-    let (tui_handle, event_tx) = if args.headless {
-        (None, None)
+    let (tui_handle, event_tx, worker_tx, bookmark_rx) = if args.headless {
+        (None, None, None, None)
     } else {
         let (event_tx, event_rx) = bounded(1000);
+        let (worker_tx, worker_rx) = bounded(16);
+        let (bookmark_tx, bookmark_rx) = bounded(16);
+
+        // Tell the TUI how many workers are registered before any change arrives
+        let _ = worker_tx
+            .try_send(WorkerChanges { active: worker_tracker.len(), ..Default::default() });
 
         // Spawn TUI thread
         let tui_pid = Some(pid);
         let window_secs = args.window;
-        let handle = std::thread::spawn(move || tui::run_live(event_rx, tui_pid, window_secs));
+        let handle = std::thread::spawn(move || {
+            tui::run_live(event_rx, worker_rx, Some(bookmark_tx), tui_pid, window_secs)
+        });
 
-        (Some(handle), Some(event_tx))
+        (Some(handle), Some(event_tx), Some(worker_tx), Some(bookmark_rx))
     };
 
+    // Shared libraries are symbolized on demand from their own files
+    let mappings = parse_executable_mappings(pid).unwrap_or_else(|e| {
+        warn!("Failed to list shared libraries: {e}. Library frames will show addresses.");
+        Vec::new()
+    });
+    // Exclude the executable by its mapped path, which may differ from --target
+    let executable = module_map.as_ref().map_or(target_path.as_str(), |m| m.path.as_str());
+    let libraries =
+        SymbolizerSet::new(mappings.clone(), executable).with_debug_dirs(args.debug_dirs.clone());
+
+    // Follow dlopen and re-exec for the rest of the session
+    let maps_rx = spawn_maps_refresher(pid, mappings);
+
     // Create event processor with all dependencies
     let mut processor = EventProcessor::new(
         args.headless,
         stack_resolver,
         &symbolizer,
-        memory_range,
+        module_map,
+        libraries,
         trace_exporter,
         event_tx,
     );
+    if let Some(recorder) = recorder {
+        processor.set_recorder(recorder);
+    }
+    if report_out.is_some() || !args.assertions.is_empty() {
+        processor.enable_hotspot_stats();
+    }
 
     // Status tracking
     let mut last_status_time = Instant::now();
     let mut stats_timer = Instant::now();
+    let mut worker_refresh_timer = Instant::now();
+    let mut export_flush_timer = Instant::now();
 
     // Setup Ctrl+C handler
     let ctrl_c = tokio::signal::ctrl_c();
     tokio::pin!(ctrl_c);
 
+    // Headless runs are stopped with SIGTERM by CI and service managers;
+    // finish the export instead of dying mid-file. The TUI keeps the
+    // default so `kill` still ends an interactive session.
+    let mut sigterm = if args.headless {
+        Some(signal(SignalKind::terminate()).context("Failed to install SIGTERM handler")?)
+    } else {
+        None
+    };
+
     // Track start time for duration limit
     let profiling_start = Instant::now();
     let duration_limit =
@@ -335,6 +619,12 @@ async fn run() -> Result<()> {
             }
         }
 
+        // Quitting the TUI ends the session
+        if tui_handle.as_ref().is_some_and(std::thread::JoinHandle::is_finished) {
+            exit_reason = "TUI closed";
+            break;
+        }
+
         // Check if target process still exists
         if !std::path::Path::new(&proc_path).exists() {
             exit_reason = "process exited";
@@ -347,6 +637,22 @@ async fn run() -> Result<()> {
             last_status_time = std::time::Instant::now();
         }
 
+        // Pick up workers of runtimes created (or dropped) since the last scan
+        if worker_refresh_timer.elapsed() >= WORKER_REFRESH_INTERVAL {
+            worker_refresh_timer = Instant::now();
+            if let Some(changes) = refresh_workers(&mut bpf, pid, &mut worker_tracker) {
+                processor.record_worker_changes(&changes);
+                if let Some(tx) = &worker_tx {
+                    let _ = tx.try_send(changes);
+                }
+            }
+        }
+
+        // Apply the latest memory layout before resolving new stacks
+        if let Some(mappings) = maps_rx.try_iter().last() {
+            processor.update_mappings(mappings);
+        }
+
         // Process all available events
         while let Some(item) = ring_buf.next() {
             let bytes: &[u8] = &item;
@@ -364,6 +670,20 @@ async fn run() -> Result<()> {
             processor.process_event(event, &stack_traces);
         }
 
+        // Bookmarks set in the TUI go into the export
+        for bookmark in bookmark_rx.iter().flat_map(crossbeam_channel::Receiver::try_iter) {
+            processor.add_bookmark(&bookmark);
+        }
+
+        // Stream the export so long sessions are not held in memory
+        if export_flush_timer.elapsed() >= EXPORT_FLUSH_INTERVAL {
+            export_flush_timer = Instant::now();
+            if let (Some(out), Some(path)) = (export_out.as_mut(), &args.export) {
+                drain_export(&mut processor, out, path).context("Failed to write trace export")?;
+            }
+            processor.flush_recorder();
+        }
+
         // Print statistics every 10 seconds in headless mode
         if args.headless && stats_timer.elapsed() > Duration::from_secs(10) {
             display_statistics(&processor.stats);
@@ -378,6 +698,10 @@ async fn run() -> Result<()> {
             _ = &mut ctrl_c => {
                 break;
             }
+            Some(()) = async { sigterm.as_mut()?.recv().await } => {
+                exit_reason = "terminated";
+                break;
+            }
         }
     }
 
@@ -385,7 +709,7 @@ async fn run() -> Result<()> {
     if !quiet || args.headless {
         let elapsed = profiling_start.elapsed();
         eprintln!(
-            "\n{}: {:.1}s, {} events (perf: {}, stack_ok: {}, stack_fail: {}, sched: {}, pool_filtered: {}, tui: {} sent / {} no-user-code)",
+            "\n{}: {:.1}s, {} events (perf: {}, stack_ok: {}, stack_fail: {}, sched: {} ({} attributed), pool_filtered: {}, tui: {} sent / {} no-user-code)",
             exit_reason,
             elapsed.as_secs_f64(),
             processor.event_count,
@@ -393,6 +717,7 @@ async fn run() -> Result<()> {
             processor.perf_stack_ok,
             processor.perf_stack_fail,
             processor.scheduler_event_count,
+            processor.correlator.attributed,
             processor.blocking_pool_filtered,
             processor.tui_sent,
             processor.tui_no_user_code,
@@ -404,22 +729,82 @@ async fn run() -> Result<()> {
         // TUI will exit when event channel is closed (happens when this scope ends)
         handle.join().ok();
     }
+    // Bookmarks set just before the TUI was closed
+    for bookmark in bookmark_rx.iter().flat_map(crossbeam_channel::Receiver::try_iter) {
+        processor.add_bookmark(&bookmark);
+    }
 
     // DEBUG: Check perf_event counters
     print_perf_event_diagnostics(&mut bpf)?;
 
     // Export trace if enabled
-    if let Some(exporter) = processor.take_exporter() {
+    if let (Some(exporter), Some(mut writer)) = (processor.take_exporter(), export_out) {
         let export_path = args.export.unwrap(); // Safe because we checked earlier
 
-        let file = File::create(&export_path).context("Failed to create trace output file")?;
-        let writer = BufWriter::new(file);
-        exporter.export(writer).context("Failed to export trace")?;
+        exporter.export(&mut writer).context("Failed to export trace")?;
+        writer.flush().context("Failed to export trace")?;
+        // The finished file has everything the checkpoint held
+        let checkpoint = checkpoint_path(&export_path);
+        if checkpoint.exists() {
+            if let Err(e) = std::fs::remove_file(&checkpoint) {
+                warn!("Could not remove {}: {e}", checkpoint.display());
+            }
+        }
 
         if !quiet {
             println!("saved: {}", export_path.display());
         }
     }
 
+    // Write the summary report, listing the workers present at the end
+    let hotspot_stats = processor.take_hotspot_stats();
+    session.workers = worker_tracker.workers().cloned().collect();
+    if let (Some(stats), Some(mut writer)) = (&hotspot_stats, report_out) {
+        let report_path = args.report.unwrap(); // Safe: report_out exists only with --report
+        let report = Report::new(
+            &session,
+            profiling_start.elapsed().as_secs_f64(),
+            exit_reason,
+            processor.diagnostics(),
+            stats,
+        );
+        report
+            .write(args.report_format, args.junit_threshold, &mut writer)
+            .context("Failed to write report")?;
+        writer.flush().context("Failed to write report")?;
+
+        if !quiet {
+            println!("saved: {}", report_path.display());
+        }
+    }
+
+    // Flush the session recording
+    if let (Some(recorder), Some(record_path)) = (processor.take_recorder(), record) {
+        let events = recorder.events;
+        recorder.finish().context("Failed to write session recording")?;
+
+        if !quiet {
+            println!("saved: {} ({events} events)", record_path.display());
+        }
+    }
+
+    // Check the blocking budget last, so every output file is complete
+    // before a failure sets the exit code
+    if let (Some(stats), false) = (&hotspot_stats, args.assertions.is_empty()) {
+        let workers: Vec<u32> = session.workers.iter().map(|w| w.worker_id).collect();
+        let outcomes = budget::check_all(&args.assertions, stats, &workers);
+        let failed = outcomes.iter().filter(|o| !o.passed).count();
+        if failed > 0 || !quiet {
+            eprintln!();
+            budget::write_table(&outcomes, &mut std::io::stderr())?;
+        }
+        if failed > 0 {
+            anyhow::bail!(
+                "blocking budget exceeded: {failed} of {} assertions failed",
+                outcomes.len()
+            );
+        }
+    }
+
     Ok(())
 }
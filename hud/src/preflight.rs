@@ -7,21 +7,30 @@
 
 use anyhow::{bail, Context, Result};
 use object::{Object, ObjectSection};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+use crate::profiling::ebpf_setup::TASK_ID_FUNCTION;
+use crate::symbolization::{find_debug_file, find_function, has_dwarf, FunctionSymbol};
 
 /// Minimum kernel version required for eBPF features used by hud
 const MIN_KERNEL_VERSION: (u32, u32) = (5, 8);
 
 /// Run all pre-flight checks before eBPF loading
 ///
+/// Returns the task ID hook point ([`TASK_ID_FUNCTION`]) if the binary has
+/// one, so the binary is only read and parsed here.
+///
 /// # Errors
 /// Returns error if any system requirement is not met (permissions, kernel version, binary).
-pub fn run_preflight_checks(target_path: &str, quiet: bool) -> Result<()> {
+pub fn run_preflight_checks(
+    target_path: &str,
+    debug_dirs: &[PathBuf],
+    quiet: bool,
+) -> Result<Option<FunctionSymbol>> {
     check_privileges()?;
     check_kernel_version()?;
     check_binary_exists(target_path)?;
-    check_debug_symbols(target_path, quiet)?;
-    Ok(())
+    check_binary_symbols(target_path, debug_dirs, quiet)
 }
 
 /// Check if running with sufficient privileges for eBPF
@@ -96,33 +105,67 @@ fn check_binary_exists(target_path: &str) -> Result<()> {
     Ok(())
 }
 
-/// Check if the binary has debug symbols for proper stack trace resolution
-fn check_debug_symbols(target_path: &str, quiet: bool) -> Result<()> {
-    if quiet {
-        return Ok(());
-    }
-
+/// Report on the target's symbols: debug info and the task ID hook point
+///
+/// With `quiet`, only a missing task ID hook is reported, since it silently
+/// disables a feature.
+fn check_binary_symbols(
+    target_path: &str,
+    debug_dirs: &[PathBuf],
+    quiet: bool,
+) -> Result<Option<FunctionSymbol>> {
     let file_data = std::fs::read(target_path)
         .with_context(|| format!("Failed to read binary: {target_path}"))?;
 
     let Ok(obj) = object::File::parse(&*file_data) else {
         // Not a valid object file, let later stages handle it
-        return Ok(());
+        return Ok(None);
     };
 
+    if !quiet {
+        check_debug_symbols(Path::new(target_path), &obj, debug_dirs);
+    }
+    Ok(check_task_id_symbol(&file_data, quiet))
+}
+
+/// Check if the binary has debug symbols for proper stack trace resolution
+///
+/// A stripped binary is fine if its separate debug file can be found.
+fn check_debug_symbols(target_path: &Path, obj: &object::File, debug_dirs: &[PathBuf]) {
+    if let Some(debug_file) = find_debug_file(target_path, obj, debug_dirs) {
+        println!("debug info: {}", debug_file.display());
+        return;
+    }
+
     // Check for .debug_info section (DWARF debug info)
-    let has_debug_info = obj.section_by_name(".debug_info").is_some_and(|s| s.size() > 0);
+    let has_debug_info = has_dwarf(obj);
 
     // Check for .symtab (symbol table - present in non-stripped binaries)
     let has_symtab = obj.section_by_name(".symtab").is_some_and(|s| s.size() > 0);
 
     if !has_debug_info && !has_symtab {
-        eprintln!("warning: binary stripped, stack traces will show addresses only");
+        eprintln!(
+            "warning: binary stripped and no debug file found (see --debug-dir), stack traces will show addresses only"
+        );
     } else if !has_debug_info {
-        eprintln!("warning: no DWARF debug info, source locations unavailable");
+        eprintln!("warning: no DWARF debug info or debug file, source locations unavailable");
     }
+}
 
-    Ok(())
+/// Check for the function the task ID uprobe attaches to
+///
+/// Matched by demangled path, so any tokio version works unless the
+/// function was inlined (common in release builds) or the binary is stripped.
+fn check_task_id_symbol(file_data: &[u8], quiet: bool) -> Option<FunctionSymbol> {
+    let symbol = find_function(file_data, TASK_ID_FUNCTION);
+    match &symbol {
+        Some(symbol) if !quiet => {
+            println!("task ids: {TASK_ID_FUNCTION} at offset 0x{:x}", symbol.file_offset);
+        }
+        Some(_) => {}
+        None => eprintln!("warning: {TASK_ID_FUNCTION} not found, task IDs unavailable"),
+    }
+    symbol
 }
 
 /// Check if the target process exists
@@ -7,6 +7,7 @@
 //! - [`load_ebpf_program()`] - Load eBPF bytecode from embedded binary
 //! - [`attach_task_id_uprobe()`] - Attach uprobe for task ID tracking
 //! - [`register_tokio_workers()`] - Discover and register Tokio worker threads
+//! - [`EbpfWorkerRegistry`] - Add and remove workers as runtimes come and go
 //! - [`start_perf_sampling()`] - Set CONFIG and attach `perf_event` sampler
 //! - [`attach_sched_switch()`] - Attach `sched_switch` tracepoint
 //!
@@ -21,7 +22,7 @@
 use anyhow::{Context, Result};
 use aya::{
     include_bytes_aligned,
-    maps::HashMap,
+    maps::{HashMap, MapData, MapError},
     programs::{perf_event, PerfEvent, TracePoint, UProbe},
     Ebpf,
 };
@@ -31,7 +32,8 @@ use log::{info, warn};
 
 use crate::domain::Pid;
 use crate::profiling::online_cpus;
-use crate::profiling::worker_discovery;
+use crate::profiling::worker_discovery::{self, WorkerRegistry};
+use crate::symbolization::FunctionSymbol;
 
 // Alias to distinguish from hud_common::WorkerInfo (the eBPF map struct)
 use crate::profiling::WorkerInfo as DiscoveredWorker;
@@ -57,14 +59,21 @@ pub fn init_ebpf_logger(bpf: &mut Ebpf) {
     }
 }
 
+/// Demangled path of the function the task ID uprobe attaches to
+pub const TASK_ID_FUNCTION: &str = "tokio::runtime::context::set_current_task_id";
+
 /// Attach task ID tracking uprobe (`set_current_task_id`)
 /// Returns true if task ID tracking is available
 ///
-/// # Errors
-/// Returns an error if uprobe attachment fails
-pub fn attach_task_id_uprobe(bpf: &mut Ebpf, target_path: &str, pid: Option<i32>) -> Result<bool> {
-    // Attach uprobe to tokio::runtime::context::set_current_task_id
-    // Note: This symbol may not exist in release builds (gets inlined)
+/// `symbol` is [`TASK_ID_FUNCTION`] in the target binary, as found by the
+/// pre-flight checks (matched by demangled path, so any tokio build works as
+/// long as the function was not inlined).
+pub fn attach_task_id_uprobe(
+    bpf: &mut Ebpf,
+    target_path: &str,
+    symbol: &FunctionSymbol,
+    pid: Option<i32>,
+) -> bool {
     let task_id_attached = if let Some(program) = bpf.program_mut("set_task_id_hook") {
         match program.try_into() {
             Ok(program) => {
@@ -73,19 +82,18 @@ pub fn attach_task_id_uprobe(bpf: &mut Ebpf, target_path: &str, pid: Option<i32>
                     warn!("⚠️  Failed to load set_task_id_hook: {e}");
                     false
                 } else {
-                    match program.attach(
-                        Some("_ZN5tokio7runtime7context19set_current_task_id17h88510a52941c215fE"),
-                        0,
-                        target_path,
-                        pid,
-                    ) {
+                    // Attach by file offset: no symbol name lookup on the aya side
+                    match program.attach(None, symbol.file_offset, target_path, pid) {
                         Ok(_) => {
-                            info!("✓ Attached uprobe: set_current_task_id");
+                            info!(
+                                "✓ Attached uprobe: {} at offset 0x{:x}",
+                                symbol.name, symbol.file_offset
+                            );
                             true
                         }
                         Err(e) => {
                             warn!("⚠️  Could not attach set_task_id_hook: {e}");
-                            warn!("   Task ID tracking unavailable (symbol likely inlined in release build)");
+                            warn!("   Task ID tracking unavailable");
                             false
                         }
                     }
@@ -101,7 +109,7 @@ pub fn attach_task_id_uprobe(bpf: &mut Ebpf, target_path: &str, pid: Option<i32>
         false
     };
 
-    Ok(task_id_attached)
+    task_id_attached
 }
 
 /// Register Tokio worker threads in the `TOKIO_WORKER_THREADS` eBPF map.
@@ -148,26 +156,69 @@ pub fn register_workers_in_ebpf(
     )?;
 
     for worker in workers {
-        let mut comm = [0u8; 16];
-        let bytes = worker.comm.as_bytes();
-        let copy_len = bytes.len().min(16);
-        comm[..copy_len].copy_from_slice(&bytes[..copy_len]);
-
-        let info = WorkerInfo {
-            worker_id: worker.worker_id,
-            pid: pid as u32,
-            comm,
-            is_active: 1,
-            _padding: [0u8; 3],
-        };
-
-        map.insert(worker.tid.0, info, 0)?;
+        map.insert(worker.tid.0, worker_entry(pid, worker), 0)?;
     }
 
     info!("✓ Registered {} Tokio worker threads", workers.len());
     Ok(workers.len())
 }
 
+/// Build the `TOKIO_WORKER_THREADS` map value for a discovered worker
+#[allow(clippy::cast_sign_loss)]
+fn worker_entry(pid: i32, worker: &DiscoveredWorker) -> WorkerInfo {
+    let mut comm = [0u8; 16];
+    let bytes = worker.comm.as_bytes();
+    let copy_len = bytes.len().min(16);
+    comm[..copy_len].copy_from_slice(&bytes[..copy_len]);
+
+    WorkerInfo {
+        worker_id: worker.worker_id,
+        pid: pid as u32,
+        comm,
+        is_active: 1,
+        _padding: [0u8; 3],
+    }
+}
+
+/// The `TOKIO_WORKER_THREADS` eBPF map as a [`WorkerRegistry`], for
+/// [`WorkerTracker::refresh`](worker_discovery::WorkerTracker::refresh).
+///
+/// The map holds at most 256 entries, so exited workers must be dropped for
+/// workers of later runtimes to fit.
+pub struct EbpfWorkerRegistry<'a> {
+    map: HashMap<&'a mut MapData, u32, WorkerInfo>,
+    pid: i32,
+}
+
+impl<'a> EbpfWorkerRegistry<'a> {
+    /// Open the worker map of a loaded eBPF program
+    ///
+    /// # Errors
+    /// Returns an error if the map is missing or has the wrong type
+    pub fn new(bpf: &'a mut Ebpf, pid: i32) -> Result<Self> {
+        let map = HashMap::try_from(
+            bpf.map_mut("TOKIO_WORKER_THREADS").context("TOKIO_WORKER_THREADS map not found")?,
+        )?;
+        Ok(Self { map, pid })
+    }
+}
+
+impl WorkerRegistry for EbpfWorkerRegistry<'_> {
+    fn register(&mut self, worker: &DiscoveredWorker) -> Result<()> {
+        self.map.insert(worker.tid.0, worker_entry(self.pid, worker), 0)?;
+        Ok(())
+    }
+
+    fn unregister(&mut self, worker: &DiscoveredWorker) -> Result<()> {
+        match self.map.remove(&worker.tid.0) {
+            Err(MapError::SyscallError(e)) if e.io_error.raw_os_error() == Some(libc::ENOENT) => {
+                Ok(())
+            }
+            result => Ok(result?),
+        }
+    }
+}
+
 /// Start perf-event sampling: set CONFIG map and attach `perf_event` sampler.
 ///
 /// This is phase 1 of scheduler detection setup. It starts the CPU sampler
@@ -3,6 +3,7 @@
 
 use crate::domain::StackId;
 use crate::profiling::StackResolver;
+use crate::trace_data::TraceEvent;
 use aya::maps::{MapData, StackTraceMap};
 use hud_common::TaskEvent;
 use std::borrow::Borrow;
@@ -11,6 +12,7 @@ use std::borrow::Borrow;
 #[allow(clippy::similar_names)]
 pub fn display_scheduler_detected<T: Borrow<MapData>>(
     event: &TaskEvent,
+    trace_event: &TraceEvent,
     stack_resolver: &StackResolver,
     stack_traces: &StackTraceMap<T>,
 ) {
@@ -30,6 +32,29 @@ pub fn display_scheduler_detected<T: Borrow<MapData>>(
     };
     println!("  state={state_str}");
 
+    // Correlated blocker (see `LatencyCorrelator`)
+    if let Some(ref blocker) = trace_event.blocker {
+        let location = match (&trace_event.file, trace_event.line) {
+            (Some(file), Some(line)) => format!(" at {file}:{line}"),
+            _ => String::new(),
+        };
+        let comm = blocker.comm.as_deref().map(|c| format!(" [{c}]")).unwrap_or_default();
+        let evidence = if blocker.samples > 0 {
+            format!(" ({} samples)", blocker.samples)
+        } else {
+            String::new()
+        };
+        println!(
+            "  blocked by tid={}{comm}{evidence} in {}{location}",
+            blocker.tid, trace_event.name
+        );
+        if let Some(ref kernel_stack) = blocker.kernel_stack {
+            for frame in kernel_stack.iter().take(5) {
+                println!("    [k] {}", frame.function);
+            }
+        }
+    }
+
     // Print stack trace
     let _ = stack_resolver.resolve_and_print(StackId(event.stack_id), stack_traces);
 }
@@ -4,7 +4,7 @@
 //!
 //! ## Event Routing
 //!
-//! - `EVENT_SCHEDULER_DETECTED` → Off-CPU threshold exceeded (blocking detection)
+//! - `EVENT_SCHEDULER_DETECTED` → Off-CPU threshold exceeded (latency interval)
 //! - `TRACE_EXECUTION_{START,END}` → Timeline visualization
 //!
 //! ## Output Modes
@@ -12,6 +12,7 @@
 //! - **Headless**: Print events to stdout
 //! - **Live TUI**: Send to TUI thread via channel
 //! - **Export**: Add to trace.json exporter
+//! - **Record**: Append what the TUI is sent to a session file (`hud record`)
 //!
 //! See [Architecture docs](../../docs/ARCHITECTURE.md) for event flow details.
 
@@ -21,18 +22,23 @@ use hud_common::{
     TaskEvent, DETECTION_PERF_SAMPLE, EVENT_SCHEDULER_DETECTED, TRACE_EXECUTION_END,
     TRACE_EXECUTION_START,
 };
-use log::warn;
+use log::{info, warn};
 use std::borrow::Borrow;
+use std::io::Write;
 use std::sync::Arc;
 
 use super::{
-    display_execution_event, display_scheduler_detected, DetectionStats, MemoryRange, StackResolver,
+    display_execution_event, display_scheduler_detected, Culprit, DetectionStats,
+    LatencyCorrelator, ModuleMap, StackResolver, WorkerChanges,
 };
-use crate::classification::classify_frame;
+use crate::analysis::HotspotStats;
+use crate::classification::{classify_frame, FrameOrigin};
 use crate::domain::StackId;
-use crate::export::TraceEventExporter;
-use crate::symbolization::Symbolizer;
-use crate::trace_data::{StackCache, StackFrame, TraceEvent};
+use crate::export::Exporter;
+use crate::recording::SessionRecorder;
+use crate::report::Diagnostics;
+use crate::symbolization::{KernelSymbols, MemoryMapping, Symbolizer, SymbolizerSet};
+use crate::trace_data::{attribution_frame, Blocker, Bookmark, StackCache, StackFrame, TraceEvent};
 
 /// Encapsulates event processing logic and state
 pub struct EventProcessor<'a> {
@@ -56,15 +62,26 @@ pub struct EventProcessor<'a> {
     pub tui_sent: usize,
     /// Cache for resolved stack traces (bounded by eBPF's 16384 unique stacks)
     stack_cache: StackCache,
+    /// Resolved kernel stacks, kept apart so a reused `stack_id` can't mix kinds
+    kernel_stack_cache: StackCache,
+    /// Per-CPU sample history for attributing scheduler latency to blockers
+    pub correlator: LatencyCorrelator,
+    /// Kernel symbol table for blocker kernel stacks (loaded on first use)
+    kernel_symbols: Option<KernelSymbols>,
 
     // Dependencies (readonly)
     stack_resolver: StackResolver<'a>,
     symbolizer: &'a Symbolizer,
-    memory_range: Option<MemoryRange>,
+    module_map: Option<ModuleMap>,
+    /// Symbolizers for shared libraries (built on first use)
+    libraries: SymbolizerSet,
 
     // Optional outputs
-    trace_exporter: Option<TraceEventExporter>,
+    trace_exporter: Option<Box<dyn Exporter>>,
     event_tx: Option<Sender<TraceEvent>>,
+    recorder: Option<SessionRecorder>,
+    /// Hotspot ranking for `--report`, fed the same events as the TUI
+    hotspots: Option<HotspotStats>,
 }
 
 impl<'a> EventProcessor<'a> {
@@ -74,8 +91,9 @@ impl<'a> EventProcessor<'a> {
         headless: bool,
         stack_resolver: StackResolver<'a>,
         symbolizer: &'a Symbolizer,
-        memory_range: Option<MemoryRange>,
-        trace_exporter: Option<TraceEventExporter>,
+        module_map: Option<ModuleMap>,
+        libraries: SymbolizerSet,
+        trace_exporter: Option<Box<dyn Exporter>>,
         event_tx: Option<Sender<TraceEvent>>,
     ) -> Self {
         Self {
@@ -91,14 +109,96 @@ impl<'a> EventProcessor<'a> {
             tui_no_user_code: 0,
             tui_sent: 0,
             stack_cache: StackCache::new(),
+            kernel_stack_cache: StackCache::new(),
+            correlator: LatencyCorrelator::new(),
+            kernel_symbols: None,
             stack_resolver,
             symbolizer,
-            memory_range,
+            module_map,
+            libraries,
             trace_exporter,
             event_tx,
+            recorder: None,
+            hotspots: None,
         }
     }
 
+    /// Record every event sent to the TUI (`hud record`)
+    ///
+    /// Works without a TUI: events are built for the recorder alone in
+    /// headless mode.
+    pub fn set_recorder(&mut self, recorder: SessionRecorder) {
+        self.recorder = Some(recorder);
+    }
+
+    /// Rank hotspots over the whole session, for the summary report
+    pub fn enable_hotspot_stats(&mut self) {
+        self.hotspots = Some(HotspotStats::new());
+    }
+
+    /// Take the session's hotspot ranking (see `enable_hotspot_stats`)
+    pub fn take_hotspot_stats(&mut self) -> Option<HotspotStats> {
+        self.hotspots.take()
+    }
+
+    /// Counters for the summary report
+    #[must_use]
+    pub fn diagnostics(&self) -> Diagnostics {
+        Diagnostics {
+            events: self.event_count,
+            perf_samples: self.perf_sample_count,
+            stacks_ok: self.perf_stack_ok,
+            stacks_failed: self.perf_stack_fail,
+            scheduler_events: self.scheduler_event_count,
+            scheduler_attributed: self.correlator.attributed,
+            blocking_pool_filtered: self.blocking_pool_filtered,
+        }
+    }
+
+    /// Record a worker re-discovery change, if recording
+    pub fn record_worker_changes(&mut self, changes: &WorkerChanges) {
+        if let Some(recorder) = &mut self.recorder {
+            if let Err(e) = recorder.record_worker_changes(changes) {
+                warn!("Recording stopped: {e}");
+                self.recorder = None;
+            }
+        }
+    }
+
+    /// Flush the session recording to disk, if recording
+    pub fn flush_recorder(&mut self) {
+        if let Some(recorder) = &mut self.recorder {
+            if let Err(e) = recorder.flush() {
+                warn!("Recording stopped: {e}");
+                self.recorder = None;
+            }
+        }
+    }
+
+    /// Apply a new memory layout from the maps refresher (`dlopen`, re-exec)
+    ///
+    /// The executable and libraries are re-located from `mappings`, and
+    /// cached stacks are dropped so they resolve against the new layout.
+    pub fn update_mappings(&mut self, mappings: Vec<MemoryMapping>) {
+        let executable = self.module_map.as_ref().map(|m| m.path.clone()).unwrap_or_default();
+
+        // An empty path means the executable was already lost to an exec
+        if let Some(module) = self.module_map.as_ref().filter(|m| !m.path.is_empty()) {
+            let relocated = module.relocated(&mappings).unwrap_or_else(|| {
+                // exec'd into another binary: its frames resolve as libraries
+                warn!("{executable} is no longer mapped; symbolizing it as a library");
+                ModuleMap::default()
+            });
+            self.stack_resolver.set_module_map(Some(relocated.clone()));
+            self.module_map = Some(relocated);
+        }
+
+        info!("Memory maps changed: {} executable mappings", mappings.len());
+        self.libraries.set_mappings(mappings, &executable);
+        self.stack_cache.clear();
+        self.kernel_stack_cache.clear();
+    }
+
     /// Process a single event
     pub fn process_event<T: std::borrow::Borrow<aya::maps::MapData>>(
         &mut self,
@@ -114,6 +214,13 @@ impl<'a> EventProcessor<'a> {
             }
             TRACE_EXECUTION_START | TRACE_EXECUTION_END => {
                 if event.detection_method == DETECTION_PERF_SAMPLE {
+                    // Every sample is a blocker candidate, so record before any filtering
+                    self.correlator.record_sample(
+                        event.cpu_id,
+                        event.timestamp_ns,
+                        event.tid,
+                        event.stack_id,
+                    );
                     self.perf_sample_count += 1;
                     if event.stack_id >= 0 {
                         self.perf_stack_ok += 1;
@@ -129,11 +236,48 @@ impl<'a> EventProcessor<'a> {
         }
     }
 
+    /// Add a bookmark set in the TUI to the export
+    pub fn add_bookmark(&mut self, bookmark: &Bookmark) {
+        info!("Bookmark at {:.3}s: {}", bookmark.timestamp, bookmark.label);
+        if let Some(ref mut exporter) = self.trace_exporter {
+            exporter.add_bookmark(bookmark);
+        }
+    }
+
+    /// Write the exporter's pending events to `out` and flush it
+    ///
+    /// # Errors
+    /// Returns an error if writing to `out` fails
+    pub fn drain_exporter(&mut self, out: &mut dyn Write) -> anyhow::Result<()> {
+        if let Some(ref mut exporter) = self.trace_exporter {
+            exporter.drain(out)?;
+            out.flush()?;
+        }
+        Ok(())
+    }
+
+    /// The exporter's latest checkpoint, if it changed (see
+    /// [`Exporter::checkpoint`])
+    ///
+    /// # Errors
+    /// Returns an error if encoding the checkpoint fails
+    pub fn checkpoint_exporter(&mut self) -> anyhow::Result<Option<Vec<u8>>> {
+        match self.trace_exporter {
+            Some(ref mut exporter) => exporter.checkpoint(),
+            None => Ok(None),
+        }
+    }
+
     /// Take the trace exporter (for final export)
-    pub fn take_exporter(&mut self) -> Option<TraceEventExporter> {
+    pub fn take_exporter(&mut self) -> Option<Box<dyn Exporter>> {
         self.trace_exporter.take()
     }
 
+    /// Take the session recorder (to flush it)
+    pub fn take_recorder(&mut self) -> Option<SessionRecorder> {
+        self.recorder.take()
+    }
+
     // Private event handlers
 
     fn handle_scheduler_detected<T: std::borrow::Borrow<aya::maps::MapData>>(
@@ -158,9 +302,91 @@ impl<'a> EventProcessor<'a> {
 
         self.stats.scheduler_detected += 1;
 
+        // eBPF captured the thread switched out as the worker resumed; the
+        // correlator looks at what the sampler saw across the whole wait
+        let culprit = self.correlator.correlate(
+            event.cpu_id,
+            event.timestamp_ns,
+            event.duration_ns,
+            event.tid,
+        );
+
+        let needs_trace_event = self.trace_exporter.is_some()
+            || self.headless
+            || (self.has_live_consumer() && event.worker_id != u32::MAX);
+        if !needs_trace_event {
+            return;
+        }
+
+        let trace_event = self.attribute_blocker(&event, culprit, stack_traces);
+
+        if let Some(ref mut exporter) = self.trace_exporter {
+            let blocker = trace_event.blocker.as_ref().map(|b| (b, trace_event.name.as_str()));
+            exporter.add_scheduler_event(&event, blocker);
+        }
+
         if self.headless {
-            display_scheduler_detected(&event, &self.stack_resolver, stack_traces);
+            display_scheduler_detected(&event, &trace_event, &self.stack_resolver, stack_traces);
         }
+
+        // Send to TUI: worker latency intervals only. These bypass the
+        // user-code filter below because the victim has no stack of its own.
+        if event.worker_id != u32::MAX {
+            self.publish_trace_event(&event, trace_event);
+        }
+    }
+
+    /// Build the `TraceEvent` for a scheduler latency event, naming its blocker.
+    ///
+    /// Preference order:
+    /// 1. The thread eBPF saw give up the CPU, if it belongs to the target
+    ///    process and its stack resolves (exact, but only the last instant)
+    /// 2. The correlator's culprit (statistical, over the whole wait window)
+    /// 3. The eBPF thread without a stack (other process, kernel thread):
+    ///    still worth naming, but there is no function to charge
+    ///
+    /// An unresolvable stack leaves the event named "execution", so it is not
+    /// charged to any hotspot.
+    fn attribute_blocker<T: Borrow<MapData>>(
+        &mut self,
+        event: &TaskEvent,
+        culprit: Option<Culprit>,
+        stack_traces: &StackTraceMap<T>,
+    ) -> TraceEvent {
+        let captured = event.blocker_tid != 0;
+        let comm = captured
+            .then(|| String::from_utf8_lossy(event.blocker_comm_bytes()).into_owned())
+            .filter(|comm| !comm.is_empty());
+        let kernel_stack = if captured {
+            self.resolve_kernel_stack(event.blocker_kernel_stack_id, stack_traces)
+        } else {
+            None
+        };
+        let captured_stack = if captured && event.blocker_pid == event.pid {
+            self.resolve_full_stack(event.blocker_stack_id, stack_traces)
+        } else {
+            None
+        };
+
+        let (mut trace_event, tid, samples) = match (captured_stack, culprit) {
+            (Some(stack), _) => (build_trace_event(event, Some(stack)), event.blocker_tid, 0),
+            (None, Some(culprit)) => {
+                let call_stack = self.resolve_full_stack(culprit.stack_id, stack_traces);
+                (build_trace_event(event, call_stack), culprit.tid, culprit.samples)
+            }
+            (None, None) if captured => (build_trace_event(event, None), event.blocker_tid, 0),
+            (None, None) => return build_trace_event(event, None),
+        };
+
+        // Thread name and kernel stack only describe the thread eBPF captured
+        let same_thread = tid == event.blocker_tid;
+        trace_event.blocker = Some(Blocker {
+            tid,
+            samples,
+            comm: comm.filter(|_| same_thread),
+            kernel_stack: kernel_stack.filter(|_| same_thread),
+        });
+        trace_event
     }
 
     fn handle_trace_execution<T: Borrow<MapData>>(
@@ -183,13 +409,16 @@ impl<'a> EventProcessor<'a> {
             return;
         }
 
-        // Get the top frame address for symbol resolution (for exporter)
-        let top_frame_addr =
-            StackResolver::get_top_frame_addr(StackId(event.stack_id), stack_traces);
-
-        // Add to trace exporter if enabled
-        if let Some(ref mut exporter) = self.trace_exporter {
-            exporter.add_event(&event, top_frame_addr);
+        // Add to trace exporter if enabled, with the full resolved stack
+        if self.trace_exporter.is_some() {
+            let call_stack = if event.event_type == TRACE_EXECUTION_START {
+                self.resolve_full_stack(event.stack_id, stack_traces)
+            } else {
+                None
+            };
+            if let Some(ref mut exporter) = self.trace_exporter {
+                exporter.add_event(&event, call_stack.as_ref());
+            }
         }
 
         // Send to TUI: only worker thread events with valid stacks and user code.
@@ -197,7 +426,7 @@ impl<'a> EventProcessor<'a> {
         //   - sched_switch events (stack_id=-1)
         //   - non-worker threads (main thread, blocking pool) via worker_id
         //   - pure runtime samples with no user code on the stack
-        if self.event_tx.is_some()
+        if self.has_live_consumer()
             && event.event_type == TRACE_EXECUTION_START
             && event.stack_id >= 0
             && event.worker_id != u32::MAX
@@ -211,11 +440,7 @@ impl<'a> EventProcessor<'a> {
                 .is_some_and(|stack| stack.iter().any(|f| f.is_user_code));
 
             if has_user_code {
-                // Non-blocking send (drop if TUI is slow)
-                if let Some(ref tx) = self.event_tx {
-                    let _ = tx.try_send(trace_event);
-                    self.tui_sent += 1;
-                }
+                self.publish_trace_event(&event, trace_event);
             } else {
                 self.tui_no_user_code += 1;
             }
@@ -228,6 +453,30 @@ impl<'a> EventProcessor<'a> {
         }
     }
 
+    /// True if something consumes resolved trace events: the TUI, the
+    /// recorder, or the hotspot stats behind `--report` and `--assert`
+    fn has_live_consumer(&self) -> bool {
+        self.event_tx.is_some() || self.recorder.is_some() || self.hotspots.is_some()
+    }
+
+    /// Count `trace_event` for the report and record it, then hand it to the TUI
+    fn publish_trace_event(&mut self, event: &TaskEvent, trace_event: TraceEvent) {
+        if let Some(stats) = &mut self.hotspots {
+            stats.record_event(&trace_event);
+        }
+        if let Some(recorder) = &mut self.recorder {
+            if let Err(e) = recorder.record_event(event, &trace_event) {
+                warn!("Recording stopped: {e}");
+                self.recorder = None;
+            }
+        }
+        // Non-blocking send (drop if TUI is slow)
+        if let Some(ref tx) = self.event_tx {
+            let _ = tx.try_send(trace_event);
+            self.tui_sent += 1;
+        }
+    }
+
     /// Resolve full call stack from eBPF stack trace map.
     ///
     /// This is the core symbolization logic. It takes a raw `stack_id` from eBPF,
@@ -247,11 +496,13 @@ impl<'a> EventProcessor<'a> {
     /// - Cache hit: O(1) `HashMap` lookup + Arc clone
     /// - Cache miss: `O(stack_depth)` × O(DWARF lookup) - expensive but rare after warmup
     ///
-    /// # Address Adjustment (PIE)
+    /// # Address Translation
     ///
-    /// Modern executables use Position-Independent Executables (PIE) for ASLR.
-    /// The addresses in the stack trace are runtime addresses, but DWARF debug info
-    /// uses file offsets. We subtract the executable's base address to convert.
+    /// Stack addresses are runtime addresses, but DWARF uses ELF virtual
+    /// addresses. Each address is translated by the [`ModuleMap`] of the file
+    /// it falls in: its /proc/pid/maps mapping gives the file offset, and the
+    /// file's `PT_LOAD` headers turn that offset into a vaddr. This handles PIE
+    /// (ASLR), non-PIE and segments whose vaddr differs from their offset alike.
     fn resolve_full_stack<T: Borrow<MapData>>(
         &mut self,
         stack_id: i64,
@@ -300,16 +551,11 @@ impl<'a> EventProcessor<'a> {
             // or in a shared library (system/third-party code).
             //
             // For user code: Convert runtime address to file offset for DWARF lookup
-            // For library code: We can't symbolize (no debug info), just show address
-            let (file_offset, is_user_code) = if let Some(range) = self.memory_range {
-                if range.contains(addr) {
-                    // Address is within main executable's memory range
-                    // Subtract base address to get file offset for DWARF
-                    (addr - range.start, true)
-                } else {
-                    // Address is outside main executable (shared library)
-                    (addr, false)
-                }
+            // For library code: Resolve against the library's own file
+            let (file_offset, is_user_code) = if let Some(module) = &self.module_map {
+                // In the main executable: translate to the ELF vaddr DWARF uses.
+                // Outside it: shared library
+                module.to_elf_vaddr(addr).map_or((addr, false), |vaddr| (vaddr, true))
             } else {
                 // No memory range info - assume everything is user code
                 // This happens if we couldn't parse /proc/<pid>/maps
@@ -338,7 +584,7 @@ impl<'a> EventProcessor<'a> {
                     });
                 } else {
                     // DWARF lookup failed - show raw address (stripped binary?)
-                    let origin = crate::classification::FrameOrigin::Unknown;
+                    let origin = FrameOrigin::Unknown;
                     resolved_frames.push(StackFrame {
                         function: format!("0x{addr:x}"),
                         file: None,
@@ -347,10 +593,25 @@ impl<'a> EventProcessor<'a> {
                         is_user_code: origin.is_user_code(),
                     });
                 }
+            } else if let Some((_, resolved)) = self.libraries.resolve(addr) {
+                // Library code - DWARF if the library ships it, else its symbol table
+                let frame = &resolved.frames[0];
+                let file_str = frame.location.as_ref().and_then(|loc| loc.file.clone());
+                let origin = classify_frame(&frame.function, file_str.as_deref(), false);
+                resolved_frames.push(StackFrame {
+                    function: if origin == FrameOrigin::Unknown {
+                        format!("<library> 0x{addr:x}")
+                    } else {
+                        frame.function.clone()
+                    },
+                    file: file_str,
+                    line: frame.location.as_ref().and_then(|loc| loc.line),
+                    origin,
+                    is_user_code: false,
+                });
             } else {
-                // Library code - can't symbolize without library debug info
-                // Just show a placeholder with the address
-                let origin = crate::classification::FrameOrigin::Unknown;
+                // Unmapped or unreadable library - just show the address
+                let origin = FrameOrigin::Unknown;
                 resolved_frames.push(StackFrame {
                     function: format!("<library> 0x{addr:x}"),
                     file: None,
@@ -370,37 +631,88 @@ impl<'a> EventProcessor<'a> {
         Some(self.stack_cache.insert(stack_id, resolved_frames))
     }
 
+    /// Resolve a kernel stack captured by eBPF against `/proc/kallsyms`.
+    ///
+    /// Kernel stacks get their own cache: with `BPF_F_REUSE_STACKID` a
+    /// `stack_id` can be recycled between a kernel and a user stack.
+    fn resolve_kernel_stack<T: Borrow<MapData>>(
+        &mut self,
+        stack_id: i64,
+        stack_traces: &StackTraceMap<T>,
+    ) -> Option<Arc<Vec<StackFrame>>> {
+        if let Some(cached) = self.kernel_stack_cache.get(stack_id) {
+            return Some(cached);
+        }
+
+        let stack_id_wrapped = StackId(stack_id);
+        if !stack_id_wrapped.is_valid() {
+            return None;
+        }
+        let stack_trace = stack_traces.get(&stack_id_wrapped.as_map_key(), 0).ok()?;
+
+        // Loaded on first use: most sessions never see a kernel stack
+        let symbols =
+            self.kernel_symbols.get_or_insert_with(|| KernelSymbols::load().unwrap_or_default());
+        let frames: Vec<StackFrame> = stack_trace
+            .frames()
+            .iter()
+            .map(|frame| frame.ip)
+            .take_while(|&addr| addr != 0)
+            .map(|addr| StackFrame {
+                function: symbols
+                    .resolve(addr)
+                    .map_or_else(|| format!("0x{addr:x}"), str::to_string),
+                file: None,
+                line: None,
+                origin: FrameOrigin::Kernel,
+                is_user_code: false,
+            })
+            .collect();
+
+        if frames.is_empty() {
+            return None;
+        }
+        Some(self.kernel_stack_cache.insert(stack_id, frames))
+    }
+
     /// Convert `TaskEvent` to `TraceEvent` with full stack resolution
-    #[allow(clippy::cast_precision_loss)]
     fn convert_to_trace_event<T: Borrow<MapData>>(
         &mut self,
         event: &TaskEvent,
         stack_traces: &StackTraceMap<T>,
     ) -> TraceEvent {
-        // Resolve full call stack
         let call_stack = self.resolve_full_stack(event.stack_id, stack_traces);
+        build_trace_event(event, call_stack)
+    }
+}
 
-        // Use the first user-code frame as the event name (answers "which of MY
-        // functions is blocking?"). Falls back to top frame if no user code found.
-        let (name, file, line) = call_stack
-            .as_ref()
-            .and_then(|stack| stack.iter().find(|f| f.is_user_code).or_else(|| stack.first()))
-            .map_or_else(
-                || ("execution".to_string(), None, None),
-                |frame| (frame.function.clone(), frame.file.clone(), frame.line),
-            );
-
-        TraceEvent {
-            name,
-            worker_id: event.worker_id,
-            tid: event.tid,
-            timestamp: event.timestamp_ns as f64 / 1_000_000_000.0, // ns to seconds
-            cpu: event.cpu_id,
-            detection_method: Some(u32::from(event.detection_method)),
-            file,
-            line,
-            call_stack,
-        }
+/// Build a `TraceEvent` from a raw event and its already-resolved call stack.
+#[allow(clippy::cast_precision_loss)]
+pub(crate) fn build_trace_event(
+    event: &TaskEvent,
+    call_stack: Option<Arc<Vec<StackFrame>>>,
+) -> TraceEvent {
+    // Use the first user-code frame as the event name (answers "which of MY
+    // functions is blocking?"). Falls back to top frame if no user code found.
+    let (name, file, line) =
+        call_stack.as_ref().and_then(|stack| attribution_frame(stack)).map_or_else(
+            || ("execution".to_string(), None, None),
+            |frame| (frame.function.clone(), frame.file.clone(), frame.line),
+        );
+
+    TraceEvent {
+        name,
+        worker_id: event.worker_id,
+        tid: event.tid,
+        timestamp: event.timestamp_ns as f64 / 1_000_000_000.0, // ns to seconds
+        cpu: event.cpu_id,
+        detection_method: Some(u32::from(event.detection_method)),
+        file,
+        line,
+        call_stack,
+        duration_ns: (event.event_type == EVENT_SCHEDULER_DETECTED).then_some(event.duration_ns),
+        task_id: (event.task_id != 0).then_some(event.task_id),
+        blocker: None,
     }
 }
 
@@ -439,13 +751,7 @@ mod tests {
     /// are irrelevant to `is_blocking_pool_stack` — it only inspects
     /// `function`.
     fn frame(function: &str) -> StackFrame {
-        StackFrame {
-            function: function.to_string(),
-            file: None,
-            line: None,
-            origin: FrameOrigin::Unknown,
-            is_user_code: false,
-        }
+        crate::testing::frame(function, FrameOrigin::Unknown)
     }
 
     // ── is_blocking_pool_stack unit tests ─────────────────────────────
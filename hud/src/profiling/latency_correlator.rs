@@ -0,0 +1,197 @@
+//! Scheduler latency correlation ("blocker vs victim")
+//!
+//! A scheduler latency event tells us a worker waited too long for the CPU,
+//! but not *why*: the stack at `sched_switch` belongs to the outgoing thread,
+//! so eBPF reports `stack_id = -1` for the victim.
+//!
+//! The 99 Hz perf sampler, however, already sees what was running. This module
+//! keeps a short per-CPU history of samples and, when a latency event arrives,
+//! looks up the samples taken on the same CPU during the wait window. The most
+//! frequent (thread, stack) pair among them is the culprit.
+//!
+//! ```text
+//! CPU 3  ──[blocker 7712: bcrypt::hash]──[blocker]──[blocker]──▶ worker W0 resumes
+//!          ▲ sample        ▲ sample        ▲ sample            (waited 37ms)
+//!          └───────────── wait window ─────────────┘
+//! ```
+//!
+//! ## Limitations
+//!
+//! - Only threads of the target process are sampled, so a blocker in another
+//!   process (or the kernel) leaves the event unattributed.
+//! - A worker that migrated CPUs while waiting is matched against the CPU it
+//!   resumed on.
+//! - Waits shorter than the 10ms sampling period often contain no sample.
+
+use std::collections::{HashMap, VecDeque};
+
+/// How far back samples are kept per CPU.
+///
+/// Latency events older than this cannot be attributed. Scheduler waits beyond
+/// a few hundred milliseconds are rare, so 5s leaves ample margin.
+const SAMPLE_RETENTION_NS: u64 = 5_000_000_000;
+
+/// Hard cap on samples kept per CPU (99 Hz × 5s ≈ 500, doubled for headroom).
+const MAX_SAMPLES_PER_CPU: usize = 1024;
+
+/// A single on-CPU perf sample, reduced to what correlation needs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct CpuSample {
+    timestamp_ns: u64,
+    tid: u32,
+    stack_id: i64,
+}
+
+/// The thread (and stack) that held a CPU while a worker waited on it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Culprit {
+    /// Thread that was running on the CPU
+    pub tid: u32,
+    /// Its most frequent stack in the window (negative if capture failed)
+    pub stack_id: i64,
+    /// Number of samples backing this attribution
+    pub samples: u32,
+}
+
+/// Per-CPU sample history used to attribute scheduler latency events.
+#[derive(Debug, Default)]
+pub struct LatencyCorrelator {
+    /// Recent samples per CPU, oldest first
+    samples: HashMap<u32, VecDeque<CpuSample>>,
+    /// Latency events that found a culprit
+    pub attributed: usize,
+    /// Latency events with no matching samples
+    pub unattributed: usize,
+}
+
+impl LatencyCorrelator {
+    /// Create an empty correlator.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remember a perf sample taken on `cpu`.
+    ///
+    /// Record every sample, including blocking-pool and non-worker threads:
+    /// those are exactly the blockers we are looking for.
+    pub fn record_sample(&mut self, cpu: u32, timestamp_ns: u64, tid: u32, stack_id: i64) {
+        let history = self.samples.entry(cpu).or_default();
+        history.push_back(CpuSample { timestamp_ns, tid, stack_id });
+
+        let cutoff = timestamp_ns.saturating_sub(SAMPLE_RETENTION_NS);
+        while history.front().is_some_and(|s| s.timestamp_ns < cutoff)
+            || history.len() > MAX_SAMPLES_PER_CPU
+        {
+            history.pop_front();
+        }
+    }
+
+    /// Find the culprit for a worker that waited `[end_ns - wait_ns, end_ns]` on `cpu`.
+    ///
+    /// Samples of the victim itself are ignored. Returns `None` when no other
+    /// thread of the target process was sampled on that CPU during the wait.
+    pub fn correlate(
+        &mut self,
+        cpu: u32,
+        end_ns: u64,
+        wait_ns: u64,
+        victim_tid: u32,
+    ) -> Option<Culprit> {
+        let culprit = self.find_culprit(cpu, end_ns.saturating_sub(wait_ns), end_ns, victim_tid);
+        if culprit.is_some() {
+            self.attributed += 1;
+        } else {
+            self.unattributed += 1;
+        }
+        culprit
+    }
+
+    fn find_culprit(
+        &self,
+        cpu: u32,
+        start_ns: u64,
+        end_ns: u64,
+        victim_tid: u32,
+    ) -> Option<Culprit> {
+        let history = self.samples.get(&cpu)?;
+
+        // Count samples per (tid, stack_id). Iterating oldest-first with `>=`
+        // below means ties go to the most recent pair, which is closest to the
+        // moment the worker finally got the CPU back.
+        let mut counts: HashMap<(u32, i64), u32> = HashMap::new();
+        let mut best: Option<Culprit> = None;
+        for sample in history
+            .iter()
+            .filter(|s| s.timestamp_ns >= start_ns && s.timestamp_ns <= end_ns)
+            .filter(|s| s.tid != victim_tid)
+        {
+            let count = counts.entry((sample.tid, sample.stack_id)).or_insert(0);
+            *count += 1;
+            if best.is_none_or(|b| *count >= b.samples) {
+                best =
+                    Some(Culprit { tid: sample.tid, stack_id: sample.stack_id, samples: *count });
+            }
+        }
+        best
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MS: u64 = 1_000_000;
+
+    #[test]
+    fn attributes_wait_to_dominant_sample_on_same_cpu() {
+        let mut correlator = LatencyCorrelator::new();
+        // Blocker 200 hogs CPU 3 with stack 7; one stray sample from thread 300
+        correlator.record_sample(3, 100 * MS, 200, 7);
+        correlator.record_sample(3, 110 * MS, 300, 9);
+        correlator.record_sample(3, 120 * MS, 200, 7);
+        correlator.record_sample(3, 130 * MS, 200, 7);
+
+        // Worker 100 resumed at 135ms after waiting 40ms
+        let culprit = correlator.correlate(3, 135 * MS, 40 * MS, 100).unwrap();
+        assert_eq!(culprit, Culprit { tid: 200, stack_id: 7, samples: 3 });
+        assert_eq!(correlator.attributed, 1);
+    }
+
+    #[test]
+    fn ignores_samples_outside_window_and_on_other_cpus() {
+        let mut correlator = LatencyCorrelator::new();
+        correlator.record_sample(3, 10 * MS, 200, 7); // before the wait
+        correlator.record_sample(1, 120 * MS, 200, 7); // different CPU
+
+        assert_eq!(correlator.correlate(3, 135 * MS, 40 * MS, 100), None);
+        assert_eq!(correlator.unattributed, 1);
+    }
+
+    #[test]
+    fn ignores_victim_samples() {
+        let mut correlator = LatencyCorrelator::new();
+        correlator.record_sample(3, 120 * MS, 100, 5);
+
+        assert_eq!(correlator.correlate(3, 135 * MS, 40 * MS, 100), None);
+    }
+
+    #[test]
+    fn ties_go_to_most_recent_pair() {
+        let mut correlator = LatencyCorrelator::new();
+        correlator.record_sample(0, 100 * MS, 200, 7);
+        correlator.record_sample(0, 110 * MS, 300, 9);
+
+        let culprit = correlator.correlate(0, 115 * MS, 20 * MS, 100).unwrap();
+        assert_eq!(culprit.tid, 300);
+    }
+
+    #[test]
+    fn old_samples_are_pruned() {
+        let mut correlator = LatencyCorrelator::new();
+        correlator.record_sample(0, MS, 200, 7);
+        correlator.record_sample(0, SAMPLE_RETENTION_NS + 10 * MS, 300, 9);
+
+        assert_eq!(correlator.samples[&0].len(), 1);
+    }
+}
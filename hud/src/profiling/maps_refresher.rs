@@ -0,0 +1,115 @@
+//! Background refresh of the target's memory maps
+//!
+//! Mappings are read once at startup, but the address space keeps changing:
+//! plugins arrive via `dlopen`, and a process that re-execs itself comes back
+//! with a fresh ASLR layout. This thread re-reads `/proc/<pid>/maps` every
+//! [`REFRESH_INTERVAL`] and sends the executable mappings whenever they
+//! change. The event loop applies them with
+//! [`EventProcessor::update_mappings`](super::EventProcessor::update_mappings).
+//!
+//! Polling means frames from a library in its first second can still show
+//! as `<library>` addresses; the next refresh fixes all later stacks.
+//!
+//! The event processor is the only consumer. Exporters receive stacks it has
+//! already resolved, and `worker_sampling` runs once at startup, before this
+//! thread is spawned, against the layout read then.
+
+use crossbeam_channel::{bounded, Receiver};
+use log::debug;
+use std::thread;
+use std::time::Duration;
+
+use crate::symbolization::{parse_executable_mappings, MemoryMapping};
+
+/// How often `/proc/<pid>/maps` is re-read
+pub const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Outcome of one re-read of the maps
+#[derive(Debug, PartialEq, Eq)]
+enum MapsUpdate {
+    /// Nothing to send
+    Unchanged,
+    /// A new layout for the event loop
+    Changed(Vec<MemoryMapping>),
+    /// The maps can no longer be read: the process is gone
+    Stop,
+}
+
+/// Decide what a re-read of the maps means, given the layout in use
+///
+/// An empty read is skipped: a process caught mid-exec or while exiting has
+/// no executable mappings, and sending that would drop the executable's
+/// layout just before it comes back.
+fn maps_update(last: &[MemoryMapping], read: Option<Vec<MemoryMapping>>) -> MapsUpdate {
+    match read {
+        None => MapsUpdate::Stop,
+        Some(mappings) if mappings.is_empty() || mappings == last => MapsUpdate::Unchanged,
+        Some(mappings) => MapsUpdate::Changed(mappings),
+    }
+}
+
+/// Spawn the refresher thread for `pid`.
+///
+/// `initial` is the mapping list already in use, so an unchanged address
+/// space sends nothing. The thread exits when the process is gone or the
+/// receiver is dropped.
+#[must_use]
+pub fn spawn_maps_refresher(pid: i32, initial: Vec<MemoryMapping>) -> Receiver<Vec<MemoryMapping>> {
+    // Only the latest layout matters; a full channel means the loop is busy
+    let (tx, rx) = bounded(4);
+
+    thread::spawn(move || {
+        let mut last = initial;
+        loop {
+            thread::sleep(REFRESH_INTERVAL);
+
+            match maps_update(&last, parse_executable_mappings(pid).ok()) {
+                MapsUpdate::Unchanged => {}
+                MapsUpdate::Changed(mappings) => {
+                    if tx.send(mappings.clone()).is_err() {
+                        return;
+                    }
+                    last = mappings;
+                }
+                MapsUpdate::Stop => {
+                    debug!("Stopping maps refresher: /proc/{pid}/maps unreadable");
+                    return;
+                }
+            }
+        }
+    });
+
+    rx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mapping(start: u64, path: &str) -> MemoryMapping {
+        MemoryMapping { start, end: start + 0x1000, offset: 0, path: path.to_string() }
+    }
+
+    #[test]
+    fn test_only_new_layouts_are_sent() {
+        let initial = vec![mapping(0x1000, "/app/server"), mapping(0x9000, "/lib/libc.so.6")];
+
+        assert_eq!(maps_update(&initial, Some(initial.clone())), MapsUpdate::Unchanged);
+
+        // dlopen adds a library
+        let mut loaded = initial.clone();
+        loaded.push(mapping(0x5000, "/opt/plugin.so"));
+        assert_eq!(maps_update(&initial, Some(loaded.clone())), MapsUpdate::Changed(loaded));
+
+        // Re-exec: same files, fresh ASLR layout
+        let relocated = vec![mapping(0x2000, "/app/server"), mapping(0xa000, "/lib/libc.so.6")];
+        assert_eq!(maps_update(&initial, Some(relocated.clone())), MapsUpdate::Changed(relocated));
+    }
+
+    #[test]
+    fn test_empty_read_is_skipped_and_unreadable_stops() {
+        let initial = vec![mapping(0x1000, "/app/server")];
+        assert_eq!(maps_update(&initial, Some(Vec::new())), MapsUpdate::Unchanged);
+        assert_eq!(maps_update(&initial, None), MapsUpdate::Stop);
+    }
+}
@@ -8,12 +8,16 @@
 //! - Debug diagnostics
 //! - Event display formatting
 //! - Event processing logic
+//! - Scheduler latency attribution (blocker vs victim)
+//! - Memory map refresh (`dlopen`, re-exec)
 
 pub mod cpu_utils;
 pub mod diagnostics;
 pub mod ebpf_setup;
 pub mod event_display;
 pub mod event_processor;
+pub mod latency_correlator;
+pub mod maps_refresher;
 pub mod stack_resolver;
 pub mod worker_discovery;
 pub mod worker_sampling;
@@ -23,16 +27,20 @@ pub use cpu_utils::online_cpus;
 pub use diagnostics::print_perf_event_diagnostics;
 pub use ebpf_setup::{
     attach_sched_switch, attach_task_id_uprobe, init_ebpf_logger, load_ebpf_program,
-    register_tokio_workers, register_workers_in_ebpf, start_perf_sampling,
+    register_tokio_workers, register_workers_in_ebpf, start_perf_sampling, EbpfWorkerRegistry,
 };
 pub use event_display::{
     display_execution_event, display_progress, display_scheduler_detected, display_statistics,
     DetectionStats,
 };
 pub use event_processor::EventProcessor;
+pub use latency_correlator::{Culprit, LatencyCorrelator};
+pub use maps_refresher::spawn_maps_refresher;
 pub use stack_resolver::StackResolver;
-pub use worker_discovery::{identify_tokio_workers, WorkerInfo};
+pub use worker_discovery::{
+    identify_tokio_workers, WorkerChanges, WorkerInfo, WorkerRegistry, WorkerTracker,
+};
 pub use worker_sampling::discover_workers_from_stacks;
 
-// Re-export MemoryRange from symbolization for convenience
-pub use crate::symbolization::MemoryRange;
+// Re-export ModuleMap from symbolization for convenience
+pub use crate::symbolization::ModuleMap;
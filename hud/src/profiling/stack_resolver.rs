@@ -9,7 +9,7 @@ use log::info;
 use std::borrow::Borrow;
 
 use crate::domain::StackId;
-use crate::symbolization::{MemoryRange, Symbolizer};
+use crate::symbolization::{ModuleMap, Symbolizer};
 
 /// Stack trace resolver - handles resolving and displaying stack traces
 ///
@@ -20,13 +20,18 @@ use crate::symbolization::{MemoryRange, Symbolizer};
 /// - Formatting and printing stack traces
 pub struct StackResolver<'a> {
     symbolizer: &'a Symbolizer,
-    memory_range: Option<MemoryRange>,
+    module_map: Option<ModuleMap>,
 }
 
 impl<'a> StackResolver<'a> {
     /// Create a new stack resolver
-    pub fn new(symbolizer: &'a Symbolizer, memory_range: Option<MemoryRange>) -> Self {
-        Self { symbolizer, memory_range }
+    pub fn new(symbolizer: &'a Symbolizer, module_map: Option<ModuleMap>) -> Self {
+        Self { symbolizer, module_map }
+    }
+
+    /// Replace the executable layout (after the address space changed)
+    pub fn set_module_map(&mut self, module_map: Option<ModuleMap>) {
+        self.module_map = module_map;
     }
 
     /// Resolve and print a stack trace from an eBPF stack trace map
@@ -96,10 +101,9 @@ impl<'a> StackResolver<'a> {
     ///
     /// Returns (`adjusted_address`, `is_in_executable`)
     fn adjust_address(&self, addr: u64) -> (u64, bool) {
-        if let Some(range) = self.memory_range {
-            if range.contains(addr) {
-                // Address is in main executable, adjust to file offset
-                let adjusted = addr - range.start;
+        if let Some(module) = &self.module_map {
+            if let Some(adjusted) = module.to_elf_vaddr(addr) {
+                // Address is in main executable, adjust to ELF vaddr
                 info!("Address 0x{addr:016x} (in executable) -> 0x{adjusted:08x}");
                 (adjusted, true)
             } else {
@@ -137,23 +141,30 @@ impl<'a> StackResolver<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::symbolization::MemoryMapping;
+
+    /// Single segment mapped from file offset 0, no program headers
+    fn module(start: u64, end: u64) -> ModuleMap {
+        let mapping = MemoryMapping { start, end, offset: 0, path: "/bin/ls".to_string() };
+        ModuleMap::from_mappings("/bin/ls", &[mapping]).unwrap()
+    }
 
     #[test]
-    fn test_memory_range_contains() {
-        let range = MemoryRange { start: 0x1000, end: 0x2000 };
-
-        assert!(range.contains(0x1000));
-        assert!(range.contains(0x1500));
-        assert!(range.contains(0x1FFF));
-        assert!(!range.contains(0x0FFF));
-        assert!(!range.contains(0x2000));
-        assert!(!range.contains(0x2001));
+    fn test_module_map_contains() {
+        let module = module(0x1000, 0x2000);
+
+        assert!(module.contains(0x1000));
+        assert!(module.contains(0x1500));
+        assert!(module.contains(0x1FFF));
+        assert!(!module.contains(0x0FFF));
+        assert!(!module.contains(0x2000));
+        assert!(!module.contains(0x2001));
     }
 
     #[test]
     fn test_adjust_address_in_executable() {
         let symbolizer = Symbolizer::new("/bin/ls").unwrap();
-        let range = MemoryRange { start: 0x7f00_0000_0000, end: 0x7f00_0010_0000 };
+        let range = module(0x7f00_0000_0000, 0x7f00_0010_0000);
         let resolver = StackResolver::new(&symbolizer, Some(range));
 
         let (adjusted, in_exec) = resolver.adjust_address(0x7f00_0005_0000);
@@ -164,7 +175,7 @@ mod tests {
     #[test]
     fn test_adjust_address_shared_library() {
         let symbolizer = Symbolizer::new("/bin/ls").unwrap();
-        let range = MemoryRange { start: 0x7f00_0000_0000, end: 0x7f00_0010_0000 };
+        let range = module(0x7f00_0000_0000, 0x7f00_0010_0000);
         let resolver = StackResolver::new(&symbolizer, Some(range));
 
         let (adjusted, in_exec) = resolver.adjust_address(0x7f00_0100_0000);
@@ -22,9 +22,16 @@
 //!
 //! Auto-discovery finds the largest group of threads sharing a common prefix,
 //! which in a Tokio application is almost always the worker pool.
+//!
+//! ## Re-discovery
+//!
+//! Discovery runs once at startup, but runtimes come and go (a second
+//! `Runtime::new()`, per-test runtimes). [`WorkerTracker`] remembers which
+//! thread names identified workers and diffs each `/proc` scan against the
+//! registered set, so new workers are added and exited ones removed.
 
 use anyhow::{Context, Result};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 
 use crate::domain::{Pid, Tid};
@@ -258,10 +265,275 @@ pub fn identify_tokio_workers(pid: Pid, name_prefix: Option<&str>) -> Result<Vec
     Ok(vec![])
 }
 
+/// Workers added and removed by a [`WorkerTracker::refresh`].
+#[derive(Debug, Clone, Default)]
+pub struct WorkerChanges {
+    /// Threads that started matching since the last scan
+    pub added: Vec<WorkerInfo>,
+    /// Registered threads that have exited
+    pub removed: Vec<WorkerInfo>,
+    /// Registered workers after the change
+    pub active: usize,
+}
+
+impl WorkerChanges {
+    /// True if no worker was added or removed
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Where [`WorkerTracker::refresh`] registers workers: the eBPF
+/// `TOKIO_WORKER_THREADS` map, or a stand-in in tests
+pub trait WorkerRegistry {
+    /// Start attributing `worker`'s events to it
+    ///
+    /// # Errors
+    /// Returns an error if the worker could not be added (e.g. the map is full)
+    fn register(&mut self, worker: &WorkerInfo) -> Result<()>;
+
+    /// Stop attributing events to `worker`; a worker already gone is not
+    /// an error
+    ///
+    /// # Errors
+    /// Returns an error if the worker could not be removed
+    fn unregister(&mut self, worker: &WorkerInfo) -> Result<()>;
+}
+
+/// Tracks the registered worker set across `/proc` scans.
+///
+/// Worker IDs are never reused: a new runtime's workers get fresh IDs, so
+/// their samples are not mixed with those of exited workers in the TUI.
+#[derive(Debug, Clone)]
+pub struct WorkerTracker {
+    /// Thread name prefixes that identify workers
+    prefixes: Vec<String>,
+    /// Registered workers by TID (ordered for stable output)
+    workers: BTreeMap<u32, WorkerInfo>,
+    /// Next worker ID to assign
+    next_id: u32,
+    /// Workers found by the last scan that could not be registered
+    unregistered: usize,
+}
+
+impl WorkerTracker {
+    /// Track workers matching `prefixes`, starting from the already
+    /// registered `workers`.
+    #[must_use]
+    pub fn new(prefixes: Vec<String>, workers: &[WorkerInfo]) -> Self {
+        let next_id = workers.iter().map(|w| w.worker_id + 1).max().unwrap_or(0);
+        let workers = workers.iter().map(|w| (w.tid.0, w.clone())).collect();
+        Self { prefixes, workers, next_id, unregistered: 0 }
+    }
+
+    /// Track workers found without a prefix (stack-based discovery) by TID
+    /// only.
+    ///
+    /// Their thread names can't become prefixes: unnamed threads inherit the
+    /// process name, so helper and blocking threads would match too. Exited
+    /// workers are still removed; new threads are not added.
+    #[must_use]
+    pub fn from_workers(workers: &[WorkerInfo]) -> Self {
+        Self::new(Vec::new(), workers)
+    }
+
+    /// Registered workers
+    pub fn workers(&self) -> impl Iterator<Item = &WorkerInfo> {
+        self.workers.values()
+    }
+
+    /// Number of registered workers
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// True if no workers are registered
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.workers.is_empty()
+    }
+
+    /// Workers the last [`refresh`](Self::refresh) found but could not
+    /// register; the next scan retries them
+    #[must_use]
+    pub fn unregistered(&self) -> usize {
+        self.unregistered
+    }
+
+    /// Diff a fresh thread list (from [`list_process_threads`]) against the
+    /// registered workers and sync `registry` with it.
+    ///
+    /// The tracker only follows the registry's successes: a worker that
+    /// could not be registered is left out (and counted in
+    /// [`unregistered`](Self::unregistered)), and one that could not be
+    /// removed stays, so the next scan retries either.
+    pub fn refresh(
+        &mut self,
+        threads: &[(u32, String)],
+        registry: &mut impl WorkerRegistry,
+    ) -> WorkerChanges {
+        let exited: Vec<WorkerInfo> = self
+            .workers
+            .values()
+            .filter(|w| !threads.iter().any(|(tid, _)| *tid == w.tid.0))
+            .cloned()
+            .collect();
+        let mut removed = Vec::new();
+        for worker in exited {
+            match registry.unregister(&worker) {
+                Ok(()) => {
+                    self.workers.remove(&worker.tid.0);
+                    removed.push(worker);
+                }
+                Err(e) => log::debug!("Could not unregister worker TID {}: {e:#}", worker.tid.0),
+            }
+        }
+
+        let mut added = Vec::new();
+        self.unregistered = 0;
+        for (tid, comm) in threads {
+            let is_worker = self.prefixes.iter().any(|p| comm.starts_with(p.as_str()));
+            if !is_worker || self.workers.contains_key(tid) {
+                continue;
+            }
+            let worker = WorkerInfo { tid: Tid(*tid), worker_id: self.next_id, comm: comm.clone() };
+            match registry.register(&worker) {
+                Ok(()) => {
+                    self.next_id += 1;
+                    self.workers.insert(*tid, worker.clone());
+                    added.push(worker);
+                }
+                Err(e) => {
+                    log::debug!("Could not register worker TID {tid}: {e:#}");
+                    self.unregistered += 1;
+                }
+            }
+        }
+
+        WorkerChanges { added, removed, active: self.workers.len() }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    // ── WorkerTracker unit tests ────────────────────────────────────────
+
+    /// Stand-in for the eBPF map: holds `capacity` workers, and fails to
+    /// remove the TIDs in `stuck`
+    struct FakeRegistry {
+        capacity: usize,
+        tids: Vec<u32>,
+        stuck: Vec<u32>,
+    }
+
+    impl FakeRegistry {
+        fn new(capacity: usize, workers: &[WorkerInfo]) -> Self {
+            Self { capacity, tids: workers.iter().map(|w| w.tid.0).collect(), stuck: vec![] }
+        }
+    }
+
+    impl WorkerRegistry for FakeRegistry {
+        fn register(&mut self, worker: &WorkerInfo) -> Result<()> {
+            if self.tids.len() >= self.capacity {
+                anyhow::bail!("map full");
+            }
+            self.tids.push(worker.tid.0);
+            Ok(())
+        }
+
+        fn unregister(&mut self, worker: &WorkerInfo) -> Result<()> {
+            if self.stuck.contains(&worker.tid.0) {
+                anyhow::bail!("delete failed");
+            }
+            self.tids.retain(|&tid| tid != worker.tid.0);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_tracker_adds_new_runtime_and_removes_exited() {
+        let threads =
+            vec![(10, "tokio-rt-worker".to_string()), (11, "tokio-rt-worker".to_string())];
+        let initial = collect_workers(&threads, "tokio-rt-worker");
+        let mut tracker = WorkerTracker::new(vec!["tokio-rt-worker".to_string()], &initial);
+        let mut registry = FakeRegistry::new(256, &initial);
+
+        // Unchanged process: nothing to do
+        assert!(tracker.refresh(&threads, &mut registry).is_empty());
+
+        // Worker 10 exits, a second runtime starts two workers
+        let threads = vec![
+            (1, "main".to_string()),
+            (11, "tokio-rt-worker".to_string()),
+            (20, "tokio-rt-worker".to_string()),
+            (21, "tokio-rt-worker".to_string()),
+        ];
+        let changes = tracker.refresh(&threads, &mut registry);
+        assert_eq!(changes.removed.iter().map(|w| w.tid.0).collect::<Vec<_>>(), [10]);
+        assert_eq!(changes.added.iter().map(|w| w.tid.0).collect::<Vec<_>>(), [20, 21]);
+        // IDs continue after the initial 0 and 1, never reusing 0
+        assert_eq!(changes.added.iter().map(|w| w.worker_id).collect::<Vec<_>>(), [2, 3]);
+        assert_eq!(changes.active, 3);
+        assert_eq!(tracker.len(), 3);
+        assert_eq!(registry.tids, [11, 20, 21]);
+    }
+
+    #[test]
+    fn test_tracker_follows_registry_failures() {
+        let threads = vec![(10, "tokio-rt-worker".to_string())];
+        let initial = collect_workers(&threads, "tokio-rt-worker");
+        let mut tracker = WorkerTracker::new(vec!["tokio-rt-worker".to_string()], &initial);
+        // Room for two workers; TID 10 cannot be removed for now
+        let mut registry = FakeRegistry::new(2, &initial);
+        registry.stuck.push(10);
+
+        // Worker 10 exits and three start: one fits, two do not
+        let threads: Vec<(u32, String)> =
+            (20..23).map(|tid| (tid, "tokio-rt-worker".to_string())).collect();
+        let changes = tracker.refresh(&threads, &mut registry);
+        assert!(changes.removed.is_empty());
+        assert_eq!(
+            changes.added.iter().map(|w| (w.tid.0, w.worker_id)).collect::<Vec<_>>(),
+            [(20, 1)]
+        );
+        assert_eq!(tracker.unregistered(), 2);
+        assert_eq!(tracker.workers().map(|w| w.tid.0).collect::<Vec<_>>(), [10, 20]);
+
+        // The map frees up: the exit and the leftover workers go through,
+        // with IDs continuing from the last one handed out
+        registry.stuck.clear();
+        registry.capacity = 256;
+        let changes = tracker.refresh(&threads, &mut registry);
+        assert_eq!(changes.removed.iter().map(|w| w.tid.0).collect::<Vec<_>>(), [10]);
+        assert_eq!(
+            changes.added.iter().map(|w| (w.tid.0, w.worker_id)).collect::<Vec<_>>(),
+            [(21, 2), (22, 3)]
+        );
+        assert_eq!(tracker.unregistered(), 0);
+        assert_eq!(registry.tids, [20, 21, 22]);
+    }
+
+    #[test]
+    fn test_tracker_from_stack_discovered_workers() {
+        // Unnamed threads share the process name with the discovered worker
+        let initial = vec![WorkerInfo { tid: Tid(5), worker_id: 0, comm: "server".to_string() }];
+        let mut tracker = WorkerTracker::from_workers(&initial);
+        let mut registry = FakeRegistry::new(256, &initial);
+
+        let threads = [(1, "server".to_string()), (5, "server".to_string())];
+        assert!(tracker.refresh(&threads, &mut registry).is_empty());
+        assert_eq!(registry.tids, [5]);
+
+        // The worker exits: still removed by TID
+        let changes = tracker.refresh(&[(1, "server".to_string())], &mut registry);
+        assert_eq!(changes.removed.iter().map(|w| w.tid.0).collect::<Vec<_>>(), [5]);
+        assert!(tracker.is_empty());
+    }
+
     // ── identify_tokio_workers integration tests ────────────────────────
 
     #[test]
@@ -25,7 +25,7 @@ use hud_common::TaskEvent;
 use log::info;
 
 use crate::domain::{Pid, StackId};
-use crate::symbolization::{MemoryRange, Symbolizer};
+use crate::symbolization::{ModuleMap, Symbolizer};
 
 use super::worker_discovery::{list_process_threads, WorkerInfo};
 
@@ -52,7 +52,7 @@ pub fn classify_thread_stack<T: Borrow<MapData>>(
     stack_id: StackId,
     stack_traces: &StackTraceMap<T>,
     symbolizer: &Symbolizer,
-    memory_range: Option<MemoryRange>,
+    module_map: Option<&ModuleMap>,
 ) -> ThreadClass {
     if !stack_id.is_valid() {
         return ThreadClass::Unknown;
@@ -76,12 +76,8 @@ pub fn classify_thread_stack<T: Borrow<MapData>>(
         }
 
         // Determine file offset for symbolization
-        let (file_offset, in_executable) = if let Some(range) = memory_range {
-            if range.contains(addr) {
-                (addr - range.start, true)
-            } else {
-                (addr, false)
-            }
+        let (file_offset, in_executable) = if let Some(module) = module_map {
+            module.to_elf_vaddr(addr).map_or((addr, false), |vaddr| (vaddr, true))
         } else {
             (addr, true)
         };
@@ -119,7 +115,7 @@ pub fn classify_thread_stack<T: Borrow<MapData>>(
 /// * `ring_buf` - The eBPF ring buffer (EVENTS map)
 /// * `stack_traces` - The eBPF stack trace map
 /// * `symbolizer` - DWARF symbolizer for the target binary
-/// * `memory_range` - Memory range of the target binary (for PIE adjustment)
+/// * `module_map` - Layout of the target binary (for PIE adjustment)
 /// * `pid` - Target process ID
 /// * `duration` - How long to sample before returning results
 ///
@@ -130,7 +126,7 @@ pub fn discover_workers_from_stacks<T: Borrow<MapData>>(
     ring_buf: &mut RingBuf<MapData>,
     stack_traces: &StackTraceMap<T>,
     symbolizer: &Symbolizer,
-    memory_range: Option<MemoryRange>,
+    module_map: Option<&ModuleMap>,
     pid: Pid,
     duration: Duration,
 ) -> anyhow::Result<Vec<WorkerInfo>> {
@@ -154,12 +150,7 @@ pub fn discover_workers_from_stacks<T: Borrow<MapData>>(
             let event = unsafe { std::ptr::read_unaligned(bytes.as_ptr().cast::<TaskEvent>()) };
 
             let class = *stack_cache.entry(event.stack_id).or_insert_with(|| {
-                classify_thread_stack(
-                    StackId(event.stack_id),
-                    stack_traces,
-                    symbolizer,
-                    memory_range,
-                )
+                classify_thread_stack(StackId(event.stack_id), stack_traces, symbolizer, module_map)
             });
 
             // Upgrade classification: Worker > BlockingPool > Unknown
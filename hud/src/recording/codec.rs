@@ -0,0 +1,252 @@
+//! Little-endian encoding shared by the session writer and reader
+
+use hud_common::TaskEvent;
+use std::io;
+
+use super::SessionConfig;
+use crate::classification::FrameOrigin;
+use crate::domain::Tid;
+use crate::profiling::WorkerInfo;
+use crate::trace_data::StackFrame;
+
+/// Appends fields to a record payload
+#[derive(Default)]
+pub(super) struct Encoder {
+    pub(super) buf: Vec<u8>,
+}
+
+impl Encoder {
+    pub(super) fn u8(&mut self, v: u8) {
+        self.buf.push(v);
+    }
+
+    pub(super) fn u32(&mut self, v: u32) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    pub(super) fn u64(&mut self, v: u64) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    pub(super) fn i64(&mut self, v: i64) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    /// Length-prefixed collection size
+    #[allow(clippy::cast_possible_truncation)]
+    pub(super) fn len(&mut self, len: usize) {
+        self.u32(len as u32);
+    }
+
+    pub(super) fn str(&mut self, s: &str) {
+        self.len(s.len());
+        self.buf.extend_from_slice(s.as_bytes());
+    }
+
+    pub(super) fn opt_str(&mut self, s: Option<&str>) {
+        self.u8(u8::from(s.is_some()));
+        if let Some(s) = s {
+            self.str(s);
+        }
+    }
+
+    pub(super) fn opt_u32(&mut self, v: Option<u32>) {
+        self.u8(u8::from(v.is_some()));
+        if let Some(v) = v {
+            self.u32(v);
+        }
+    }
+}
+
+/// Reads fields back from a record payload
+pub(super) struct Decoder<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> Decoder<'a> {
+    pub(super) fn new(buf: &'a [u8]) -> Self {
+        Self { buf }
+    }
+
+    fn take<const N: usize>(&mut self) -> io::Result<[u8; N]> {
+        let (head, rest) = self.buf.split_first_chunk::<N>().ok_or_else(truncated)?;
+        self.buf = rest;
+        Ok(*head)
+    }
+
+    pub(super) fn u8(&mut self) -> io::Result<u8> {
+        Ok(self.take::<1>()?[0])
+    }
+
+    pub(super) fn u32(&mut self) -> io::Result<u32> {
+        self.take().map(u32::from_le_bytes)
+    }
+
+    pub(super) fn u64(&mut self) -> io::Result<u64> {
+        self.take().map(u64::from_le_bytes)
+    }
+
+    pub(super) fn i64(&mut self) -> io::Result<i64> {
+        self.take().map(i64::from_le_bytes)
+    }
+
+    pub(super) fn len(&mut self) -> io::Result<usize> {
+        self.u32().map(|len| len as usize)
+    }
+
+    pub(super) fn str(&mut self) -> io::Result<String> {
+        let len = self.len()?;
+        if self.buf.len() < len {
+            return Err(truncated());
+        }
+        let (bytes, rest) = self.buf.split_at(len);
+        self.buf = rest;
+        String::from_utf8(bytes.to_vec())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid UTF-8 string"))
+    }
+
+    pub(super) fn opt_str(&mut self) -> io::Result<Option<String>> {
+        if self.u8()? == 0 {
+            Ok(None)
+        } else {
+            self.str().map(Some)
+        }
+    }
+
+    pub(super) fn opt_u32(&mut self) -> io::Result<Option<u32>> {
+        if self.u8()? == 0 {
+            Ok(None)
+        } else {
+            self.u32().map(Some)
+        }
+    }
+}
+
+fn truncated() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "truncated record")
+}
+
+pub(super) fn encode_config(enc: &mut Encoder, config: &SessionConfig) {
+    enc.str(&config.hud_version);
+    enc.u32(config.pid.cast_unsigned());
+    enc.str(&config.target);
+    enc.u64(config.threshold_ms);
+    enc.u64(config.window_secs);
+    enc.u64(config.started_at_ms);
+    encode_workers(enc, &config.workers);
+}
+
+pub(super) fn decode_config(dec: &mut Decoder) -> io::Result<SessionConfig> {
+    Ok(SessionConfig {
+        hud_version: dec.str()?,
+        pid: dec.u32()?.cast_signed(),
+        target: dec.str()?,
+        threshold_ms: dec.u64()?,
+        window_secs: dec.u64()?,
+        started_at_ms: dec.u64()?,
+        workers: decode_workers(dec)?,
+    })
+}
+
+pub(super) fn encode_workers(enc: &mut Encoder, workers: &[WorkerInfo]) {
+    enc.len(workers.len());
+    for worker in workers {
+        enc.u32(worker.tid.0);
+        enc.u32(worker.worker_id);
+        enc.str(&worker.comm);
+    }
+}
+
+pub(super) fn decode_workers(dec: &mut Decoder) -> io::Result<Vec<WorkerInfo>> {
+    (0..dec.len()?)
+        .map(|_| Ok(WorkerInfo { tid: Tid(dec.u32()?), worker_id: dec.u32()?, comm: dec.str()? }))
+        .collect()
+}
+
+/// Every field of the eBPF event, in declaration order (padding excluded)
+pub(super) fn encode_task_event(enc: &mut Encoder, event: &TaskEvent) {
+    enc.u32(event.pid);
+    enc.u32(event.tid);
+    enc.u64(event.timestamp_ns);
+    enc.u32(event.event_type);
+    enc.i64(event.stack_id);
+    enc.u64(event.duration_ns);
+    enc.u32(event.worker_id);
+    enc.u32(event.cpu_id);
+    enc.i64(event.thread_state);
+    enc.u64(event.task_id);
+    enc.u8(event.category);
+    enc.u8(event.detection_method);
+    enc.u8(event.is_tokio_worker);
+    enc.u32(event.blocker_tid);
+    enc.u32(event.blocker_pid);
+    enc.i64(event.blocker_stack_id);
+    enc.i64(event.blocker_kernel_stack_id);
+    enc.buf.extend_from_slice(&event.blocker_comm);
+}
+
+pub(super) fn decode_task_event(dec: &mut Decoder) -> io::Result<TaskEvent> {
+    Ok(TaskEvent {
+        pid: dec.u32()?,
+        tid: dec.u32()?,
+        timestamp_ns: dec.u64()?,
+        event_type: dec.u32()?,
+        stack_id: dec.i64()?,
+        duration_ns: dec.u64()?,
+        worker_id: dec.u32()?,
+        cpu_id: dec.u32()?,
+        thread_state: dec.i64()?,
+        task_id: dec.u64()?,
+        category: dec.u8()?,
+        detection_method: dec.u8()?,
+        is_tokio_worker: dec.u8()?,
+        _padding: [0; 5],
+        blocker_tid: dec.u32()?,
+        blocker_pid: dec.u32()?,
+        blocker_stack_id: dec.i64()?,
+        blocker_kernel_stack_id: dec.i64()?,
+        blocker_comm: dec.take()?,
+    })
+}
+
+pub(super) fn encode_frame(enc: &mut Encoder, frame: &StackFrame) {
+    enc.str(&frame.function);
+    enc.opt_str(frame.file.as_deref());
+    enc.opt_u32(frame.line);
+    enc.u8(origin_tag(frame.origin));
+    enc.u8(u8::from(frame.is_user_code));
+}
+
+pub(super) fn decode_frame(dec: &mut Decoder) -> io::Result<StackFrame> {
+    Ok(StackFrame {
+        function: dec.str()?,
+        file: dec.opt_str()?,
+        line: dec.opt_u32()?,
+        origin: origin_from_tag(dec.u8()?),
+        is_user_code: dec.u8()? != 0,
+    })
+}
+
+fn origin_tag(origin: FrameOrigin) -> u8 {
+    match origin {
+        FrameOrigin::UserCode => 1,
+        FrameOrigin::StdLib => 2,
+        FrameOrigin::RuntimeLib => 3,
+        FrameOrigin::ThirdParty => 4,
+        FrameOrigin::SystemLib => 5,
+        FrameOrigin::Kernel => 6,
+        FrameOrigin::Unknown => 0,
+    }
+}
+
+fn origin_from_tag(tag: u8) -> FrameOrigin {
+    match tag {
+        1 => FrameOrigin::UserCode,
+        2 => FrameOrigin::StdLib,
+        3 => FrameOrigin::RuntimeLib,
+        4 => FrameOrigin::ThirdParty,
+        5 => FrameOrigin::SystemLib,
+        6 => FrameOrigin::Kernel,
+        _ => FrameOrigin::Unknown,
+    }
+}
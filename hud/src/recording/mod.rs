@@ -0,0 +1,59 @@
+//! Session recording and offline replay
+//!
+//! `hud record -o session.hud` saves what the TUI was sent to a compact
+//! binary file; `hud replay session.hud` feeds it back through the same
+//! `TraceEvent` channel, without root or the profiled process.
+//!
+//! ## Format (version 1)
+//!
+//! Integers are little-endian; strings are a `u32` byte length plus UTF-8.
+//!
+//! ```text
+//! header   "HUDREC" | version: u16 | config length: u32 | SessionConfig
+//! record   tag: u8  | payload length: u32 | payload
+//! ```
+//!
+//! | Tag | Record  | Payload                                              |
+//! |-----|---------|------------------------------------------------------|
+//! | 1   | Stack   | stack id, resolved frames                            |
+//! | 2   | Event   | raw `TaskEvent`, call stack id, blocker              |
+//! | 3   | Workers | workers added and removed by re-discovery            |
+//!
+//! Each resolved stack is written once, before the first event using it.
+//! Stack ids start at 1; 0 means "no stack". Only events that reached the
+//! TUI are recorded, so a replay shows what the live session showed. A file
+//! cut short (killed recorder) replays up to its last complete record.
+
+mod codec;
+pub mod reader;
+pub mod writer;
+
+pub use reader::{Record, SessionReader};
+pub use writer::SessionRecorder;
+
+use crate::profiling::WorkerInfo;
+
+/// File signature
+pub const MAGIC: &[u8; 6] = b"HUDREC";
+
+/// Bumped on any incompatible layout change
+pub const FORMAT_VERSION: u16 = 1;
+
+/// Everything about a session that is not an event
+#[derive(Debug, Clone, Default)]
+pub struct SessionConfig {
+    /// hud version that wrote the file
+    pub hud_version: String,
+    /// Profiled process
+    pub pid: i32,
+    /// Binary used for symbol resolution
+    pub target: String,
+    /// Blocking threshold (`--threshold`)
+    pub threshold_ms: u64,
+    /// Rolling window of the live TUI (`--window`, 0 = all data)
+    pub window_secs: u64,
+    /// Wall-clock start, in milliseconds since the Unix epoch
+    pub started_at_ms: u64,
+    /// Workers registered when recording started
+    pub workers: Vec<WorkerInfo>,
+}
@@ -0,0 +1,289 @@
+//! Session reader for `hud replay`
+
+use anyhow::{anyhow, bail, Context, Result};
+use hud_common::TaskEvent;
+use log::warn;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::Path;
+use std::sync::Arc;
+
+use super::codec::{decode_config, decode_frame, decode_task_event, decode_workers, Decoder};
+use super::writer::{TAG_EVENT, TAG_STACK, TAG_WORKERS};
+use super::{SessionConfig, FORMAT_VERSION, MAGIC};
+use crate::profiling::event_processor::build_trace_event;
+use crate::profiling::WorkerChanges;
+use crate::trace_data::{Blocker, StackFrame, TraceEvent};
+
+/// Largest record accepted; anything bigger means a corrupt length
+const MAX_RECORD_LEN: usize = 64 << 20;
+
+/// A replayable entry of a session file
+pub enum Record {
+    /// An event, rebuilt exactly as the live session sent it to the TUI
+    Event { raw: Box<TaskEvent>, trace: TraceEvent },
+    /// Workers added or removed by re-discovery
+    Workers(WorkerChanges),
+}
+
+/// Reads a session file written by [`SessionRecorder`](super::SessionRecorder)
+pub struct SessionReader<R: Read = BufReader<File>> {
+    input: R,
+    config: SessionConfig,
+    /// Stack table, filled as stack records go by
+    stacks: HashMap<u32, Arc<Vec<StackFrame>>>,
+}
+
+impl SessionReader {
+    /// Open `path` and read its header
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be opened or is not a session
+    /// file this version of hud can read
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = File::open(path)
+            .with_context(|| format!("Failed to open recording {}", path.display()))?;
+        Self::new(BufReader::new(file)).map_err(|e| anyhow!("{}: {e:#}", path.display()))
+    }
+}
+
+impl<R: Read> SessionReader<R> {
+    /// Read the session header from `input`
+    ///
+    /// # Errors
+    /// Returns an error on a missing signature, an unsupported format
+    /// version or a truncated header
+    pub fn new(mut input: R) -> Result<Self> {
+        let mut magic = [0; 6];
+        if input.read_exact(&mut magic).is_err() || &magic != MAGIC {
+            bail!("Not a hud recording");
+        }
+        let mut version = [0; 2];
+        input.read_exact(&mut version).context("Truncated recording header")?;
+        let version = u16::from_le_bytes(version);
+        if version != FORMAT_VERSION {
+            bail!(
+                "Unsupported recording version {version} (this hud reads version {FORMAT_VERSION})"
+            );
+        }
+
+        let header = read_payload(&mut input).context("Truncated recording header")?;
+        let config = decode_config(&mut Decoder::new(&header)).context("Corrupt session header")?;
+        Ok(Self { input, config, stacks: HashMap::new() })
+    }
+
+    /// Session metadata from the header
+    #[must_use]
+    pub fn config(&self) -> &SessionConfig {
+        &self.config
+    }
+
+    /// Next event or worker change, `None` at the end of the file.
+    ///
+    /// A file cut off mid-record ends at its last complete record.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be read or a record is corrupt
+    pub fn next_record(&mut self) -> Result<Option<Record>> {
+        loop {
+            let mut tag = [0; 1];
+            if self.input.read(&mut tag)? == 0 {
+                return Ok(None);
+            }
+            let payload = match read_payload(&mut self.input) {
+                Ok(payload) => payload,
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                    warn!("Recording ends mid-record (was hud killed?); stopping there");
+                    return Ok(None);
+                }
+                Err(e) => return Err(e.into()),
+            };
+            let mut dec = Decoder::new(&payload);
+
+            match tag[0] {
+                TAG_STACK => {
+                    let id = dec.u32()?;
+                    let frames = (0..dec.len()?)
+                        .map(|_| decode_frame(&mut dec))
+                        .collect::<io::Result<Vec<_>>>()?;
+                    self.stacks.insert(id, Arc::new(frames));
+                }
+                TAG_EVENT => return self.decode_event(&mut dec).map(Some),
+                TAG_WORKERS => {
+                    let active = usize::try_from(dec.u64()?).unwrap_or(usize::MAX);
+                    let added = decode_workers(&mut dec)?;
+                    let removed = decode_workers(&mut dec)?;
+                    return Ok(Some(Record::Workers(WorkerChanges { added, removed, active })));
+                }
+                // Written by a newer hud of the same format version
+                _ => {}
+            }
+        }
+    }
+
+    fn decode_event(&self, dec: &mut Decoder) -> Result<Record> {
+        let raw = decode_task_event(dec)?;
+        let call_stack = self.stack(dec.u32()?)?;
+        let blocker = if dec.u8()? == 0 {
+            None
+        } else {
+            Some(Blocker {
+                tid: dec.u32()?,
+                samples: dec.u32()?,
+                comm: dec.opt_str()?,
+                kernel_stack: self.stack(dec.u32()?)?,
+            })
+        };
+
+        let mut trace = build_trace_event(&raw, call_stack);
+        trace.blocker = blocker;
+        Ok(Record::Event { raw: Box::new(raw), trace })
+    }
+
+    fn stack(&self, id: u32) -> Result<Option<Arc<Vec<StackFrame>>>> {
+        if id == 0 {
+            return Ok(None);
+        }
+        match self.stacks.get(&id) {
+            Some(stack) => Ok(Some(Arc::clone(stack))),
+            None => bail!("Corrupt recording: stack {id} used before it was written"),
+        }
+    }
+}
+
+/// Read a `u32` length and that many bytes
+fn read_payload(input: &mut impl Read) -> io::Result<Vec<u8>> {
+    let mut len = [0; 4];
+    input.read_exact(&mut len)?;
+    let len = u32::from_le_bytes(len) as usize;
+    if len > MAX_RECORD_LEN {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "corrupt record length"));
+    }
+    let mut payload = vec![0; len];
+    input.read_exact(&mut payload)?;
+    Ok(payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::classification::FrameOrigin;
+    use crate::domain::Tid;
+    use crate::profiling::WorkerInfo;
+    use crate::recording::SessionRecorder;
+    use crate::testing::{frame, frame_at, worker_event};
+    use hud_common::EVENT_SCHEDULER_DETECTED;
+
+    fn worker(tid: u32, worker_id: u32) -> WorkerInfo {
+        WorkerInfo { tid: Tid(tid), worker_id, comm: "tokio-runtime-w".into() }
+    }
+
+    fn scheduler_event(timestamp_ns: u64) -> TaskEvent {
+        let mut blocker_comm = [0; 16];
+        blocker_comm[..7].copy_from_slice(b"hasher1");
+        TaskEvent {
+            pid: 100,
+            tid: 101,
+            cpu_id: 3,
+            task_id: 9,
+            blocker_tid: 102,
+            blocker_pid: 100,
+            blocker_stack_id: 5,
+            blocker_kernel_stack_id: 6,
+            blocker_comm,
+            ..worker_event(EVENT_SCHEDULER_DETECTED, timestamp_ns, 7_000_000)
+        }
+    }
+
+    fn config() -> SessionConfig {
+        SessionConfig {
+            hud_version: "0.0.0".into(),
+            pid: 100,
+            target: "/srv/app".into(),
+            threshold_ms: 5,
+            window_secs: 30,
+            started_at_ms: 1_700_000_000_000,
+            workers: vec![worker(101, 0), worker(103, 1)],
+        }
+    }
+
+    /// Two events sharing one stack, a worker change, then the second event
+    fn record_session() -> Vec<u8> {
+        let stack = Arc::new(vec![frame_at("app::hash", "src/main.rs", 42, FrameOrigin::UserCode)]);
+        let kernel = Arc::new(vec![frame("do_syscall_64", FrameOrigin::Kernel)]);
+
+        let mut recorder = SessionRecorder::new(Vec::new(), &config()).unwrap();
+        for ts in [1_000_000_000, 2_000_000_000] {
+            let event = scheduler_event(ts);
+            let mut trace = build_trace_event(&event, Some(Arc::clone(&stack)));
+            trace.blocker = Some(Blocker {
+                tid: 102,
+                samples: 0,
+                comm: Some("hasher1".into()),
+                kernel_stack: Some(Arc::clone(&kernel)),
+            });
+            recorder.record_event(&event, &trace).unwrap();
+            if ts == 1_000_000_000 {
+                let changes =
+                    WorkerChanges { added: vec![worker(104, 2)], removed: vec![], active: 3 };
+                recorder.record_worker_changes(&changes).unwrap();
+            }
+        }
+        assert_eq!(recorder.events, 2);
+        recorder.finish().unwrap()
+    }
+
+    #[test]
+    fn round_trip_rebuilds_trace_events() {
+        let data = record_session();
+        let mut reader = SessionReader::new(&data[..]).unwrap();
+        assert_eq!(reader.config().target, "/srv/app");
+        assert_eq!(reader.config().workers.len(), 2);
+
+        let Some(Record::Event { raw, trace: first }) = reader.next_record().unwrap() else {
+            panic!("expected event");
+        };
+        assert_eq!(raw.blocker_comm_bytes(), b"hasher1");
+        assert_eq!(first.name, "app::hash");
+        assert_eq!(first.duration_ns, Some(7_000_000));
+        assert_eq!(first.task_id, Some(9));
+        let blocker = first.blocker.as_ref().unwrap();
+        assert_eq!(blocker.comm.as_deref(), Some("hasher1"));
+        assert_eq!(blocker.kernel_stack.as_ref().unwrap()[0].origin, FrameOrigin::Kernel);
+
+        let Some(Record::Workers(changes)) = reader.next_record().unwrap() else {
+            panic!("expected worker changes");
+        };
+        assert_eq!((changes.added[0].tid, changes.active), (Tid(104), 3));
+
+        let Some(Record::Event { trace: second, .. }) = reader.next_record().unwrap() else {
+            panic!("expected event");
+        };
+        // Stacks are stored once and shared again on replay
+        assert!(Arc::ptr_eq(
+            first.call_stack.as_ref().unwrap(),
+            second.call_stack.as_ref().unwrap()
+        ));
+        assert!(reader.next_record().unwrap().is_none());
+    }
+
+    #[test]
+    fn truncated_file_replays_complete_records() {
+        let data = record_session();
+        let mut reader = SessionReader::new(&data[..data.len() - 3]).unwrap();
+        assert!(matches!(reader.next_record().unwrap(), Some(Record::Event { .. })));
+        assert!(matches!(reader.next_record().unwrap(), Some(Record::Workers(_))));
+        assert!(reader.next_record().unwrap().is_none());
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        assert!(SessionReader::new(&b"{\"traceEvents\": []}"[..]).is_err());
+
+        let mut data = record_session();
+        data[6] = 99;
+        let err = SessionReader::new(&data[..]).err().unwrap();
+        assert!(err.to_string().contains("version 99"));
+    }
+}
@@ -0,0 +1,153 @@
+//! Streaming session writer
+//!
+//! Records are written as events arrive and flushed about once a second
+//! (see [`SessionRecorder::flush`]), so a killed recorder loses at most the
+//! last second of the session. Resolved stacks are shared between events through `Arc`
+//! (see `StackCache`), which is what deduplicates them in the file.
+
+use anyhow::{Context, Result};
+use hud_common::TaskEvent;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+use super::codec::{encode_config, encode_frame, encode_task_event, encode_workers, Encoder};
+use super::{SessionConfig, FORMAT_VERSION, MAGIC};
+use crate::profiling::WorkerChanges;
+use crate::trace_data::{StackFrame, TraceEvent};
+
+pub(super) const TAG_STACK: u8 = 1;
+pub(super) const TAG_EVENT: u8 = 2;
+pub(super) const TAG_WORKERS: u8 = 3;
+
+/// Writes a session file for `hud replay`
+pub struct SessionRecorder<W: Write = BufWriter<File>> {
+    out: W,
+    /// Stacks already written, keyed by `Arc` address. The `Arc` is held so
+    /// the address cannot be reused by a different stack.
+    stacks: HashMap<usize, (u32, Arc<Vec<StackFrame>>)>,
+    /// Events written so far
+    pub events: u64,
+}
+
+impl SessionRecorder {
+    /// Create `path` and write the session header
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be created or written
+    pub fn create(path: &Path, config: &SessionConfig) -> Result<Self> {
+        let file = File::create(path)
+            .with_context(|| format!("Failed to create recording {}", path.display()))?;
+        Self::new(BufWriter::new(file), config).context("Failed to write recording header")
+    }
+}
+
+impl<W: Write> SessionRecorder<W> {
+    /// Write the session header to `out`
+    ///
+    /// # Errors
+    /// Returns an error if writing to `out` fails
+    pub fn new(mut out: W, config: &SessionConfig) -> io::Result<Self> {
+        let mut enc = Encoder::default();
+        encode_config(&mut enc, config);
+
+        out.write_all(MAGIC)?;
+        out.write_all(&FORMAT_VERSION.to_le_bytes())?;
+        let mut header = Encoder::default();
+        header.len(enc.buf.len());
+        out.write_all(&header.buf)?;
+        out.write_all(&enc.buf)?;
+
+        Ok(Self { out, stacks: HashMap::new(), events: 0 })
+    }
+
+    /// Record an event exactly as it was sent to the TUI
+    ///
+    /// # Errors
+    /// Returns an error if writing to the session file fails
+    pub fn record_event(&mut self, event: &TaskEvent, trace_event: &TraceEvent) -> io::Result<()> {
+        let call_stack = self.stack_ref(trace_event.call_stack.as_ref())?;
+        let blocker = trace_event
+            .blocker
+            .as_ref()
+            .map(|b| Ok::<_, io::Error>((b, self.stack_ref(b.kernel_stack.as_ref())?)))
+            .transpose()?;
+
+        let mut enc = Encoder::default();
+        encode_task_event(&mut enc, event);
+        enc.u32(call_stack);
+        enc.u8(u8::from(blocker.is_some()));
+        if let Some((blocker, kernel_stack)) = blocker {
+            enc.u32(blocker.tid);
+            enc.u32(blocker.samples);
+            enc.opt_str(blocker.comm.as_deref());
+            enc.u32(kernel_stack);
+        }
+        self.write_record(TAG_EVENT, &enc.buf)?;
+        self.events += 1;
+        Ok(())
+    }
+
+    /// Record workers found or lost by re-discovery
+    ///
+    /// # Errors
+    /// Returns an error if writing to the session file fails
+    pub fn record_worker_changes(&mut self, changes: &WorkerChanges) -> io::Result<()> {
+        let mut enc = Encoder::default();
+        enc.u64(changes.active as u64);
+        encode_workers(&mut enc, &changes.added);
+        encode_workers(&mut enc, &changes.removed);
+        self.write_record(TAG_WORKERS, &enc.buf)
+    }
+
+    /// Push buffered records to the file, so they survive the recorder being
+    /// killed
+    ///
+    /// # Errors
+    /// Returns an error if writing to the session file fails
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+
+    /// Flush buffered records and return the underlying writer
+    ///
+    /// # Errors
+    /// Returns an error if the final flush fails
+    pub fn finish(mut self) -> io::Result<W> {
+        self.out.flush()?;
+        Ok(self.out)
+    }
+
+    /// Id of `stack` in the file (0 = none), writing it on first use
+    #[allow(clippy::cast_possible_truncation)]
+    fn stack_ref(&mut self, stack: Option<&Arc<Vec<StackFrame>>>) -> io::Result<u32> {
+        let Some(stack) = stack else {
+            return Ok(0);
+        };
+        let key = Arc::as_ptr(stack) as usize;
+        if let Some((id, _)) = self.stacks.get(&key) {
+            return Ok(*id);
+        }
+
+        let id = self.stacks.len() as u32 + 1;
+        let mut enc = Encoder::default();
+        enc.u32(id);
+        enc.len(stack.len());
+        for frame in stack.iter() {
+            encode_frame(&mut enc, frame);
+        }
+        self.write_record(TAG_STACK, &enc.buf)?;
+        self.stacks.insert(key, (id, Arc::clone(stack)));
+        Ok(id)
+    }
+
+    fn write_record(&mut self, tag: u8, payload: &[u8]) -> io::Result<()> {
+        let mut header = Encoder::default();
+        header.u8(tag);
+        header.len(payload.len());
+        self.out.write_all(&header.buf)?;
+        self.out.write_all(payload)
+    }
+}
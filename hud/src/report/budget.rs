@@ -0,0 +1,300 @@
+//! Blocking budget assertions (`--assert`)
+//!
+//! Rules checked against the session's `HotspotStats` when profiling ends.
+//! Any failure makes hud exit with a distinct code after printing a table of
+//! every rule, so a CI step can gate on hud's exit status alone:
+//!
+//! ```text
+//! blocking budget: 1 of 2 assertions failed
+//!   STATUS  RULE                      ACTUAL   DETAIL
+//!   FAIL    max-share=10              41.2%    my_app::hash_password
+//!   ok      forbid=my_app::api::*     0        no matching hotspot
+//! ```
+
+use anyhow::{Context, Result};
+use std::fmt;
+use std::io::Write;
+use std::str::FromStr;
+
+use crate::analysis::HotspotStats;
+
+/// One `--assert` rule
+#[derive(Debug, Clone, PartialEq)]
+pub enum Assertion {
+    /// `max-share=PCT`: no single function above PCT% of samples
+    MaxShare(f64),
+    /// `forbid=PATTERN`: no hotspot whose name matches PATTERN (`*` wildcards)
+    Forbid(String),
+    /// `max-sched-waits=N@MS`: at most N scheduler waits longer than MS
+    MaxSchedWaits { count: usize, over_ms: u64 },
+    /// `max-imbalance=RATIO`: the busiest worker has at most RATIO times the
+    /// mean samples per worker
+    MaxImbalance(f64),
+}
+
+impl FromStr for Assertion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (rule, value) = s.split_once('=').ok_or_else(|| {
+            format!("expected RULE=VALUE, got '{s}' (rules: {})", Self::RULES.join(", "))
+        })?;
+        let value = value.trim();
+
+        match rule.trim() {
+            "max-share" => {
+                let pct = value.trim_end_matches('%');
+                pct.parse().map(Self::MaxShare).map_err(|_| format!("invalid percentage '{value}'"))
+            }
+            "forbid" if !value.is_empty() => Ok(Self::Forbid(value.to_string())),
+            "forbid" => Err("forbid needs a function name pattern".to_string()),
+            "max-sched-waits" => {
+                let (count, over) =
+                    value.split_once('@').ok_or_else(|| format!("expected N@MS, got '{value}'"))?;
+                let count = count.parse().map_err(|_| format!("invalid count '{count}'"))?;
+                let over_ms = over
+                    .trim_end_matches("ms")
+                    .parse()
+                    .map_err(|_| format!("invalid duration '{over}'"))?;
+                Ok(Self::MaxSchedWaits { count, over_ms })
+            }
+            "max-imbalance" => value
+                .parse()
+                .map(Self::MaxImbalance)
+                .map_err(|_| format!("invalid ratio '{value}'")),
+            other => Err(format!("unknown rule '{other}' (rules: {})", Self::RULES.join(", "))),
+        }
+    }
+}
+
+impl fmt::Display for Assertion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MaxShare(pct) => write!(f, "max-share={pct}"),
+            Self::Forbid(pattern) => write!(f, "forbid={pattern}"),
+            Self::MaxSchedWaits { count, over_ms } => {
+                write!(f, "max-sched-waits={count}@{over_ms}ms")
+            }
+            Self::MaxImbalance(ratio) => write!(f, "max-imbalance={ratio}"),
+        }
+    }
+}
+
+/// Result of checking one rule
+#[derive(Debug, Clone)]
+pub struct Outcome {
+    pub rule: String,
+    pub passed: bool,
+    /// Measured value, formatted for the table
+    pub actual: String,
+    /// Offending function or worker, or why the rule passed trivially
+    pub detail: String,
+}
+
+impl Assertion {
+    const RULES: [&'static str; 4] =
+        ["max-share=PCT", "forbid=PATTERN", "max-sched-waits=N@MS", "max-imbalance=RATIO"];
+
+    /// Check this rule against the session's statistics
+    ///
+    /// `workers` are the worker IDs known at the end of the session, so
+    /// workers that never blocked count toward the imbalance mean.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn check(&self, stats: &HotspotStats, workers: &[u32]) -> Outcome {
+        let (passed, actual, detail) = match self {
+            Self::MaxShare(limit) => match stats.to_hotspots().first() {
+                Some(top) => {
+                    (top.percentage <= *limit, format!("{:.1}%", top.percentage), top.name.clone())
+                }
+                None => (true, "0.0%".to_string(), "no samples".to_string()),
+            },
+            Self::Forbid(pattern) => {
+                let matches: Vec<_> = stats
+                    .to_hotspots()
+                    .into_iter()
+                    .filter(|h| glob_match(pattern, &h.name))
+                    .collect();
+                let detail = match matches.first() {
+                    Some(h) if matches.len() > 1 => {
+                        format!("{} ({:.1}%) and {} more", h.name, h.percentage, matches.len() - 1)
+                    }
+                    Some(h) => format!("{} ({:.1}%)", h.name, h.percentage),
+                    None => "no matching hotspot".to_string(),
+                };
+                (matches.is_empty(), matches.len().to_string(), detail)
+            }
+            Self::MaxSchedWaits { count, over_ms } => {
+                let waits = stats.sched_waits_over(over_ms * 1_000_000);
+                (waits <= *count, waits.to_string(), format!("waits over {over_ms}ms"))
+            }
+            Self::MaxImbalance(limit) => {
+                let mut samples = stats.worker_samples();
+                for &worker in workers {
+                    samples.entry(worker).or_insert(0);
+                }
+                let total: u64 = samples.values().sum();
+                match samples.iter().max_by_key(|(&worker, &count)| (count, worker)) {
+                    Some((&worker, &busiest)) if total > 0 => {
+                        let mean = total as f64 / samples.len() as f64;
+                        let ratio = busiest as f64 / mean;
+                        let detail = format!(
+                            "worker {worker}: {busiest} of {total} samples, {} workers",
+                            samples.len()
+                        );
+                        (ratio <= *limit, format!("{ratio:.2}"), detail)
+                    }
+                    _ => (true, "-".to_string(), "no samples".to_string()),
+                }
+            }
+        };
+        Outcome { rule: self.to_string(), passed, actual, detail }
+    }
+}
+
+/// Check every rule, in order
+#[must_use]
+pub fn check_all(assertions: &[Assertion], stats: &HotspotStats, workers: &[u32]) -> Vec<Outcome> {
+    assertions.iter().map(|a| a.check(stats, workers)).collect()
+}
+
+/// Print the outcome of every rule as a table
+///
+/// # Errors
+/// Returns an error if writing to the output fails
+pub fn write_table(outcomes: &[Outcome], writer: &mut dyn Write) -> Result<()> {
+    let failed = outcomes.iter().filter(|o| !o.passed).count();
+    let rule_width = outcomes.iter().map(|o| o.rule.len()).max().unwrap_or(0).max(4);
+    let actual_width = outcomes.iter().map(|o| o.actual.len()).max().unwrap_or(0).max(6);
+
+    if failed == 0 {
+        writeln!(writer, "blocking budget: all {} assertions passed", outcomes.len())
+    } else {
+        writeln!(writer, "blocking budget: {failed} of {} assertions failed", outcomes.len())
+    }
+    .context("Failed to write assertion results")?;
+    writeln!(
+        writer,
+        "  {:<7} {:<rule_width$}  {:<actual_width$}  DETAIL",
+        "STATUS", "RULE", "ACTUAL"
+    )
+    .context("Failed to write assertion results")?;
+    for o in outcomes {
+        let status = if o.passed { "ok" } else { "FAIL" };
+        writeln!(
+            writer,
+            "  {status:<7} {:<rule_width$}  {:<actual_width$}  {}",
+            o.rule, o.actual, o.detail
+        )
+        .context("Failed to write assertion results")?;
+    }
+    Ok(())
+}
+
+/// Match `name` against `pattern`, where `*` matches any run of characters
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = name.strip_prefix(first) else {
+        return false;
+    };
+    let mut parts: Vec<&str> = parts.collect();
+    // No wildcard: exact match
+    let Some(last) = parts.pop() else {
+        return rest.is_empty();
+    };
+    for part in parts {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::sample;
+    use crate::trace_data::TraceEvent;
+
+    fn stats() -> HotspotStats {
+        let mut stats = HotspotStats::new();
+        for _ in 0..6 {
+            stats.record_event(&sample("my_app::api::login", 0));
+        }
+        for worker in 0..4 {
+            stats.record_event(&sample("my_app::db::query", worker));
+        }
+        for wait_ms in [8, 25, 40] {
+            stats.record_event(&TraceEvent {
+                duration_ns: Some(wait_ms * 1_000_000),
+                ..sample("execution", 1)
+            });
+        }
+        stats
+    }
+
+    fn check(rule: &str) -> Outcome {
+        rule.parse::<Assertion>().unwrap().check(&stats(), &[0, 1, 2, 3])
+    }
+
+    #[test]
+    fn test_parse_rules() {
+        assert_eq!("max-share=10%".parse(), Ok(Assertion::MaxShare(10.0)));
+        assert_eq!(
+            "max-sched-waits=5@20ms".parse(),
+            Ok(Assertion::MaxSchedWaits { count: 5, over_ms: 20 })
+        );
+        assert_eq!("max-imbalance=1.5".parse(), Ok(Assertion::MaxImbalance(1.5)));
+        assert!("max-share".parse::<Assertion>().is_err());
+        assert!("max-sched-waits=5".parse::<Assertion>().is_err());
+        assert!("min-share=10".parse::<Assertion>().is_err());
+    }
+
+    #[test]
+    fn test_max_share() {
+        let outcome = check("max-share=50");
+        assert!(!outcome.passed);
+        assert_eq!(outcome.actual, "60.0%");
+        assert_eq!(outcome.detail, "my_app::api::login");
+        assert!(check("max-share=60").passed);
+    }
+
+    #[test]
+    fn test_forbid_matches_wildcards() {
+        assert!(!check("forbid=my_app::api::*").passed);
+        assert!(!check("forbid=*::query").passed);
+        assert!(check("forbid=my_app::cache::*").passed);
+        assert!(check("forbid=my_app::api").passed);
+        assert!(glob_match("a*b*c", "a-b-b-c"));
+        assert!(!glob_match("ab*ba", "aba"));
+    }
+
+    #[test]
+    fn test_max_sched_waits_counts_waits_over_limit() {
+        assert_eq!(check("max-sched-waits=1@20ms").actual, "2");
+        assert!(!check("max-sched-waits=1@20ms").passed);
+        assert!(check("max-sched-waits=2@20").passed);
+    }
+
+    #[test]
+    fn test_max_imbalance_includes_idle_workers() {
+        // Worker 0: 7 of 10 samples over 4 workers, mean 2.5
+        let outcome = check("max-imbalance=2");
+        assert_eq!(outcome.actual, "2.80");
+        assert!(!outcome.passed);
+        assert!(check("max-imbalance=3").passed);
+    }
+
+    #[test]
+    fn test_table_lists_every_rule() {
+        let outcomes = vec![check("max-share=50"), check("forbid=my_app::cache::*")];
+        let mut out = Vec::new();
+        write_table(&outcomes, &mut out).unwrap();
+        let table = String::from_utf8(out).unwrap();
+        assert!(table.starts_with("blocking budget: 1 of 2 assertions failed\n"));
+        assert!(table.contains("FAIL    max-share=50"));
+        assert!(table.contains("ok      forbid=my_app::cache::*"));
+    }
+}
@@ -0,0 +1,184 @@
+//! `JUnit` XML report (`--report-format junit`)
+//!
+//! One testsuite for the session, one testcase per hotspot. A hotspot whose
+//! share of samples is at or above the threshold fails, with its location,
+//! worker breakdown and scheduler waits in the failure body; the rest pass.
+//! CI systems (GitHub Actions reporters, GitLab, Jenkins) then show the
+//! blocking functions as failed tests.
+
+use anyhow::{Context, Result};
+use std::fmt::Write as _;
+use std::io::Write;
+
+use super::{HotspotSummary, Report};
+
+/// Write `report` as `JUnit` XML, failing hotspots at or above `threshold_pct`
+///
+/// # Errors
+/// Returns an error if writing to the output fails
+pub fn write(report: &Report, threshold_pct: f64, writer: &mut dyn Write) -> Result<()> {
+    let failures = report.hotspots.iter().filter(|h| h.percentage >= threshold_pct).count();
+    let session = &report.session;
+    let suite = format!("hud blocking hotspots: {} (pid {})", session.target, session.pid);
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        xml,
+        "<testsuites name=\"hud\" tests=\"{tests}\" failures=\"{failures}\" time=\"{time:.3}\">",
+        tests = report.hotspots.len(),
+        time = session.duration_secs,
+    );
+    let _ = writeln!(
+        xml,
+        "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{failures}\" time=\"{:.3}\">",
+        escape(&suite),
+        report.hotspots.len(),
+        session.duration_secs,
+    );
+
+    xml.push_str("    <properties>\n");
+    for (name, value) in [
+        ("hud_version", report.hud_version.clone()),
+        ("threshold_ms", session.threshold_ms.to_string()),
+        ("fail_at_percent", threshold_pct.to_string()),
+        ("total_samples", report.total_samples.to_string()),
+        ("exit_reason", session.exit_reason.clone()),
+    ] {
+        let _ = writeln!(xml, "      <property name=\"{name}\" value=\"{}\"/>", escape(&value));
+    }
+    xml.push_str("    </properties>\n");
+
+    for hotspot in &report.hotspots {
+        write_testcase(&mut xml, hotspot, threshold_pct);
+    }
+
+    xml.push_str("  </testsuite>\n</testsuites>\n");
+    writer.write_all(xml.as_bytes()).context("Failed to write JUnit report")?;
+    Ok(())
+}
+
+fn write_testcase(xml: &mut String, hotspot: &HotspotSummary, threshold_pct: f64) {
+    let mut attrs = format!("classname=\"hud.hotspots\" name=\"{}\"", escape(&hotspot.name));
+    if let Some(ref file) = hotspot.file {
+        let _ = write!(attrs, " file=\"{}\"", escape(file));
+    }
+    if let Some(line) = hotspot.line {
+        let _ = write!(attrs, " line=\"{line}\"");
+    }
+
+    if hotspot.percentage < threshold_pct {
+        let _ = writeln!(xml, "    <testcase {attrs}/>");
+        return;
+    }
+
+    let message = format!(
+        "{} blocked workers in {:.1}% of samples (fails at {threshold_pct}%)",
+        hotspot.name, hotspot.percentage
+    );
+    let mut details = format!("rank: {}\nsamples: {}\n", hotspot.rank, hotspot.samples);
+    if let Some(ref file) = hotspot.file {
+        let _ = writeln!(details, "location: {file}:{}", hotspot.line.unwrap_or(0));
+    }
+    for (worker, samples) in &hotspot.workers {
+        let _ = writeln!(details, "worker {worker}: {samples} samples");
+    }
+    if hotspot.sched_waits > 0 {
+        let _ = writeln!(
+            details,
+            "scheduler waits caused: {} ({:.1}ms)",
+            hotspot.sched_waits, hotspot.sched_wait_ms
+        );
+    }
+
+    let _ = writeln!(xml, "    <testcase {attrs}>");
+    let _ = writeln!(
+        xml,
+        "      <failure type=\"BlockingHotspot\" message=\"{}\">{}</failure>",
+        escape(&message),
+        escape(&details)
+    );
+    xml.push_str("    </testcase>\n");
+}
+
+/// Escape text for XML attributes and content
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::HotspotStats;
+    use crate::domain::Tid;
+    use crate::profiling::WorkerInfo;
+    use crate::recording::SessionConfig;
+    use crate::report::{Diagnostics, ReportFormat};
+    use crate::testing::sample;
+    use crate::trace_data::TraceEvent;
+
+    fn report() -> Report {
+        let mut stats = HotspotStats::new();
+        let hash = TraceEvent {
+            file: Some("src/lib.rs".to_string()),
+            line: Some(7),
+            ..sample("app::hash<[u8; 32]>", 0)
+        };
+        for _ in 0..9 {
+            stats.record_event(&hash);
+        }
+        stats.record_event(&sample("app::log", 1));
+
+        let config = SessionConfig {
+            hud_version: "0.0.0".to_string(),
+            pid: 42,
+            target: "/bin/app".to_string(),
+            threshold_ms: 5,
+            window_secs: 0,
+            started_at_ms: 0,
+            workers: vec![WorkerInfo { tid: Tid(100), worker_id: 0, comm: "tokio-rt".into() }],
+        };
+        Report::new(&config, 2.5, "duration limit reached", Diagnostics::default(), &stats)
+    }
+
+    #[test]
+    fn test_json_report_ranks_hotspots() {
+        let mut out = Vec::new();
+        report().write(ReportFormat::Json, 10.0, &mut out).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+
+        assert_eq!(json["session"]["pid"], 42);
+        assert_eq!(json["session"]["workers"][0]["tid"], 100);
+        assert_eq!(json["total_samples"], 10);
+        assert_eq!(json["hotspots"][0]["rank"], 1);
+        assert_eq!(json["hotspots"][0]["samples"], 9);
+        assert_eq!(json["hotspots"][1]["name"], "app::log");
+    }
+
+    #[test]
+    fn test_junit_fails_hotspots_over_threshold() {
+        let mut out = Vec::new();
+        report().write(ReportFormat::Junit, 50.0, &mut out).unwrap();
+        let xml = String::from_utf8(out).unwrap();
+
+        assert!(xml.contains("tests=\"2\" failures=\"1\""));
+        // Only the 90% hotspot fails
+        assert_eq!(xml.matches("<failure").count(), 1);
+        assert!(xml.contains("name=\"app::hash&lt;[u8; 32]&gt;\" file=\"src/lib.rs\" line=\"7\">"));
+        assert!(xml.contains("<testcase classname=\"hud.hotspots\" name=\"app::log\""));
+
+        let mut out = Vec::new();
+        report().write(ReportFormat::Junit, 95.0, &mut out).unwrap();
+        assert!(!String::from_utf8(out).unwrap().contains("<failure"));
+    }
+}
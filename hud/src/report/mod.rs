@@ -0,0 +1,185 @@
+//! Session summary report for CI (`--report`)
+//!
+//! Written once when profiling ends: the hotspot ranking the TUI would show
+//! (`HotspotStats::to_hotspots`, the streaming form of `analyze_hotspots`),
+//! plus session metadata and the event processor's diagnostics counters.
+//!
+//! - **JSON** ([`Report::write_json`]): everything, for scripts and dashboards
+//! - **`JUnit` XML** ([`junit`]): one testcase per hotspot, failing at or above
+//!   `--junit-threshold`, for CI systems that render test results
+//!
+//! [`budget`] checks `--assert` rules against the same statistics.
+
+pub mod budget;
+pub mod junit;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::Write;
+
+use crate::analysis::HotspotStats;
+use crate::recording::SessionConfig;
+
+/// Report file format (`--report-format`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum ReportFormat {
+    /// Full summary as JSON
+    #[default]
+    Json,
+    /// XML test results for CI, one testcase per hotspot
+    Junit,
+}
+
+/// Counters from the event processor, for judging how much to trust a report
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct Diagnostics {
+    /// Events read from the ring buffer
+    pub events: usize,
+    /// CPU samples from the perf sampler
+    pub perf_samples: usize,
+    /// Samples whose user stack was captured
+    pub stacks_ok: usize,
+    /// Samples whose user stack could not be captured
+    pub stacks_failed: usize,
+    /// Scheduler latency events over the threshold
+    pub scheduler_events: usize,
+    /// Scheduler events attributed to a blocking thread
+    pub scheduler_attributed: usize,
+    /// Samples dropped for running on the blocking pool
+    pub blocking_pool_filtered: usize,
+}
+
+/// Session metadata
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionSummary {
+    pub pid: i32,
+    pub target: String,
+    pub threshold_ms: u64,
+    pub started_at_ms: u64,
+    pub duration_secs: f64,
+    /// Why profiling stopped (`duration limit reached`, `process exited`, ...)
+    pub exit_reason: String,
+    pub workers: Vec<WorkerSummary>,
+}
+
+/// A Tokio worker thread at the end of the session
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkerSummary {
+    pub worker_id: u32,
+    pub tid: u32,
+    pub comm: String,
+}
+
+/// One ranked function
+#[derive(Debug, Clone, Serialize)]
+pub struct HotspotSummary {
+    /// 1 for the function with the most samples
+    pub rank: usize,
+    pub name: String,
+    pub samples: usize,
+    /// Share of all samples, 0-100
+    pub percentage: f64,
+    pub file: Option<String>,
+    pub line: Option<u32>,
+    /// Samples per worker ID
+    pub workers: BTreeMap<u32, usize>,
+    /// Scheduler waits this function caused by holding a worker's CPU
+    pub sched_waits: usize,
+    pub sched_wait_ms: f64,
+}
+
+/// Summary of a profiling session
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+    pub hud_version: String,
+    pub session: SessionSummary,
+    pub diagnostics: Diagnostics,
+    /// Samples behind the percentages
+    pub total_samples: u64,
+    /// Functions by samples, most first
+    pub hotspots: Vec<HotspotSummary>,
+}
+
+impl Report {
+    /// Build a report from the session's hotspot statistics
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn new(
+        config: &SessionConfig,
+        duration_secs: f64,
+        exit_reason: &str,
+        diagnostics: Diagnostics,
+        stats: &HotspotStats,
+    ) -> Self {
+        let hotspots = stats
+            .to_hotspots()
+            .into_iter()
+            .enumerate()
+            .map(|(i, h)| HotspotSummary {
+                rank: i + 1,
+                name: h.name,
+                samples: h.count,
+                percentage: h.percentage,
+                file: h.file,
+                line: h.line,
+                workers: h.workers.into_iter().collect(),
+                sched_waits: h.sched_waits,
+                sched_wait_ms: h.sched_wait_ns as f64 / 1_000_000.0,
+            })
+            .collect();
+
+        Self {
+            hud_version: config.hud_version.clone(),
+            session: SessionSummary {
+                pid: config.pid,
+                target: config.target.clone(),
+                threshold_ms: config.threshold_ms,
+                started_at_ms: config.started_at_ms,
+                duration_secs,
+                exit_reason: exit_reason.to_string(),
+                workers: config
+                    .workers
+                    .iter()
+                    .map(|w| WorkerSummary {
+                        worker_id: w.worker_id,
+                        tid: w.tid.0,
+                        comm: w.comm.clone(),
+                    })
+                    .collect(),
+            },
+            diagnostics,
+            total_samples: stats.total_samples(),
+            hotspots,
+        }
+    }
+
+    /// Write the report as pretty-printed JSON
+    ///
+    /// # Errors
+    /// Returns an error if writing to the output fails
+    pub fn write_json(&self, writer: &mut dyn Write) -> Result<()> {
+        serde_json::to_writer_pretty(&mut *writer, self).context("Failed to write report JSON")?;
+        writeln!(writer).context("Failed to write report JSON")?;
+        Ok(())
+    }
+
+    /// Write the report in `format`
+    ///
+    /// `threshold_pct` is the `JUnit` failure threshold; JSON reports include
+    /// every hotspot and leave judging them to the reader.
+    ///
+    /// # Errors
+    /// Returns an error if writing to the output fails
+    pub fn write(
+        &self,
+        format: ReportFormat,
+        threshold_pct: f64,
+        writer: &mut dyn Write,
+    ) -> Result<()> {
+        match format {
+            ReportFormat::Json => self.write_json(writer),
+            ReportFormat::Junit => junit::write(self, threshold_pct, writer),
+        }
+    }
+}
@@ -0,0 +1,160 @@
+//! Separate debug info lookup
+//!
+//! Production binaries are usually stripped, with DWARF split into a
+//! separate `.debug` file (`objcopy --only-keep-debug`, distro `-dbg`
+//! packages). The stripped binary records where to find it in two ways:
+//!
+//! - **Build ID** (`.note.gnu.build-id`): a hash of the build, looked up as
+//!   `.build-id/ab/cdef….debug` under a debug root
+//! - **Debug link** (`.gnu_debuglink`): a file name, looked up next to the
+//!   binary, in its `.debug/` subdirectory, and under `/usr/lib/debug`
+//!
+//! Directories passed with `--debug-dir` are searched first and may be laid
+//! out as a debug root (`.build-id/…`), a flat store (`<build-id>.debug`) or
+//! a debuginfod cache (`<build-id>/debuginfo`).
+//!
+//! A candidate is accepted only if it contains DWARF and, when both files
+//! carry a build ID, the IDs match. Debug link CRCs are not checked.
+
+use log::debug;
+use object::{Object, ObjectSection};
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// System-wide debug root used by distro debug packages
+const SYSTEM_DEBUG_ROOT: &str = "/usr/lib/debug";
+
+/// True if the object carries non-empty DWARF line/function info
+#[must_use]
+pub fn has_dwarf(obj: &object::File) -> bool {
+    obj.section_by_name(".debug_info").is_some_and(|s| s.size() > 0)
+}
+
+/// Find a separate debug file for `binary_path`.
+///
+/// `obj` is the parsed binary; `debug_dirs` are extra directories to search
+/// before the system locations. Returns `None` if the binary already has
+/// DWARF or no matching debug file exists.
+#[must_use]
+pub fn find_debug_file(
+    binary_path: &Path,
+    obj: &object::File,
+    debug_dirs: &[PathBuf],
+) -> Option<PathBuf> {
+    if has_dwarf(obj) {
+        return None;
+    }
+
+    let build_id = obj.build_id().ok().flatten();
+    let debuglink =
+        obj.gnu_debuglink().ok().flatten().and_then(|(name, _crc)| std::str::from_utf8(name).ok());
+
+    candidate_paths(binary_path, build_id, debuglink, debug_dirs)
+        .into_iter()
+        .find(|path| is_matching_debug_file(path, build_id))
+}
+
+/// All places a debug file may live, in search order.
+fn candidate_paths(
+    binary_path: &Path,
+    build_id: Option<&[u8]>,
+    debuglink: Option<&str>,
+    debug_dirs: &[PathBuf],
+) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+
+    if let Some(id) = build_id.filter(|id| id.len() > 1) {
+        let hex = id.iter().fold(String::new(), |mut s, b| {
+            let _ = write!(s, "{b:02x}");
+            s
+        });
+        let build_id_path = format!(".build-id/{}/{}.debug", &hex[..2], &hex[2..]);
+
+        for dir in debug_dirs {
+            paths.push(dir.join(&build_id_path));
+            paths.push(dir.join(format!("{hex}.debug")));
+            paths.push(dir.join(&hex).join("debuginfo"));
+        }
+        paths.push(Path::new(SYSTEM_DEBUG_ROOT).join(&build_id_path));
+    }
+
+    if let Some(name) = debuglink {
+        let binary_dir = binary_path.parent().unwrap_or(Path::new("/"));
+        for dir in debug_dirs {
+            paths.push(dir.join(name));
+        }
+        paths.push(binary_dir.join(name));
+        paths.push(binary_dir.join(".debug").join(name));
+        // /usr/lib/debug mirrors the binary's directory: /usr/lib/debug/usr/bin/app.debug
+        let mirrored = binary_dir.strip_prefix("/").unwrap_or(binary_dir);
+        paths.push(Path::new(SYSTEM_DEBUG_ROOT).join(mirrored).join(name));
+    }
+
+    paths
+}
+
+/// Check that `path` is a debug file for a binary with `build_id`.
+fn is_matching_debug_file(path: &Path, build_id: Option<&[u8]>) -> bool {
+    let Ok(data) = fs::read(path) else {
+        return false;
+    };
+    let Ok(obj) = object::File::parse(&*data) else {
+        debug!("Ignoring {}: not an object file", path.display());
+        return false;
+    };
+    if !has_dwarf(&obj) {
+        return false;
+    }
+    match (build_id, obj.build_id().ok().flatten()) {
+        (Some(expected), Some(actual)) if expected != actual => {
+            debug!("Ignoring {}: build ID mismatch", path.display());
+            false
+        }
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_id_paths_search_debug_dirs_first() {
+        let dirs = [PathBuf::from("/srv/debug")];
+        let paths =
+            candidate_paths(Path::new("/opt/app/server"), Some(&[0xab, 0xcd, 0xef]), None, &dirs);
+        assert_eq!(
+            paths,
+            [
+                PathBuf::from("/srv/debug/.build-id/ab/cdef.debug"),
+                PathBuf::from("/srv/debug/abcdef.debug"),
+                PathBuf::from("/srv/debug/abcdef/debuginfo"),
+                PathBuf::from("/usr/lib/debug/.build-id/ab/cdef.debug"),
+            ]
+        );
+    }
+
+    #[test]
+    fn debuglink_paths_follow_gdb_order() {
+        let paths = candidate_paths(Path::new("/opt/app/server"), None, Some("server.debug"), &[]);
+        assert_eq!(
+            paths,
+            [
+                PathBuf::from("/opt/app/server.debug"),
+                PathBuf::from("/opt/app/.debug/server.debug"),
+                PathBuf::from("/usr/lib/debug/opt/app/server.debug"),
+            ]
+        );
+    }
+
+    #[test]
+    fn binary_with_dwarf_needs_no_debug_file() {
+        // Test binaries are built with debug info
+        let exe = std::env::current_exe().unwrap();
+        let data = fs::read(&exe).unwrap();
+        let obj = object::File::parse(&*data).unwrap();
+        assert!(has_dwarf(&obj));
+        assert_eq!(find_debug_file(&exe, &obj, &[]), None);
+    }
+}
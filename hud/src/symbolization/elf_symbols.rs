@@ -0,0 +1,94 @@
+//! ELF symbol table lookup by demangled path
+//!
+//! Rust symbol names carry a per-build hash (legacy mangling) or crate
+//! disambiguator (v0 mangling), so the same function has a different linker
+//! name in every tokio build:
+//!
+//! ```text
+//! _ZN5tokio7runtime7context19set_current_task_id17h88510a52941c215fE   (legacy)
+//! _RNvNtNtCs1234_5tokio7runtime7context19set_current_task_id           (v0)
+//! ```
+//!
+//! Both demangle to `tokio::runtime::context::set_current_task_id` once the
+//! hash is dropped, which is what we match on. The result is a file offset,
+//! which is what uprobes attach to.
+
+use object::{Object, ObjectSection, ObjectSymbol, SymbolKind};
+
+/// A function located in an ELF symbol table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionSymbol {
+    /// Linker (mangled) name
+    pub name: String,
+    /// Virtual address from the symbol table
+    pub address: u64,
+    /// Offset of the function within the file (for uprobe attachment)
+    pub file_offset: u64,
+}
+
+/// Find a function by its demangled path, e.g. `tokio::runtime::context::set_current_task_id`.
+///
+/// Searches `.symtab` first, then `.dynsym`. Returns `None` if the binary
+/// cannot be parsed, is stripped, or the function was inlined away.
+#[must_use]
+pub fn find_function(binary: &[u8], path: &str) -> Option<FunctionSymbol> {
+    let obj = object::File::parse(binary).ok()?;
+
+    obj.symbols()
+        .chain(obj.dynamic_symbols())
+        .filter(|sym| sym.kind() == SymbolKind::Text && sym.is_definition())
+        .find(|sym| sym.name().is_ok_and(|name| demangled_path(name) == path))
+        .and_then(|sym| {
+            // Uprobes take file offsets: translate through the containing section
+            let section = obj.section_by_index(sym.section_index()?).ok()?;
+            let (section_offset, _) = section.file_range()?;
+            Some(FunctionSymbol {
+                name: sym.name().ok()?.to_string(),
+                address: sym.address(),
+                file_offset: sym.address() - section.address() + section_offset,
+            })
+        })
+}
+
+/// Demangle a Rust symbol to its path without hash or crate disambiguator.
+///
+/// Non-Rust symbols are returned unchanged.
+fn demangled_path(name: &str) -> String {
+    format!("{:#}", rustc_demangle::demangle(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legacy_mangling_drops_hash() {
+        assert_eq!(
+            demangled_path("_ZN5tokio7runtime7context19set_current_task_id17h88510a52941c215fE"),
+            "tokio::runtime::context::set_current_task_id"
+        );
+    }
+
+    #[test]
+    fn v0_mangling_drops_disambiguator() {
+        assert_eq!(
+            demangled_path("_RNvNtNtCs1234_5tokio7runtime7context19set_current_task_id"),
+            "tokio::runtime::context::set_current_task_id"
+        );
+    }
+
+    #[test]
+    fn finds_function_in_own_binary() {
+        let binary = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+        let symbol = find_function(&binary, "hud::symbolization::elf_symbols::find_function")
+            .expect("test binary keeps its symbol table");
+        assert!(symbol.name.contains("find_function"));
+        assert!(symbol.file_offset > 0);
+    }
+
+    #[test]
+    fn missing_function_is_none() {
+        let binary = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+        assert_eq!(find_function(&binary, "no::such::function"), None);
+    }
+}
@@ -0,0 +1,105 @@
+//! Kernel symbol resolution via `/proc/kallsyms`
+//!
+//! Kernel stack frames captured by eBPF are raw kernel addresses. There is no
+//! DWARF to consult, but the kernel exports its symbol table:
+//!
+//! ```text
+//! ffffffff81000000 T _stext
+//! ffffffff81c3e1a0 T __schedule
+//! ffffffffc0a41000 t nf_hook_slow    [nf_tables]
+//! ```
+//!
+//! Each address resolves to the closest preceding text symbol.
+//!
+//! With `kptr_restrict` set, unprivileged readers see all-zero addresses.
+//! hud runs as root, but zero entries are skipped anyway so a restricted
+//! table yields an empty (unresolving) symbol set instead of garbage.
+
+use anyhow::{Context, Result};
+
+/// Sorted kernel text symbols for address lookup.
+#[derive(Debug, Default)]
+pub struct KernelSymbols {
+    /// `(address, name)` sorted by address
+    symbols: Vec<(u64, String)>,
+}
+
+impl KernelSymbols {
+    /// Load symbols from `/proc/kallsyms`.
+    ///
+    /// # Errors
+    /// Returns an error if `/proc/kallsyms` cannot be read
+    pub fn load() -> Result<Self> {
+        let contents =
+            std::fs::read_to_string("/proc/kallsyms").context("Failed to read /proc/kallsyms")?;
+        Ok(Self::parse(&contents))
+    }
+
+    /// Parse the `/proc/kallsyms` format, keeping only text (code) symbols.
+    #[must_use]
+    pub fn parse(contents: &str) -> Self {
+        let mut symbols: Vec<(u64, String)> = contents
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split_whitespace();
+                let addr = u64::from_str_radix(fields.next()?, 16).ok()?;
+                let kind = fields.next()?;
+                let name = fields.next()?;
+                let is_text = matches!(kind, "t" | "T" | "w" | "W");
+                (addr != 0 && is_text).then(|| (addr, name.to_string()))
+            })
+            .collect();
+        symbols.sort_unstable_by_key(|(addr, _)| *addr);
+        Self { symbols }
+    }
+
+    /// Resolve a kernel address to the enclosing function name.
+    #[must_use]
+    pub fn resolve(&self, addr: u64) -> Option<&str> {
+        let idx = self.symbols.partition_point(|(start, _)| *start <= addr);
+        idx.checked_sub(1).map(|i| self.symbols[i].1.as_str())
+    }
+
+    /// True if no symbols were loaded (e.g., `kptr_restrict` hid the addresses)
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "\
+ffffffff81000000 T _stext
+ffffffff81c3e1a0 T __schedule
+ffffffff81c3e900 t schedule_idle
+ffffffff82000000 D jiffies
+ffffffffc0a41000 t nf_hook_slow\t[nf_tables]
+";
+
+    #[test]
+    fn resolves_to_preceding_text_symbol() {
+        let symbols = KernelSymbols::parse(SAMPLE);
+        assert_eq!(symbols.resolve(0xffff_ffff_81c3_e1a0), Some("__schedule"));
+        assert_eq!(symbols.resolve(0xffff_ffff_81c3_e1ff), Some("__schedule"));
+        assert_eq!(symbols.resolve(0xffff_ffff_c0a4_1010), Some("nf_hook_slow"));
+        assert_eq!(symbols.resolve(0x1000), None);
+    }
+
+    #[test]
+    fn skips_data_symbols() {
+        let symbols = KernelSymbols::parse(SAMPLE);
+        // `jiffies` is data, so addresses past it still resolve to the last function
+        assert_eq!(symbols.resolve(0xffff_ffff_8200_0010), Some("schedule_idle"));
+    }
+
+    #[test]
+    fn restricted_table_is_empty() {
+        let symbols =
+            KernelSymbols::parse("0000000000000000 T _stext\n0000000000000000 T __schedule\n");
+        assert!(symbols.is_empty());
+        assert_eq!(symbols.resolve(0xffff_ffff_8100_0000), None);
+    }
+}
@@ -1,14 +1,20 @@
 //! Memory mapping utilities for process address space analysis
 //!
 //! This module provides functionality for parsing /proc/pid/maps to determine
-//! the memory ranges of loaded binaries, which is essential for symbolizing
-//! addresses from position-independent executables (PIE).
+//! where binaries are loaded, which is essential for symbolizing addresses
+//! from position-independent executables (PIE). [`ModuleMap`] translates
+//! runtime addresses back to ELF virtual addresses.
+//!
+//! [`parse_executable_mappings`] returns every file-backed executable
+//! mapping (the binary and each shared library), so frames outside the main
+//! executable can be symbolized against the library they came from.
 
 use anyhow::{Context, Result};
 use log::info;
+use object::{Object, ObjectSegment};
 use std::fs;
 
-/// Memory range of a loaded binary in a process's address space
+/// Overall memory range of a loaded binary in a process's address space
 #[derive(Debug, Clone, Copy)]
 pub struct MemoryRange {
     pub start: u64,
@@ -23,62 +29,203 @@ impl MemoryRange {
     }
 }
 
-/// Parse /proc/pid/maps to find the memory range of a specific binary
-///
-/// This function reads the process's memory maps and finds all mappings
-/// that match the given binary path, returning the range from the minimum
-/// start address to the maximum end address.
+/// A file-backed executable mapping from /proc/pid/maps
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryMapping {
+    pub start: u64,
+    pub end: u64,
+    /// Offset of `start` within the mapped file
+    pub offset: u64,
+    /// Path of the mapped file (e.g., "/usr/lib/x86_64-linux-gnu/libc.so.6")
+    pub path: String,
+}
+
+impl MemoryMapping {
+    /// Check if an address falls within this mapping
+    #[must_use]
+    pub fn contains(&self, addr: u64) -> bool {
+        addr >= self.start && addr < self.end
+    }
+
+    /// Translate a runtime address to an offset within the mapped file
+    #[must_use]
+    pub fn file_offset(&self, addr: u64) -> u64 {
+        addr - self.start + self.offset
+    }
+}
+
+/// Parse /proc/pid/maps for every file-backed executable mapping
 ///
-/// # Arguments
-/// * `pid` - The process ID to query
-/// * `binary_path` - The path to the binary to find (e.g., "/path/to/executable")
+/// Anonymous mappings, JIT regions and pseudo-files (`[vdso]`, `[vsyscall]`)
+/// are skipped: there is no file to read symbols from.
 ///
 /// # Errors
-/// Returns an error if /proc/pid/maps cannot be read or if the binary is not found
-pub fn parse_memory_maps(pid: i32, binary_path: &str) -> Result<MemoryRange> {
+/// Returns an error if /proc/pid/maps cannot be read
+pub fn parse_executable_mappings(pid: i32) -> Result<Vec<MemoryMapping>> {
     let maps_path = format!("/proc/{pid}/maps");
     let maps = fs::read_to_string(&maps_path).context(format!("Failed to read {maps_path}"))?;
+    Ok(parse_executable_mappings_from(&maps))
+}
 
-    // Find ALL mappings of the target binary to get the full range
-    let (start_addr, end_addr) = maps
-        .lines()
-        .filter(|line| line.contains(binary_path))
+/// Parse the contents of a maps file (split out for testing)
+fn parse_executable_mappings_from(maps: &str) -> Vec<MemoryMapping> {
+    maps.lines()
         .filter_map(|line| {
-            // Parse the line: "start-end perms offset dev inode pathname"
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            if parts.len() >= 3 {
-                let range = parts[0];
-                let range_parts: Vec<&str> = range.split('-').collect();
-                if range_parts.len() == 2 {
-                    let start = u64::from_str_radix(range_parts[0], 16).ok()?;
-                    let end = u64::from_str_radix(range_parts[1], 16).ok()?;
-                    Some((start, end))
-                } else {
-                    None
-                }
-            } else {
-                None
+            // "start-end perms offset dev inode pathname"; the pathname is the
+            // rest of the line and may itself contain spaces
+            let mut parts = line.splitn(6, ' ');
+            let (start, end) = parts.next()?.split_once('-')?;
+            let perms = parts.next()?;
+            let offset = parts.next()?;
+            let path = parts.nth(2)?.trim_start();
+            // The binary was replaced on disk (e.g. upgraded while running)
+            let path = path.strip_suffix(" (deleted)").unwrap_or(path);
+            if !perms.contains('x') || !path.starts_with('/') {
+                return None;
             }
+            Some(MemoryMapping {
+                start: u64::from_str_radix(start, 16).ok()?,
+                end: u64::from_str_radix(end, 16).ok()?,
+                offset: u64::from_str_radix(offset, 16).ok()?,
+                path: path.to_string(),
+            })
         })
-        .fold((None, None), |(min_start, max_end), (start, end)| {
-            // Track the minimum start and maximum end
-            (
-                Some(min_start.map_or(start, |s: u64| s.min(start))),
-                Some(max_end.map_or(end, |e: u64| e.max(end))),
-            )
-        });
-
-    match (start_addr, end_addr) {
-        (Some(start), Some(end)) => {
-            info!(
-                "Executable memory range: 0x{:x} - 0x{:x} (size: {} KB)",
-                start,
-                end,
-                (end - start) / 1024
+        .collect()
+}
+
+/// Runtime layout of one loaded ELF file (executable or shared library)
+///
+/// Each executable segment is mapped separately, possibly with gaps between
+/// them, and the ELF virtual addresses DWARF uses are not simply
+/// `addr - start`:
+///
+/// ```text
+/// runtime addr ──(maps: start, offset)──▶ file offset ──(PT_LOAD)──▶ ELF vaddr
+/// ```
+///
+/// This covers PIE (vaddr ≈ offset), non-PIE (vaddr = runtime address) and
+/// linkers that place segments at a vaddr different from their file offset.
+#[derive(Debug, Clone, Default)]
+pub struct ModuleMap {
+    /// Path of the mapped file, as shown in /proc/pid/maps
+    pub path: String,
+    /// Executable mappings of this file, sorted by start address
+    segments: Vec<MemoryMapping>,
+    /// `PT_LOAD` program headers: (file offset, vaddr, file size)
+    load_segments: Vec<(u64, u64, u64)>,
+}
+
+impl ModuleMap {
+    /// Build the layout of `path` from the mappings that belong to it
+    ///
+    /// Only mappings whose path matches exactly are used. Without program
+    /// headers (see [`Self::load_program_headers`]) file offsets are assumed
+    /// to equal virtual addresses.
+    #[must_use]
+    pub fn from_mappings(path: &str, mappings: &[MemoryMapping]) -> Option<Self> {
+        let mut segments: Vec<MemoryMapping> =
+            mappings.iter().filter(|m| m.path == path).cloned().collect();
+        if segments.is_empty() {
+            return None;
+        }
+        segments.sort_unstable_by_key(|m| m.start);
+        Some(Self { path: path.to_string(), segments, load_segments: Vec::new() })
+    }
+
+    /// Group mappings into one `ModuleMap` per file
+    #[must_use]
+    pub fn group(mappings: &[MemoryMapping]) -> Vec<Self> {
+        let mut paths: Vec<&str> = mappings.iter().map(|m| m.path.as_str()).collect();
+        paths.sort_unstable();
+        paths.dedup();
+        paths.into_iter().filter_map(|path| Self::from_mappings(path, mappings)).collect()
+    }
+
+    /// Load the target process's executable
+    ///
+    /// Segments are matched against the file the process is actually running
+    /// (`/proc/pid/exe`); program headers are read from `binary_path`, which
+    /// may be a copy of that binary (e.g. one with debug info).
+    ///
+    /// # Errors
+    /// Returns an error if /proc/pid/maps or the binary cannot be read, or the
+    /// executable has no executable mappings
+    pub fn load(pid: i32, binary_path: &str) -> Result<Self> {
+        let mapped_path = fs::read_link(format!("/proc/{pid}/exe"))
+            .or_else(|_| fs::canonicalize(binary_path))
+            .map_or_else(
+                |_| binary_path.to_string(),
+                |p| p.to_string_lossy().trim_end_matches(" (deleted)").to_string(),
             );
-            Ok(MemoryRange { start, end })
+
+        let mappings = parse_executable_mappings(pid)?;
+        let mut module = Self::from_mappings(&mapped_path, &mappings)
+            .with_context(|| format!("Could not find executable mappings for {mapped_path}"))?;
+
+        let binary = fs::read(binary_path).context(format!("Failed to read {binary_path}"))?;
+        module.load_program_headers(&binary);
+
+        let range = module.range();
+        info!(
+            "Executable memory range: 0x{:x} - 0x{:x} ({} segments)",
+            range.start,
+            range.end,
+            module.segments.len()
+        );
+        Ok(module)
+    }
+
+    /// The same file at its location in a new mapping list (after `dlopen`
+    /// or re-exec), keeping the program headers already read
+    #[must_use]
+    pub fn relocated(&self, mappings: &[MemoryMapping]) -> Option<Self> {
+        let mut module = Self::from_mappings(&self.path, mappings)?;
+        module.load_segments.clone_from(&self.load_segments);
+        Some(module)
+    }
+
+    /// Read `PT_LOAD` program headers from the ELF file
+    ///
+    /// Unparseable files keep the offset == vaddr assumption.
+    pub fn load_program_headers(&mut self, binary: &[u8]) {
+        let Ok(obj) = object::File::parse(binary) else {
+            return;
+        };
+        self.load_segments = obj
+            .segments()
+            .map(|segment| {
+                let (offset, size) = segment.file_range();
+                (offset, segment.address(), size)
+            })
+            .collect();
+    }
+
+    /// Check if an address falls in one of this file's executable segments
+    #[must_use]
+    pub fn contains(&self, addr: u64) -> bool {
+        self.segments.iter().any(|m| m.contains(addr))
+    }
+
+    /// Translate a runtime address to the ELF virtual address DWARF and the
+    /// symbol tables use. `None` if the address is not in this file.
+    #[must_use]
+    pub fn to_elf_vaddr(&self, addr: u64) -> Option<u64> {
+        let file_offset = self.segments.iter().find(|m| m.contains(addr))?.file_offset(addr);
+        let vaddr = self
+            .load_segments
+            .iter()
+            .find(|(offset, _, size)| file_offset >= *offset && file_offset < offset + size)
+            .map_or(file_offset, |(offset, vaddr, _)| file_offset - offset + vaddr);
+        Some(vaddr)
+    }
+
+    /// Overall address range, from the first segment start to the last end
+    #[must_use]
+    pub fn range(&self) -> MemoryRange {
+        MemoryRange {
+            start: self.segments.first().map_or(0, |m| m.start),
+            end: self.segments.iter().map(|m| m.end).max().unwrap_or(0),
         }
-        _ => Err(anyhow::anyhow!("Could not find memory range for {binary_path}")),
     }
 }
 
@@ -99,16 +246,102 @@ mod tests {
     }
 
     #[test]
-    fn test_parse_memory_maps_self() {
-        // Test parsing our own process's memory maps
-        let pid = i32::try_from(std::process::id()).expect("PID exceeds i32::MAX");
+    fn test_parse_executable_mappings() {
+        let maps = "\
+55d0c0000000-55d0c0010000 r--p 00000000 08:01 1234 /opt/app/server
+55d0c0010000-55d0c0200000 r-xp 00010000 08:01 1234 /opt/app/server
+7f1a2c028000-7f1a2c1bd000 r-xp 00028000 08:01 5678 /usr/lib/x86_64-linux-gnu/libc.so.6
+7f1a2c400000-7f1a2c600000 rwxp 00000000 00:00 0
+7ffd5a3f2000-7ffd5a3f4000 r-xp 00000000 00:00 0 [vdso]
+";
+        let mappings = parse_executable_mappings_from(maps);
+        assert_eq!(mappings.len(), 2);
+        assert_eq!(mappings[0].path, "/opt/app/server");
+        assert_eq!(mappings[0].offset, 0x10000);
+
+        let libc = &mappings[1];
+        assert_eq!(libc.path, "/usr/lib/x86_64-linux-gnu/libc.so.6");
+        assert!(libc.contains(0x7f1a_2c03_0000));
+        assert_eq!(libc.file_offset(0x7f1a_2c03_0000), 0x30000);
+    }
+
+    #[test]
+    fn test_parse_executable_mappings_path_with_spaces() {
+        let maps = "\
+55d0c0010000-55d0c0200000 r-xp 00010000 08:01 1234                       /opt/my app/server
+";
+        let mappings = parse_executable_mappings_from(maps);
+        assert_eq!(mappings.len(), 1);
+        assert_eq!(mappings[0].path, "/opt/my app/server");
+    }
 
-        // Try to find the current executable
+    #[test]
+    fn test_parse_executable_mappings_deleted() {
+        let maps = "\
+55d0c0010000-55d0c0200000 r-xp 00010000 08:01 1234                       /opt/app/server (deleted)
+";
+        let mappings = parse_executable_mappings_from(maps);
+        assert_eq!(mappings.len(), 1);
+        assert_eq!(mappings[0].path, "/opt/app/server");
+    }
+
+    fn mapping(start: u64, end: u64, offset: u64, path: &str) -> MemoryMapping {
+        MemoryMapping { start, end, offset, path: path.to_string() }
+    }
+
+    #[test]
+    fn test_module_map_matches_exact_path() {
+        let mappings =
+            [mapping(0x1000, 0x2000, 0, "/app/foo"), mapping(0x5000, 0x6000, 0, "/app/foobar")];
+        let module = ModuleMap::from_mappings("/app/foo", &mappings).unwrap();
+        assert!(module.contains(0x1800));
+        assert!(!module.contains(0x5800));
+        assert_eq!(ModuleMap::group(&mappings).len(), 2);
+    }
+
+    #[test]
+    fn test_module_map_translates_through_program_headers() {
+        // Two executable segments with a gap; the second is linked at a vaddr
+        // 0x1000 above its file offset (as lld does)
+        let mut module = ModuleMap::from_mappings(
+            "/app/server",
+            &[
+                mapping(0x5555_0000_1000, 0x5555_0000_3000, 0x1000, "/app/server"),
+                mapping(0x5555_0001_0000, 0x5555_0001_2000, 0x8000, "/app/server"),
+            ],
+        )
+        .unwrap();
+        module.load_segments = vec![(0x1000, 0x1000, 0x2000), (0x8000, 0x9000, 0x2000)];
+
+        assert_eq!(module.to_elf_vaddr(0x5555_0000_1234), Some(0x1234));
+        assert_eq!(module.to_elf_vaddr(0x5555_0001_0010), Some(0x9010));
+        assert_eq!(module.to_elf_vaddr(0x5555_0000_8000), None); // in the gap
+        assert_eq!(module.range().start, 0x5555_0000_1000);
+        assert_eq!(module.range().end, 0x5555_0001_2000);
+    }
+
+    #[test]
+    fn test_module_map_non_pie() {
+        // Non-PIE: loaded at its link address, so vaddr == runtime address
+        let mut module = ModuleMap::from_mappings(
+            "/app/static",
+            &[mapping(0x40_1000, 0x48_0000, 0x1000, "/app/static")],
+        )
+        .unwrap();
+        module.load_segments = vec![(0x1000, 0x40_1000, 0x7_f000)];
+        assert_eq!(module.to_elf_vaddr(0x40_2345), Some(0x40_2345));
+    }
+
+    #[test]
+    fn test_module_map_self() {
+        let pid = i32::try_from(std::process::id()).expect("PID exceeds i32::MAX");
         let exe = std::env::current_exe().expect("Failed to get current exe");
         let exe_path = exe.to_str().expect("Failed to convert exe path to string");
 
-        // This might fail in some test environments, so we allow it
-        let _result = parse_memory_maps(pid, exe_path);
-        // We don't assert success because it depends on the test environment
+        let module = ModuleMap::load(pid, exe_path).expect("own executable is mapped");
+        let addr = test_module_map_self as *const () as u64;
+        assert!(module.contains(addr));
+        // Equal for a non-PIE test binary, lower when loaded above its link address
+        assert!(module.to_elf_vaddr(addr).is_some_and(|vaddr| vaddr <= addr));
     }
 }
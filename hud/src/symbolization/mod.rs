@@ -5,21 +5,37 @@
 //!
 //! ## Key Components
 //!
-//! - **`symbolizer`** - DWARF-based symbol resolution with caching
-//! - **`memory_maps`** - Parse `/proc/pid/maps` for PIE/ASLR base address adjustment
+//! - **`symbolizer`** - DWARF-based symbol resolution with caching (ELF symtab fallback)
+//! - **`debuginfo`** - Find separate debug files (build ID, `.gnu_debuglink`, `--debug-dir`)
+//! - **`symbolizer_set`** - Lazily built per-library symbolizers for shared objects
+//! - **`memory_maps`** - Parse `/proc/pid/maps`; `ModuleMap` translates runtime addresses to ELF vaddrs
+//! - **`kallsyms`** - Kernel frame resolution via `/proc/kallsyms`
+//! - **`elf_symbols`** - Find functions by demangled path (for uprobe attachment)
 //!
 //! ## Address Translation
 //!
 //! 1. Get runtime address from eBPF stack trace (e.g., `0x55f3a2b4c780`)
-//! 2. Parse `/proc/<pid>/maps` to find binary's memory range
-//! 3. Calculate file offset: `runtime_addr - base_address`
-//! 4. Look up in DWARF debug info to get function/file/line
-//! 5. Demangle Rust symbol names
+//! 2. Parse `/proc/<pid>/maps` to find the segment containing it
+//! 3. Calculate file offset: `runtime_addr - segment_start + segment_offset`
+//! 4. Map the file offset to an ELF virtual address via the `PT_LOAD` program headers
+//! 5. Look up in DWARF debug info to get function/file/line
+//! 6. Demangle Rust symbol names
+//!
+//! Addresses in shared libraries go through the same steps against the
+//! library's own mapping and file (see `symbolizer_set`).
 //!
 //! See [Architecture docs](../../docs/ARCHITECTURE.md) for details on DWARF, PIE, and ASLR.
 
+pub mod debuginfo;
+pub mod elf_symbols;
+pub mod kallsyms;
 pub mod memory_maps;
 pub mod symbolizer;
+pub mod symbolizer_set;
 
-pub use memory_maps::{parse_memory_maps, MemoryRange};
+pub use debuginfo::{find_debug_file, has_dwarf};
+pub use elf_symbols::{find_function, FunctionSymbol};
+pub use kallsyms::KernelSymbols;
+pub use memory_maps::{parse_executable_mappings, MemoryMapping, MemoryRange, ModuleMap};
 pub use symbolizer::Symbolizer;
+pub use symbolizer_set::SymbolizerSet;
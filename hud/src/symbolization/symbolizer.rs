@@ -4,20 +4,32 @@
 use addr2line::Context;
 use anyhow::{Context as _, Result};
 use gimli::{EndianRcSlice, RunTimeEndian};
-use object::{Object, ObjectSection};
+use log::info;
+use object::{Object, ObjectSection, ObjectSymbol, SymbolKind};
 use rustc_demangle::demangle;
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 
+use super::debuginfo::find_debug_file;
+
 /// Symbolizer for resolving instruction pointers to source locations
 ///
 /// Includes a cache to avoid re-resolving the same addresses repeatedly,
 /// which significantly improves performance when symbolizing stack traces.
+///
+/// DWARF is preferred (function, file, line, inlined frames). When it has
+/// nothing for an address, e.g. system libraries shipped without debug info,
+/// the ELF `.symtab`/`.dynsym` tables still give a function name.
+///
+/// Stripped binaries are symbolized from their separate debug file when one
+/// can be found (see [`debuginfo`](super::debuginfo)).
 pub struct Symbolizer {
     ctx: Context<EndianRcSlice<RunTimeEndian>>,
+    /// Function symbols sorted by address: (start, size, demangled name)
+    elf_symbols: Vec<(u64, u64, String)>,
     /// Cache of resolved frames by address (Rc avoids cloning on cache hits)
     cache: RefCell<HashMap<u64, Rc<ResolvedFrame>>>,
 }
@@ -28,17 +40,35 @@ impl Symbolizer {
     /// # Errors
     /// Returns an error if the binary file cannot be read or parsed, or if DWARF debug info is missing
     pub fn new<P: AsRef<Path>>(binary_path: P) -> Result<Self> {
-        let binary_data = fs::read(binary_path.as_ref()).context("Failed to read binary file")?;
+        Self::with_debug_dirs(binary_path, &[])
+    }
+
+    /// Create a symbolizer that also searches `debug_dirs` for a separate
+    /// debug file (before the system locations)
+    ///
+    /// # Errors
+    /// Returns an error if the binary file cannot be read or parsed, or if DWARF debug info is missing
+    pub fn with_debug_dirs<P: AsRef<Path>>(binary_path: P, debug_dirs: &[PathBuf]) -> Result<Self> {
+        let binary_path = binary_path.as_ref();
+        let binary_data = fs::read(binary_path).context("Failed to read binary file")?;
 
         let obj_file = object::File::parse(&*binary_data).context("Failed to parse object file")?;
 
+        // Stripped binary: DWARF (and the full .symtab) live in the debug file
+        let debug_data = find_debug_file(binary_path, &obj_file, debug_dirs).and_then(|path| {
+            info!("Using debug info from {} for {}", path.display(), binary_path.display());
+            fs::read(path).ok()
+        });
+        let debug_file = debug_data.as_deref().and_then(|data| object::File::parse(data).ok());
+        let dwarf_file = debug_file.as_ref().unwrap_or(&obj_file);
+
         // Load DWARF debug info
         let endian =
-            if obj_file.is_little_endian() { RunTimeEndian::Little } else { RunTimeEndian::Big };
+            if dwarf_file.is_little_endian() { RunTimeEndian::Little } else { RunTimeEndian::Big };
 
         let load_section =
             |id: gimli::SectionId| -> Result<EndianRcSlice<RunTimeEndian>, gimli::Error> {
-                let data = obj_file
+                let data = dwarf_file
                     .section_by_name(id.name())
                     .and_then(|section| section.uncompressed_data().ok())
                     .unwrap_or(std::borrow::Cow::Borrowed(&[][..]));
@@ -48,7 +78,19 @@ impl Symbolizer {
         let dwarf = gimli::Dwarf::load(&load_section)?;
         let ctx = Context::from_dwarf(dwarf).context("Failed to load DWARF debug information")?;
 
-        Ok(Self { ctx, cache: RefCell::new(HashMap::new()) })
+        Ok(Self {
+            ctx,
+            elf_symbols: load_elf_symbols(std::iter::once(&obj_file).chain(debug_file.as_ref())),
+            cache: RefCell::new(HashMap::new()),
+        })
+    }
+
+    /// Look up the function containing `addr` in the ELF symbol tables.
+    fn resolve_elf_symbol(&self, addr: u64) -> Option<&str> {
+        let idx = self.elf_symbols.partition_point(|(start, _, _)| *start <= addr);
+        let (start, size, name) = self.elf_symbols.get(idx.checked_sub(1)?)?;
+        // Size 0 means unknown extent (hand-written assembly): trust the nearest symbol
+        (*size == 0 || addr < start + size).then_some(name.as_str())
     }
 
     /// Resolve an instruction pointer to source location information
@@ -81,6 +123,15 @@ impl Symbolizer {
             }
         }
 
+        // No DWARF for this address: fall back to the ELF symbol tables
+        let dwarf_function_known = result.iter().any(|f| f.function != "<unknown>");
+        if !dwarf_function_known {
+            if let Some(name) = self.resolve_elf_symbol(addr) {
+                let location = result.into_iter().next().and_then(|f| f.location);
+                result = vec![InlinedFrame { function: name.to_string(), location }];
+            }
+        }
+
         let resolved = Rc::new(ResolvedFrame {
             addr,
             frames: if result.is_empty() {
@@ -103,6 +154,24 @@ impl Symbolizer {
     }
 }
 
+/// Collect function symbols from `.symtab` and `.dynsym`, sorted by address.
+fn load_elf_symbols<'a>(
+    obj_files: impl Iterator<Item = &'a object::File<'a>>,
+) -> Vec<(u64, u64, String)> {
+    let mut symbols: Vec<(u64, u64, String)> = obj_files
+        .flat_map(|obj_file| obj_file.symbols().chain(obj_file.dynamic_symbols()))
+        .filter(|sym| sym.kind() == SymbolKind::Text && sym.is_definition() && sym.address() != 0)
+        .filter_map(|sym| {
+            let name = sym.name().ok()?;
+            Some((sym.address(), sym.size(), format!("{:#}", demangle(name))))
+        })
+        .collect();
+    symbols.sort_unstable_by_key(|(addr, _, _)| *addr);
+    // .symtab and .dynsym overlap; keep one entry per address
+    symbols.dedup_by_key(|(addr, _, _)| *addr);
+    symbols
+}
+
 /// A resolved stack frame (may contain multiple inlined frames)
 #[derive(Debug, Clone)]
 pub struct ResolvedFrame {
@@ -0,0 +1,171 @@
+//! Per-library symbolizers for frames outside the main executable
+//!
+//! Blocking often bottoms out in a shared library: `libc` for `read`/`futex`,
+//! `libssl` for handshakes, `libz` for compression. Each mapped library gets
+//! its own [`Symbolizer`], built the first time one of its addresses shows up
+//! (most profiles only ever touch a handful of the dozens of mapped DSOs).
+//!
+//! Libraries without DWARF still resolve through their ELF `.symtab` or
+//! `.dynsym`; a library that cannot be opened at all is remembered as such
+//! so it is not retried on every frame.
+
+use log::debug;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use super::memory_maps::{MemoryMapping, ModuleMap};
+use super::symbolizer::{ResolvedFrame, Symbolizer};
+
+/// Lazily built symbolizers for the shared libraries mapped into the target
+#[derive(Default)]
+pub struct SymbolizerSet {
+    /// Loaded libraries outside the main binary
+    modules: Vec<ModuleMap>,
+    /// Library path → symbolizer (`None` if the file could not be loaded)
+    symbolizers: HashMap<String, Option<Symbolizer>>,
+    /// Extra directories to search for separate debug files
+    debug_dirs: Vec<PathBuf>,
+}
+
+impl SymbolizerSet {
+    /// Create a set covering `mappings`, excluding the main executable
+    /// (which has its own symbolizer).
+    #[must_use]
+    pub fn new(mappings: Vec<MemoryMapping>, executable: &str) -> Self {
+        let mappings: Vec<MemoryMapping> =
+            mappings.into_iter().filter(|m| m.path != executable).collect();
+        Self {
+            modules: ModuleMap::group(&mappings),
+            symbolizers: HashMap::new(),
+            debug_dirs: Vec::new(),
+        }
+    }
+
+    /// Replace the mapping list after the address space changed
+    ///
+    /// Symbolizers of libraries that are still mapped are kept; unmapped
+    /// libraries are dropped (a later `dlopen` of the same path reloads it).
+    pub fn set_mappings(&mut self, mappings: Vec<MemoryMapping>, executable: &str) {
+        let mappings: Vec<MemoryMapping> =
+            mappings.into_iter().filter(|m| m.path != executable).collect();
+        let modules: Vec<ModuleMap> = ModuleMap::group(&mappings)
+            .into_iter()
+            .map(|module| {
+                self.modules
+                    .iter()
+                    .find(|old| old.path == module.path)
+                    .and_then(|old| old.relocated(&mappings))
+                    .unwrap_or(module)
+            })
+            .collect();
+        self.symbolizers.retain(|path, _| modules.iter().any(|m| &m.path == path));
+        self.modules = modules;
+    }
+
+    /// Also search `debug_dirs` for the libraries' separate debug files
+    #[must_use]
+    pub fn with_debug_dirs(mut self, debug_dirs: Vec<PathBuf>) -> Self {
+        self.debug_dirs = debug_dirs;
+        self
+    }
+
+    /// Find the library containing `addr`
+    #[must_use]
+    pub fn find_module(&self, addr: u64) -> Option<&ModuleMap> {
+        self.modules.iter().find(|m| m.contains(addr))
+    }
+
+    /// Resolve a runtime address against the library it falls in.
+    ///
+    /// Returns the library and the resolved frame, or `None` if the address
+    /// is in no known library or the library could not be loaded.
+    pub fn resolve(&mut self, addr: u64) -> Option<(&ModuleMap, Rc<ResolvedFrame>)> {
+        let module = self.modules.iter_mut().find(|m| m.contains(addr))?;
+        let symbolizer = self
+            .symbolizers
+            .entry(module.path.clone())
+            .or_insert_with(|| {
+                // First use: program headers for address translation, then symbols
+                if let Ok(data) = fs::read(&module.path) {
+                    module.load_program_headers(&data);
+                }
+                match Symbolizer::with_debug_dirs(&module.path, &self.debug_dirs) {
+                    Ok(symbolizer) => Some(symbolizer),
+                    Err(e) => {
+                        debug!("No symbols for {}: {e:#}", module.path);
+                        None
+                    }
+                }
+            })
+            .as_ref()?;
+        let vaddr = module.to_elf_vaddr(addr)?;
+        Some((module, symbolizer.resolve(vaddr)))
+    }
+
+    /// Number of libraries loaded so far (for diagnostics)
+    #[must_use]
+    pub fn loaded_count(&self) -> usize {
+        self.symbolizers.values().filter(|s| s.is_some()).count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::symbolization::parse_executable_mappings;
+
+    #[test]
+    fn excludes_main_executable() {
+        let mapping = |path: &str, start| MemoryMapping {
+            start,
+            end: start + 0x1000,
+            offset: 0,
+            path: path.to_string(),
+        };
+        let set = SymbolizerSet::new(
+            vec![mapping("/opt/app/server", 0x1000), mapping("/usr/lib/libz.so.1", 0x8000)],
+            "/opt/app/server",
+        );
+        assert!(set.find_module(0x1800).is_none());
+        assert_eq!(set.find_module(0x8800).map(|m| m.path.as_str()), Some("/usr/lib/libz.so.1"));
+    }
+
+    #[test]
+    fn set_mappings_follows_dlopen() {
+        let mapping = |path: &str, start| MemoryMapping {
+            start,
+            end: start + 0x1000,
+            offset: 0,
+            path: path.to_string(),
+        };
+        let mut set = SymbolizerSet::new(vec![mapping("/usr/lib/libz.so.1", 0x8000)], "/app");
+        assert!(set.find_module(0x20800).is_none());
+
+        set.set_mappings(
+            vec![mapping("/usr/lib/libz.so.1", 0x8000), mapping("/opt/plugins/libext.so", 0x20000)],
+            "/app",
+        );
+        assert_eq!(
+            set.find_module(0x20800).map(|m| m.path.as_str()),
+            Some("/opt/plugins/libext.so")
+        );
+    }
+
+    #[test]
+    fn resolves_libc_function_by_name() {
+        // Our own process maps libc; `getpid` should resolve via .dynsym
+        let pid = i32::try_from(std::process::id()).unwrap();
+        let exe = std::env::current_exe().unwrap();
+        let mut set =
+            SymbolizerSet::new(parse_executable_mappings(pid).unwrap(), exe.to_str().unwrap());
+
+        let addr = libc::getpid as *const () as u64;
+        // Statically linked test binaries have no libc mapping; nothing to check
+        let Some((module, resolved)) = set.resolve(addr) else { return };
+        assert!(module.path.contains("libc"));
+        assert!(resolved.frames[0].function.contains("getpid"));
+        assert_eq!(set.loaded_count(), 1);
+    }
+}
@@ -0,0 +1,86 @@
+//! Fixtures shared by the unit tests and, through the `testing` feature, the
+//! integration tests in `tests/`
+
+use hud_common::{TaskEvent, DETECTION_SCHEDULER};
+use std::sync::Arc;
+
+use crate::classification::FrameOrigin;
+use crate::trace_data::{StackFrame, TraceEvent};
+
+/// A worker event with the fields the exporters read; everything else zeroed.
+///
+/// PID 1000, TID 1001, worker 0 on CPU 2, no task or blocker. Override
+/// fields with struct update syntax.
+#[must_use]
+pub fn worker_event(event_type: u32, timestamp_ns: u64, duration_ns: u64) -> TaskEvent {
+    TaskEvent {
+        pid: 1000,
+        tid: 1001,
+        timestamp_ns,
+        event_type,
+        stack_id: -1,
+        duration_ns,
+        worker_id: 0,
+        cpu_id: 2,
+        thread_state: 0,
+        task_id: 0,
+        category: 0,
+        detection_method: DETECTION_SCHEDULER,
+        is_tokio_worker: 1,
+        _padding: [0; 5],
+        blocker_tid: 0,
+        blocker_pid: 0,
+        blocker_stack_id: -1,
+        blocker_kernel_stack_id: -1,
+        blocker_comm: [0; 16],
+    }
+}
+
+/// A frame without a source location
+#[must_use]
+pub fn frame(function: &str, origin: FrameOrigin) -> StackFrame {
+    StackFrame {
+        function: function.to_string(),
+        file: None,
+        line: None,
+        origin,
+        is_user_code: origin.is_user_code(),
+    }
+}
+
+/// A frame at `file:line`
+#[must_use]
+pub fn frame_at(function: &str, file: &str, line: u32, origin: FrameOrigin) -> StackFrame {
+    StackFrame { file: Some(file.to_string()), line: Some(line), ..frame(function, origin) }
+}
+
+/// A perf sample named `name` on `worker_id` (TID `100 + worker_id`), with
+/// no stack, source location or task. Override fields with struct update
+/// syntax.
+#[must_use]
+pub fn sample(name: &str, worker_id: u32) -> TraceEvent {
+    TraceEvent {
+        name: name.to_string(),
+        worker_id,
+        tid: 100 + worker_id,
+        timestamp: 1.0,
+        cpu: 0,
+        detection_method: Some(4),
+        file: None,
+        line: None,
+        call_stack: None,
+        duration_ns: None,
+        task_id: None,
+        blocker: None,
+    }
+}
+
+/// A perf sample whose stack is `frames`, outermost first, all user code
+#[must_use]
+pub fn stack_sample(frames: &[&str]) -> TraceEvent {
+    let stack = frames.iter().rev().map(|name| frame(name, FrameOrigin::UserCode)).collect();
+    TraceEvent {
+        call_stack: Some(Arc::new(stack)),
+        ..sample(frames.last().map_or("execution", |name| name), 0)
+    }
+}
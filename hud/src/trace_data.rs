@@ -3,10 +3,12 @@
 //! This module contains the core data structures for live profiling mode:
 //!
 //! - [`TraceEvent`] - A single profiling sample with optional call stack
+//! - [`Blocker`] - The thread that held a worker's CPU during a latency event
 //! - [`LiveData`] - Accumulates events from eBPF
 //! - [`TraceData`] - Immutable snapshot for rendering (cheap Arc clones)
 //! - [`StackFrame`] - A single frame in a resolved call stack
 //! - [`StackCache`] - Deduplicates resolved stacks by eBPF `stack_id`
+//! - [`Bookmark`] - A labelled moment set in the live view, kept in exports
 //!
 //! # Memory Model
 //!
@@ -60,6 +62,16 @@ pub struct StackFrame {
     pub is_user_code: bool,
 }
 
+/// The frame a sample is attributed to: the first user-code frame, or the
+/// innermost frame if the stack has no user code.
+///
+/// Event names in the TUI, reports and exports all come from this frame, so
+/// their per-function numbers agree.
+#[must_use]
+pub fn attribution_frame(stack: &[StackFrame]) -> Option<&StackFrame> {
+    stack.iter().find(|f| f.is_user_code).or_else(|| stack.first())
+}
+
 /// Cache of resolved stack traces, keyed by eBPF `stack_id`.
 ///
 /// # Why Cache?
@@ -132,6 +144,11 @@ impl StackCache {
         }
     }
 
+    /// Drop all cached stacks (e.g., after the address space changed).
+    pub fn clear(&mut self) {
+        self.stacks.clear();
+    }
+
     /// Number of unique stacks currently cached.
     #[must_use]
     pub fn len(&self) -> usize {
@@ -196,6 +213,64 @@ pub struct TraceEvent {
     /// Full resolved call stack, shared via `Arc` for memory efficiency.
     /// `None` if stack capture failed (e.g., kernel stack, recursion limit).
     pub call_stack: Option<Arc<Vec<StackFrame>>>,
+
+    /// Measured off-CPU latency in nanoseconds.
+    ///
+    /// `Some` only for scheduler-detected events (the worker sat in the run
+    /// queue this long before getting the CPU back). Perf samples are
+    /// point-in-time observations and leave this `None`.
+    pub duration_ns: Option<u64>,
+
+    /// Tokio task running on the worker when the event was captured.
+    ///
+    /// `None` if the task ID uprobe is not attached or no task was running
+    /// (e.g., the worker was in the scheduler itself).
+    pub task_id: Option<u64>,
+
+    /// Thread that held the CPU while this worker waited (scheduler events only).
+    ///
+    /// When set, `name`, `file`, `line` and `call_stack` describe the blocker's
+    /// code rather than the victim's, since the victim has no stack to show.
+    pub blocker: Option<Blocker>,
+}
+
+/// The culprit behind a scheduler latency event.
+///
+/// The worker that waited is the *victim*; the blocker is the thread that was
+/// running on its CPU during the wait.
+#[derive(Debug, Clone)]
+pub struct Blocker {
+    /// Linux thread ID of the blocking thread.
+    pub tid: u32,
+
+    /// On-CPU samples of the blocker inside the wait window.
+    /// 0 when eBPF captured the blocker directly at `sched_switch`.
+    pub samples: u32,
+
+    /// Thread name of the blocker (from `sched_switch`, if captured).
+    pub comm: Option<String>,
+
+    /// Kernel stack of the blocker at the moment it gave up the CPU.
+    pub kernel_stack: Option<Arc<Vec<StackFrame>>>,
+}
+
+impl Blocker {
+    /// Short label for display: the thread name if known, else the TID.
+    #[must_use]
+    pub fn label(&self) -> String {
+        self.comm.clone().unwrap_or_else(|| format!("tid {}", self.tid))
+    }
+}
+
+impl TraceEvent {
+    /// True for off-CPU latency events reported by the `sched_switch` hook.
+    ///
+    /// These are intervals, not 99 Hz samples, so sample-based aggregations
+    /// (hotspot percentages, worker load) must skip them.
+    #[must_use]
+    pub fn is_scheduler_event(&self) -> bool {
+        self.duration_ns.is_some()
+    }
 }
 
 // =============================================================================
@@ -225,6 +300,52 @@ pub struct TraceData {
     pub duration: f64,
 }
 
+impl TraceData {
+    /// Snapshot of the events with timestamps in `[start, end)`.
+    ///
+    /// Like the rolling window, relies on events arriving in time order.
+    #[must_use]
+    pub fn between(&self, start: f64, end: f64) -> TraceData {
+        let from = self.events.partition_point(|e| e.timestamp < start);
+        let to = self.events.partition_point(|e| e.timestamp < end).max(from);
+        TraceData {
+            events: Arc::new(self.events[from..to].to_vec()),
+            workers: Arc::clone(&self.workers),
+            duration: end - start,
+        }
+    }
+
+    /// CPU samples, counted as `HotspotStats::total_samples` counts them
+    #[must_use]
+    pub fn sample_count(&self) -> u64 {
+        self.events.iter().filter(|e| !e.is_scheduler_event() && e.name != "execution").count()
+            as u64
+    }
+}
+
+// =============================================================================
+// BOOKMARKS
+// =============================================================================
+
+/// A moment marked in the live view, such as "deployed here"
+///
+/// Sent from the TUI to the profiler, which writes it to the export.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bookmark {
+    /// Seconds on the event clock, as [`TraceEvent::timestamp`]
+    pub timestamp: f64,
+    pub label: String,
+}
+
+impl Bookmark {
+    /// Timestamp in nanoseconds, as `TaskEvent::timestamp_ns`
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn timestamp_ns(&self) -> u64 {
+        (self.timestamp * 1_000_000_000.0).round() as u64
+    }
+}
+
 // =============================================================================
 // LIVE DATA (MUTABLE ACCUMULATOR)
 // =============================================================================
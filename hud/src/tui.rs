@@ -7,8 +7,19 @@
 //! - **Analysis** - Hotspot list + worker stats (default)
 //! - **`DrillDown`** - Detailed view of selected function (F-35 targeting UI)
 //! - **Search** - Filter hotspots by name
+//! - **`FlameGraph`** - Icicle of every sampled call stack (`F`)
+//! - **Butterfly** - Callers and callees of one function (`B`)
+//! - **Timeline** - Heatmap cursor for picking a time range (`L`)
+//! - **Bookmark** - Text input labelling a moment for the export (`M`)
 //! - **Help** - Keyboard shortcuts and concepts
 //!
+//! ## Pause and Time Range
+//!
+//! `P` freezes every panel on the data shown at that moment; events keep
+//! arriving in the background and show up on resume. A range picked on the
+//! timeline limits every panel to `[t0, t1]`, and `[`/`]` slide it through
+//! history.
+//!
 //! ## Entry Point
 //!
 //! - `run_live()` - Real-time profiling with eBPF event channel
@@ -16,9 +27,13 @@
 //! ## Sub-Modules
 //!
 //! - `hotspot` - Hotspot list and sorting
-//! - `timeline` - Per-worker execution timeline
+//! - `activity` - Per-worker sample and wait totals
+//! - `timeline` - Per-worker blocking heatmap over time, with range selection
 //! - `workers` - Worker statistics panel
 //! - `status` - Summary status bar
+//! - `tasks` - Per-task blocking breakdown (toggled with `T`)
+//! - `flamegraph` - Merged call tree of all sampled stacks
+//! - `butterfly` - Callers and callees of one function
 //! - `theme` - Color scheme
 
 // TUI rendering intentionally uses precision-losing casts and long functions for clarity
@@ -32,7 +47,7 @@
 )]
 
 use anyhow::Result;
-use crossbeam_channel::Receiver;
+use crossbeam_channel::{Receiver, Sender};
 use crossterm::{
     event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind},
     execute,
@@ -49,19 +64,29 @@ use ratatui::{
 use std::io;
 use std::time::Duration;
 
+mod activity;
+mod butterfly;
+mod flamegraph;
 pub mod hotspot; // Public for testing
 mod layout;
 mod status;
+mod tasks;
 mod theme;
 mod timeline;
 mod workers;
 
+use activity::ActivityView;
+use butterfly::ButterflyView;
+use flamegraph::{CallTree, FlameGraphView};
 use hotspot::HotspotView;
 use status::StatusPanel;
+use tasks::TasksPanel;
 use theme::{CAUTION_AMBER, CRITICAL_RED, HUD_GREEN, INFO_DIM};
-use timeline::TimelineView;
+use timeline::{TimelineState, TimelineView};
 use workers::WorkersPanel;
 
+use crate::profiling::WorkerChanges;
+use crate::trace_data::Bookmark;
 pub use crate::trace_data::{LiveData, TraceData, TraceEvent};
 
 // =============================================================================
@@ -122,6 +147,28 @@ pub(crate) fn format_duration_human(secs: f64) -> String {
     }
 }
 
+/// Cut `text` to at most `width` characters, ending in "..." when cut
+pub(crate) fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    if width <= 3 {
+        return text.chars().take(width).collect();
+    }
+    let mut out: String = text.chars().take(width - 3).collect();
+    out.push_str("...");
+    out
+}
+
+/// Format a scheduler latency in nanoseconds as a compact string (e.g., "37ms", "1.2s")
+pub(crate) fn format_latency(ns: u64) -> String {
+    match ns {
+        n if n >= 1_000_000_000 => format!("{:.1}s", n as f64 / 1_000_000_000.0),
+        n if n >= 1_000_000 => format!("{}ms", n / 1_000_000),
+        n => format!("{}us", n / 1_000),
+    }
+}
+
 // =============================================================================
 // VIEW MODES
 // =============================================================================
@@ -139,6 +186,16 @@ enum ViewMode {
     Search,
     /// Help overlay with keyboard shortcuts
     Help,
+    /// Flame graph of all sampled call stacks, in place of the panels
+    FlameGraph,
+    /// Text input for highlighting frames in the flame graph
+    FlameSearch,
+    /// Callers and callees of one function, in place of the panels
+    Butterfly,
+    /// Timeline in place of the activity panel, with a range cursor
+    Timeline,
+    /// Text input for a bookmark label
+    Bookmark,
 }
 
 // =============================================================================
@@ -185,7 +242,7 @@ fn render_help_overlay(f: &mut ratatui::Frame, area: Rect) {
 
     // Responsive sizing: expand on small terminals, clamp to available space
     let width_pct = if area.width < 80 { 95 } else { 80 };
-    let height = 34_u16.min(area.height.saturating_sub(2));
+    let height = 36_u16.min(area.height.saturating_sub(2));
     let popup_area = centered_popup(area, width_pct, height);
 
     let help_text = vec![
@@ -216,7 +273,14 @@ fn render_help_overlay(f: &mut ratatui::Frame, area: Rect) {
         ]),
         Line::from(vec![
             Span::styled("  Timeline  ", STYLE_LABEL),
-            Span::styled("When blocking happened. Spikes show bursts of blocking.", STYLE_DIM),
+            Span::styled(
+                "When blocking happened (L). Select a burst to see its hotspots.",
+                STYLE_DIM,
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled("  Tasks     ", STYLE_LABEL),
+            Span::styled("One task on top = runaway task. Spread out = shared code.", STYLE_DIM),
         ]),
         Line::from(""),
         // Debug info
@@ -253,6 +317,38 @@ fn render_help_overlay(f: &mut ratatui::Frame, area: Rect) {
             Span::styled("Y", STYLE_KEY),
             Span::styled(" Yank (in drilldown)", STYLE_DIM),
         ]),
+        Line::from(vec![
+            Span::styled("  T", STYLE_KEY),
+            Span::styled(" Toggle tasks panel (blocking samples per Tokio task)", STYLE_DIM),
+        ]),
+        Line::from(vec![
+            Span::styled("  L", STYLE_KEY),
+            Span::styled(" Timeline (←→ cursor, Space marks a range for all panels)", STYLE_DIM),
+        ]),
+        Line::from(vec![
+            Span::styled("  [ ]", STYLE_KEY),
+            Span::styled(" Slide the range back/forward   ", STYLE_DIM),
+            Span::styled("Esc", STYLE_KEY),
+            Span::styled(" Back to all time", STYLE_DIM),
+        ]),
+        Line::from(vec![
+            Span::styled("  P", STYLE_KEY),
+            Span::styled(" Pause (events keep buffering)   ", STYLE_DIM),
+            Span::styled("M", STYLE_KEY),
+            Span::styled(" Bookmark a moment for the export", STYLE_DIM),
+        ]),
+        Line::from(vec![
+            Span::styled("  A", STYLE_KEY),
+            Span::styled(" Rank by: first user frame → leaf frame → inclusive (+self)", STYLE_DIM),
+        ]),
+        Line::from(vec![
+            Span::styled("  F", STYLE_KEY),
+            Span::styled(" Flame graph (↑↓←→ move, Enter zoom, Backspace zoom out)", STYLE_DIM),
+        ]),
+        Line::from(vec![
+            Span::styled("  B", STYLE_KEY),
+            Span::styled(" Callers/callees of the selected function (Enter re-centers)", STYLE_DIM),
+        ]),
         Line::from(""),
         Line::from(Span::styled("  Press any key to close", STYLE_DIM)),
     ];
@@ -418,10 +514,26 @@ fn render_drilldown_overlay(
             Span::styled("HIT  ", STYLE_DIM), // Sample hit count
             Span::styled(format!("{} samples", hotspot.count), STYLE_DIM),
         ]),
-        Line::from(Span::styled("  └─", Style::new().fg(sev_color))),
-        Line::from(""),
     ];
 
+    // Scheduler latency this function caused while holding a worker's CPU
+    if hotspot.sched_waits > 0 {
+        lines.push(Line::from(vec![
+            Span::styled("  │ ", Style::new().fg(sev_color)),
+            Span::styled("WAIT ", STYLE_DIM),
+            Span::styled(
+                format!(
+                    "{} stalls · {}",
+                    hotspot.sched_waits,
+                    format_latency(hotspot.sched_wait_ns)
+                ),
+                Style::new().fg(CAUTION_AMBER),
+            ),
+        ]));
+    }
+    lines.push(Line::from(Span::styled("  └─", Style::new().fg(sev_color))));
+    lines.push(Line::from(""));
+
     // Call trace section - inverted to show: your_code → library → blocking_fn
     if let Some(call_stack) = hotspot.call_stacks.first() {
         lines.push(Line::from(Span::styled("  CALL TRACE", STYLE_DIM)));
@@ -524,7 +636,7 @@ fn render_drilldown_overlay(
         worker_list.sort_unstable_by(|a, b| b.1.cmp(a.1));
 
         for (&worker_id, &count) in worker_list.iter().take(4) {
-            let pct = (count as f64 / hotspot.count as f64) * 100.0;
+            let pct = (count as f64 / hotspot.count.max(1) as f64) * 100.0;
             let filled = ((pct / 100.0) * 12.0) as usize;
             let bar = format!("{}{}", "▓".repeat(filled), "░".repeat(12 - filled));
             lines.push(Line::from(vec![
@@ -618,6 +730,15 @@ fn format_hotspot_for_yank(hotspot: &crate::analysis::FunctionHotspot) -> String
     writeln!(out, "=== HOTSPOT: {} ===", hotspot.name).ok();
     writeln!(out, "CPU: {:.1}%", hotspot.percentage).ok();
     writeln!(out, "Samples: {}", hotspot.count).ok();
+    if hotspot.sched_waits > 0 {
+        writeln!(
+            out,
+            "Caused waits: {} ({} total worker wait)",
+            hotspot.sched_waits,
+            format_latency(hotspot.sched_wait_ns)
+        )
+        .ok();
+    }
 
     if let Some(ref file) = hotspot.file {
         match hotspot.line {
@@ -639,6 +760,8 @@ fn format_hotspot_for_yank(hotspot: &crate::analysis::FunctionHotspot) -> String
                 FrameOrigin::StdLib => "[STD]",
                 FrameOrigin::RuntimeLib => "[RUNTIME]",
                 FrameOrigin::ThirdParty => "[3RDPARTY]",
+                FrameOrigin::SystemLib => "[SYSLIB]",
+                FrameOrigin::Kernel => "[KERNEL]",
                 FrameOrigin::Unknown => "[???]",
             };
 
@@ -660,7 +783,7 @@ fn format_hotspot_for_yank(hotspot: &crate::analysis::FunctionHotspot) -> String
         worker_list.sort_unstable_by(|a, b| b.1.cmp(a.1));
 
         for (&worker_id, &count) in worker_list.iter().take(4) {
-            let pct = (count as f64 / hotspot.count as f64) * 100.0;
+            let pct = (count as f64 / hotspot.count.max(1) as f64) * 100.0;
             writeln!(out, "  W{worker_id}: {pct:.0}% ({count} samples)").ok();
         }
     }
@@ -723,6 +846,16 @@ fn yank_file_group_to_clipboard(group: &hotspot::FileGroup) -> Result<()> {
 
 /// Render search input overlay (standalone version)
 fn render_search_overlay(f: &mut ratatui::Frame, area: Rect, query: &str) {
+    render_input_overlay(
+        f,
+        area,
+        "[ FILTER ] Enter=apply Esc=cancel",
+        &format!("Search: {query}_"),
+    );
+}
+
+/// Render a one-line text input box titled `title`
+fn render_input_overlay(f: &mut ratatui::Frame, area: Rect, title: &str, text: &str) {
     let popup_area = {
         let vertical = Layout::default()
             .direction(Direction::Vertical)
@@ -743,19 +876,18 @@ fn render_search_overlay(f: &mut ratatui::Frame, area: Rect, query: &str) {
             .split(vertical[1])[1]
     };
 
-    let search_text = format!("Search: {query}_");
-    let search_widget = Paragraph::new(search_text)
+    let input_widget = Paragraph::new(text.to_string())
         .block(
             Block::default()
                 .borders(Borders::ALL)
                 .border_type(BorderType::Plain)
-                .title("[ FILTER ] Enter=apply Esc=cancel")
+                .title(title.to_string())
                 .style(Style::default().bg(ratatui::style::Color::Black).fg(HUD_GREEN)),
         )
         .style(Style::default().fg(CAUTION_AMBER));
 
     f.render_widget(ratatui::widgets::Clear, popup_area);
-    f.render_widget(search_widget, popup_area);
+    f.render_widget(input_widget, popup_area);
 }
 
 /// Render file drilldown overlay showing all hotspot functions in a file.
@@ -950,6 +1082,8 @@ struct LiveApp {
     live_data: LiveData,
     /// Hotspot statistics for efficient aggregation
     hotspot_stats: crate::analysis::HotspotStats,
+    /// Per-task statistics for the tasks panel
+    task_stats: crate::analysis::TaskStats,
     /// Hotspot view (rebuilt on each update, preserves selection)
     hotspot_view: Option<HotspotView>,
 
@@ -957,6 +1091,45 @@ struct LiveApp {
     view_mode: ViewMode,
     search_query: String,
     should_quit: bool,
+    /// Show the tasks panel in place of the activity panel
+    show_tasks: bool,
+    /// Which stack frame the hotspot ranking charges samples to
+    attribution: crate::analysis::Attribution,
+    /// Timeline cursor and the time range every panel is limited to
+    timeline: TimelineState,
+    /// Snapshot the panels show while paused; events keep arriving meanwhile
+    paused: Option<TraceData>,
+    /// Events received since pausing
+    buffered: usize,
+
+    /// Bookmarks set so far, shown on the timeline
+    bookmarks: Vec<Bookmark>,
+    /// Sends bookmarks to the profiler for the export (none when replaying)
+    bookmark_tx: Option<Sender<Bookmark>>,
+    /// Label being typed and the moment it marks
+    bookmark_label: String,
+    bookmark_at: f64,
+    /// View to return to when the label is entered
+    bookmark_return: ViewMode,
+
+    /// Call tree of every sampled stack, for the flame graph
+    call_tree: CallTree,
+    /// Call tree of the window, rebuilt while the flame graph is shown
+    window_tree: Option<CallTree>,
+    /// Flame graph zoom, selection and highlight
+    flame_view: FlameGraphView,
+    /// Text being typed in the flame graph search
+    flame_query: String,
+
+    /// Butterfly navigation, while the butterfly view is open
+    butterfly_view: Option<ButterflyView>,
+    /// Callers and callees of the centered function, recomputed each refresh
+    butterfly: Option<crate::analysis::Butterfly>,
+    /// Streaming callers and callees of the butterfly's function over every
+    /// event received, fed as events arrive
+    butterfly_stats: Option<crate::analysis::ButterflyStats>,
+    /// View to return to when the butterfly view closes
+    butterfly_return: ViewMode,
 
     /// Frozen snapshot of hotspot for drilldown (prevents flicker during live updates)
     frozen_hotspot: Option<crate::analysis::FunctionHotspot>,
@@ -967,24 +1140,117 @@ struct LiveApp {
 
     /// Rolling time window in seconds. None = show all data, Some(n) = show last n seconds.
     window_secs: Option<f64>,
+
+    /// Registered workers, once the profiler has reported them
+    live_workers: Option<usize>,
+    /// Latest worker re-discovery change: (added, removed, when)
+    worker_change: Option<(usize, usize, std::time::Instant)>,
 }
 
 impl LiveApp {
-    fn new(window_secs: Option<f64>) -> Self {
+    fn new(window_secs: Option<f64>, bookmark_tx: Option<Sender<Bookmark>>) -> Self {
         Self {
             live_data: LiveData::new(),
             hotspot_stats: crate::analysis::HotspotStats::new(),
+            task_stats: crate::analysis::TaskStats::new(),
             hotspot_view: None,
             view_mode: ViewMode::Analysis,
             search_query: String::new(),
             should_quit: false,
+            show_tasks: false,
+            attribution: crate::analysis::Attribution::default(),
+            timeline: TimelineState::new(),
+            paused: None,
+            buffered: 0,
+            bookmarks: Vec::new(),
+            bookmark_tx,
+            bookmark_label: String::new(),
+            bookmark_at: 0.0,
+            bookmark_return: ViewMode::Analysis,
+            call_tree: CallTree::new(),
+            window_tree: None,
+            flame_view: FlameGraphView::new(),
+            flame_query: String::new(),
+            butterfly_view: None,
+            butterfly: None,
+            butterfly_stats: None,
+            butterfly_return: ViewMode::Analysis,
             frozen_hotspot: None,
             frozen_file_group: None,
             file_drilldown_selected: 0,
             window_secs,
+            live_workers: None,
+            worker_change: None,
+        }
+    }
+
+    /// Apply a worker re-discovery update from the profiler
+    fn apply_worker_changes(&mut self, changes: &WorkerChanges) {
+        self.live_workers = Some(changes.active);
+        if !changes.is_empty() {
+            self.worker_change =
+                Some((changes.added.len(), changes.removed.len(), std::time::Instant::now()));
         }
     }
 
+    /// Data the panels show: the snapshot taken when pausing, else the
+    /// live data (windowed if set)
+    fn snapshot(&self) -> TraceData {
+        self.paused.clone().unwrap_or_else(|| self.live_data.as_trace_data(self.window_secs))
+    }
+
+    /// True when the panels show less than every event received, so the
+    /// streaming aggregates do not apply and each refresh recomputes
+    fn is_partial(&self) -> bool {
+        self.window_secs.is_some() || self.paused.is_some() || self.timeline.range().is_some()
+    }
+
+    /// Freeze the panels, or follow the live data again
+    fn toggle_pause(&mut self) {
+        self.paused = match self.paused {
+            Some(_) => None,
+            None => Some(self.live_data.as_trace_data(self.window_secs)),
+        };
+        self.buffered = 0;
+    }
+
+    /// Start typing a bookmark for the timeline cursor, or else the newest
+    /// event shown
+    fn begin_bookmark(&mut self) {
+        let cursor = self.timeline.cursor().filter(|_| self.view_mode == ViewMode::Timeline);
+        let Some(at) = cursor.or_else(|| self.snapshot().events.last().map(|e| e.timestamp)) else {
+            return;
+        };
+        self.bookmark_at = at;
+        self.bookmark_label.clear();
+        self.bookmark_return = self.view_mode;
+        self.view_mode = ViewMode::Bookmark;
+    }
+
+    /// Keep the typed bookmark and send it to the profiler for the export
+    fn add_bookmark(&mut self) {
+        let label = match self.bookmark_label.trim() {
+            "" => format!("bookmark {}", self.bookmarks.len() + 1),
+            label => label.to_string(),
+        };
+        let bookmark = Bookmark { timestamp: self.bookmark_at, label };
+        if let Some(tx) = &self.bookmark_tx {
+            if tx.try_send(bookmark.clone()).is_err() {
+                log::warn!("Bookmark not exported: {}", bookmark.label);
+            }
+        }
+        self.bookmarks.push(bookmark);
+        self.view_mode = self.bookmark_return;
+    }
+
+    /// Open the butterfly view centered on `function`
+    fn open_butterfly(&mut self, function: String) {
+        self.butterfly_view = Some(ButterflyView::new(function));
+        self.butterfly = None;
+        self.butterfly_return = self.view_mode;
+        self.view_mode = ViewMode::Butterfly;
+    }
+
     /// Process keyboard input based on current view mode
     fn handle_key(&mut self, key: KeyCode) {
         match self.view_mode {
@@ -1038,6 +1304,147 @@ impl LiveApp {
                         hv.toggle_view();
                     }
                 }
+                KeyCode::Char('t' | 'T') => self.show_tasks = !self.show_tasks,
+                KeyCode::Char('f' | 'F') => self.view_mode = ViewMode::FlameGraph,
+                KeyCode::Char('l' | 'L') => self.view_mode = ViewMode::Timeline,
+                KeyCode::Char('p' | 'P') => self.toggle_pause(),
+                KeyCode::Char('m' | 'M') => self.begin_bookmark(),
+                KeyCode::Char('[' | ']') => {
+                    let steps = if key == KeyCode::Char('[') { -1 } else { 1 };
+                    self.timeline.shift_range(steps, &self.snapshot());
+                }
+                KeyCode::Esc => self.timeline.clear(),
+                KeyCode::Char('a' | 'A') => {
+                    self.attribution = self.attribution.next();
+                    let events = self.live_data.as_trace_data(None).events;
+                    self.hotspot_stats.set_attribution(self.attribution, &events);
+                    // A different ranking; the old position means nothing in it
+                    if let Some(hv) = &mut self.hotspot_view {
+                        hv.selected_index = 0;
+                    }
+                }
+                KeyCode::Char('b' | 'B') => {
+                    let selected = self
+                        .hotspot_view
+                        .as_ref()
+                        .filter(|hv| hv.view_mode() == hotspot::ViewMode::Functions)
+                        .and_then(|hv| hv.get_selected())
+                        .map(|h| h.name.clone());
+                    if let Some(name) = selected {
+                        self.open_butterfly(name);
+                    }
+                }
+                _ => {}
+            },
+            // Flame graph - move through the call tree, zoom, highlight
+            ViewMode::FlameGraph => {
+                let tree = self.window_tree.as_ref().unwrap_or(&self.call_tree);
+                match key {
+                    KeyCode::Esc | KeyCode::Char('q' | 'Q' | 'f' | 'F') => {
+                        self.view_mode = ViewMode::Analysis;
+                    }
+                    KeyCode::Up => self.flame_view.ascend(tree),
+                    KeyCode::Down => self.flame_view.descend(tree),
+                    KeyCode::Left => self.flame_view.sibling(tree, false),
+                    KeyCode::Right => self.flame_view.sibling(tree, true),
+                    KeyCode::Enter => self.flame_view.focus_selected(tree),
+                    KeyCode::Backspace => self.flame_view.zoom_out(tree),
+                    KeyCode::Char('/') => {
+                        self.view_mode = ViewMode::FlameSearch;
+                        self.flame_query.clear();
+                    }
+                    KeyCode::Char('c' | 'C') => self.flame_view.set_search(""),
+                    KeyCode::Char('b' | 'B') => {
+                        if let Some(name) = self.flame_view.selected_function(tree) {
+                            self.open_butterfly(name);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            // Timeline - move the cursor, select a range; ↑↓ still move
+            // through the hotspots it filters. The range outlives the view.
+            ViewMode::Timeline => match key {
+                KeyCode::Esc | KeyCode::Char('q' | 'Q' | 'l' | 'L') => {
+                    self.view_mode = ViewMode::Analysis;
+                    self.timeline.close();
+                }
+                KeyCode::Left | KeyCode::Right => {
+                    let columns = if key == KeyCode::Left { -1 } else { 1 };
+                    self.timeline.move_cursor(columns, &self.snapshot());
+                }
+                KeyCode::Char(' ') => self.timeline.mark(&self.snapshot()),
+                KeyCode::Char('[' | ']') => {
+                    let steps = if key == KeyCode::Char('[') { -1 } else { 1 };
+                    self.timeline.shift_range(steps, &self.snapshot());
+                }
+                KeyCode::Char('p' | 'P') => self.toggle_pause(),
+                KeyCode::Char('m' | 'M') => self.begin_bookmark(),
+                KeyCode::Char('+' | '=') => self.timeline.zoom_in(),
+                KeyCode::Char('-') => self.timeline.zoom_out(),
+                KeyCode::Char('c' | 'C') => self.timeline.clear(),
+                KeyCode::Up => {
+                    if let Some(hv) = &mut self.hotspot_view {
+                        hv.scroll_up();
+                    }
+                }
+                KeyCode::Down => {
+                    if let Some(hv) = &mut self.hotspot_view {
+                        hv.scroll_down();
+                    }
+                }
+                _ => {}
+            },
+            // Butterfly - move through callers and callees, re-center
+            ViewMode::Butterfly => {
+                let (Some(view), Some(butterfly)) = (&mut self.butterfly_view, &self.butterfly)
+                else {
+                    if matches!(key, KeyCode::Esc | KeyCode::Char('q' | 'Q')) {
+                        self.view_mode = self.butterfly_return;
+                        self.butterfly_view = None;
+                    }
+                    return;
+                };
+                match key {
+                    KeyCode::Esc | KeyCode::Char('q' | 'Q') => {
+                        self.view_mode = self.butterfly_return;
+                        self.butterfly_view = None;
+                        self.butterfly = None;
+                    }
+                    KeyCode::Up => view.scroll_up(),
+                    KeyCode::Down => view.scroll_down(butterfly),
+                    KeyCode::Enter => {
+                        view.recenter(butterfly);
+                        self.butterfly = None;
+                    }
+                    KeyCode::Backspace => {
+                        view.back();
+                        self.butterfly = None;
+                    }
+                    _ => {}
+                }
+            }
+            // Bookmark label - text input, Enter keeps the bookmark
+            ViewMode::Bookmark => match key {
+                KeyCode::Esc => self.view_mode = self.bookmark_return,
+                KeyCode::Enter => self.add_bookmark(),
+                KeyCode::Backspace => {
+                    self.bookmark_label.pop();
+                }
+                KeyCode::Char(c) => self.bookmark_label.push(c),
+                _ => {}
+            },
+            // Flame graph search - text input for highlighting frames
+            ViewMode::FlameSearch => match key {
+                KeyCode::Esc => self.view_mode = ViewMode::FlameGraph,
+                KeyCode::Enter => {
+                    self.flame_view.set_search(&self.flame_query);
+                    self.view_mode = ViewMode::FlameGraph;
+                }
+                KeyCode::Backspace => {
+                    self.flame_query.pop();
+                }
+                KeyCode::Char(c) => self.flame_query.push(c),
                 _ => {}
             },
             // Search overlay - text input for filtering
@@ -1066,6 +1473,11 @@ impl LiveApp {
                     self.view_mode = ViewMode::Analysis;
                     self.frozen_hotspot = None;
                 }
+                KeyCode::Char('b' | 'B') => {
+                    if let Some(name) = self.frozen_hotspot.as_ref().map(|h| h.name.clone()) {
+                        self.open_butterfly(name);
+                    }
+                }
                 KeyCode::Char('y' | 'Y') => {
                     if let Some(ref hotspot) = self.frozen_hotspot {
                         if let Err(e) = yank_hotspot_to_clipboard(hotspot) {
@@ -1133,14 +1545,16 @@ impl LiveApp {
             .as_ref()
             .map_or((0, hotspot::ViewMode::default()), |hv| (hv.selected_index, hv.view_mode()));
 
-        // When windowing is enabled, compute hotspots from filtered trace data.
-        // Without windowing, use the efficient streaming aggregator (HotspotStats).
-        let hotspots = if self.window_secs.is_some() {
-            crate::analysis::analyze_hotspots(trace_data)
+        // When windowed, paused or limited to a timeline range, compute
+        // hotspots from the filtered trace data. Otherwise, use the efficient
+        // streaming aggregator (HotspotStats).
+        let hotspots = if self.is_partial() {
+            crate::analysis::analyze_hotspots_by(trace_data, self.attribution)
         } else {
-            self.hotspot_stats.to_hotspots()
+            self.hotspot_stats.to_hotspots_by(self.attribution)
         };
         let mut new_view = HotspotView::from_hotspots(hotspots);
+        new_view.set_attribution(self.attribution);
 
         // Restore view mode
         new_view.set_view_mode(old_view_mode);
@@ -1159,6 +1573,29 @@ impl LiveApp {
     }
 }
 
+/// Render the bottom-right panel: activity by default, tasks when toggled
+/// with `T`, the timeline while it has focus (`L`).
+///
+/// The timeline spans all of `trace_data`, so a range can be picked from
+/// anywhere in it; activity covers `view_data`, the range picked.
+fn render_activity_or_tasks(
+    f: &mut ratatui::Frame,
+    area: Rect,
+    activity_view: &ActivityView,
+    tasks_panel: Option<&TasksPanel>,
+    timeline: Option<(&TimelineState, &[Bookmark])>,
+    trace_data: &TraceData,
+    view_data: &TraceData,
+) {
+    match (timeline, tasks_panel) {
+        (Some((state, bookmarks)), _) => {
+            TimelineView::new(trace_data, state, area).render(f, area, state, bookmarks);
+        }
+        (None, Some(panel)) => panel.render(f, area),
+        (None, None) => activity_view.render(f, area, view_data),
+    }
+}
+
 // =============================================================================
 // LIVE MODE ENTRY POINT
 // =============================================================================
@@ -1174,12 +1611,20 @@ impl LiveApp {
 ///
 /// # Arguments
 /// * `event_rx` - Channel receiving trace events from eBPF
+/// * `worker_rx` - Channel receiving worker re-discovery changes
+/// * `bookmark_tx` - Channel the bookmarks set with `M` are sent on, for the export
 /// * `pid` - Process ID being profiled (for display)
 /// * `window_secs` - Rolling time window in seconds (0 = show all data)
 ///
 /// # Errors
 /// Returns an error if terminal setup or rendering fails
-pub fn run_live(event_rx: Receiver<TraceEvent>, pid: Option<i32>, window_secs: u64) -> Result<()> {
+pub fn run_live(
+    event_rx: Receiver<TraceEvent>,
+    worker_rx: Receiver<WorkerChanges>,
+    bookmark_tx: Option<Sender<Bookmark>>,
+    pid: Option<i32>,
+    window_secs: u64,
+) -> Result<()> {
     // -------------------------------------------------------------------------
     // Terminal Setup
     // -------------------------------------------------------------------------
@@ -1194,12 +1639,14 @@ pub fn run_live(event_rx: Receiver<TraceEvent>, pid: Option<i32>, window_secs: u
     // -------------------------------------------------------------------------
     // Convert window_secs: 0 = None (show all data), N = Some(N.0) (last N seconds)
     let window = if window_secs > 0 { Some(window_secs as f64) } else { None };
-    let mut app = LiveApp::new(window);
+    let mut app = LiveApp::new(window, bookmark_tx);
     let mut last_update = std::time::Instant::now();
 
     // 10 Hz refresh rate balances responsiveness with CPU usage.
     // Higher rates (e.g., 30 Hz) cause unnecessary redraws; lower rates feel laggy.
     const UPDATE_INTERVAL: Duration = Duration::from_millis(100);
+    // How long "+N -M" stays next to the worker count after a change
+    const WORKER_CHANGE_HIGHLIGHT: Duration = Duration::from_secs(10);
 
     // -------------------------------------------------------------------------
     // Main Event Loop
@@ -1209,21 +1656,78 @@ pub fn run_live(event_rx: Receiver<TraceEvent>, pid: Option<i32>, window_secs: u
         while let Ok(event) = event_rx.try_recv() {
             // Record to stats aggregator, then add to raw event storage
             app.hotspot_stats.record_event(&event);
+            app.task_stats.record_event(&event);
+            app.call_tree.record_event(&event);
+            if let Some(stats) = &mut app.butterfly_stats {
+                stats.record_event(&event);
+            }
             app.live_data.add_event(event);
+            app.buffered += usize::from(app.paused.is_some());
+        }
+        while let Ok(changes) = worker_rx.try_recv() {
+            app.apply_worker_changes(&changes);
         }
 
-        // Snapshot current data for rendering (filtered by window if set)
-        let trace_data = app.live_data.as_trace_data(app.window_secs);
+        // Snapshot current data for rendering (filtered by window if set,
+        // frozen while paused)
+        let trace_data = app.snapshot();
 
         // Redraw periodically
         if last_update.elapsed() >= UPDATE_INTERVAL {
+            // A timeline range limits every panel but the timeline to that range
+            let view_data = app
+                .timeline
+                .range()
+                .map_or_else(|| trace_data.clone(), |(start, end)| trace_data.between(start, end));
             // Rebuild hotspot view from trace data (preserves selection)
-            app.update_hotspot_view(&trace_data);
-
-            let status_panel = StatusPanel::new(&trace_data);
-            let workers_panel = WorkersPanel::new(&trace_data);
-            let timeline_view = TimelineView::new(&trace_data);
+            app.update_hotspot_view(&view_data);
+
+            let mut status_panel = StatusPanel::new(&view_data);
+            if let Some(active) = app.live_workers {
+                // Highlight a re-discovery change for a few seconds
+                let change = app
+                    .worker_change
+                    .filter(|(_, _, at)| at.elapsed() < WORKER_CHANGE_HIGHLIGHT)
+                    .map(|(added, removed, _)| (added, removed));
+                status_panel = status_panel.with_live_workers(active, change);
+            }
+            let workers_panel = WorkersPanel::new(&view_data);
+            let activity_view = ActivityView::new(&view_data);
+            // Same rule as hotspots: batch over the data shown, else streaming stats
+            let tasks_panel = app.show_tasks.then(|| {
+                if app.is_partial() {
+                    TasksPanel::new(&crate::analysis::analyze_tasks(&view_data))
+                } else {
+                    TasksPanel::new(&app.task_stats)
+                }
+            });
             let has_events = !trace_data.events.is_empty();
+            let show_flame = matches!(app.view_mode, ViewMode::FlameGraph | ViewMode::FlameSearch);
+            // Partial: only the stacks shown, and only while anyone is looking
+            app.window_tree =
+                (show_flame && app.is_partial()).then(|| CallTree::from_events(&view_data.events));
+            // Same rule again; the streaming stats are rebuilt from every
+            // event only when the butterfly is re-centered
+            let is_partial = app.is_partial();
+            // The header counts the samples shown, like the panels
+            let sample_count = if is_partial {
+                view_data.sample_count()
+            } else {
+                app.hotspot_stats.total_samples()
+            };
+            app.butterfly = app.butterfly_view.as_ref().map(|view| {
+                if is_partial {
+                    return crate::analysis::analyze_butterfly(&view_data, view.function());
+                }
+                let stats = match app.butterfly_stats.take() {
+                    Some(stats) if stats.function() == view.function() => stats,
+                    _ => crate::analysis::ButterflyStats::from_data(&trace_data, view.function()),
+                };
+                app.butterfly_stats.insert(stats).to_butterfly()
+            });
+            if app.butterfly_view.is_none() {
+                app.butterfly_stats = None;
+            }
 
             terminal.draw(|f| {
                 let area = f.area();
@@ -1285,15 +1789,23 @@ pub fn run_live(event_rx: Receiver<TraceEvent>, pid: Option<i32>, window_secs: u
 
                 // Show session duration and sample count
                 let session_str = format_duration_human(trace_data.duration);
-                let sample_count = app.hotspot_stats.total_samples();
 
-                let header = Paragraph::new(vec![Line::from(vec![
-                    Span::styled("HUD", STYLE_HEADING),
-                    Span::styled(" | ", STYLE_DIM),
+                // Paused: how many events are waiting to be shown
+                let state = if app.paused.is_some() {
+                    Span::styled(
+                        format!("[PAUSED +{}]", app.buffered),
+                        Style::new().fg(CAUTION_AMBER).add_modifier(Modifier::BOLD),
+                    )
+                } else {
                     Span::styled(
                         "[LIVE]",
                         Style::new().fg(CRITICAL_RED).add_modifier(Modifier::BOLD),
-                    ),
+                    )
+                };
+                let mut header_spans = vec![
+                    Span::styled("HUD", STYLE_HEADING),
+                    Span::styled(" | ", STYLE_DIM),
+                    state,
                     Span::styled(" | ", STYLE_DIM),
                     Span::styled(format!("PID:{pid_display}"), Style::new().fg(HUD_GREEN)),
                     Span::styled(" | ", STYLE_DIM),
@@ -1301,8 +1813,15 @@ pub fn run_live(event_rx: Receiver<TraceEvent>, pid: Option<i32>, window_secs: u
                     Span::styled(" | ", STYLE_DIM),
                     Span::styled(format!("{sample_count} samples"), Style::new().fg(CAUTION_AMBER)),
                     Span::styled(format!(" ({rate:.0}/s)"), STYLE_DIM),
-                ])])
-                .block(
+                ];
+                if let Some((start, end)) = app.timeline.range() {
+                    header_spans.push(Span::styled(" | ", STYLE_DIM));
+                    header_spans.push(Span::styled(
+                        format!("range:{}", format_duration_human(end - start)),
+                        Style::new().fg(CAUTION_AMBER),
+                    ));
+                }
+                let header = Paragraph::new(vec![Line::from(header_spans)]).block(
                     Block::default()
                         .borders(Borders::ALL)
                         .border_type(BorderType::Plain)
@@ -1313,17 +1832,37 @@ pub fn run_live(event_rx: Receiver<TraceEvent>, pid: Option<i32>, window_secs: u
                 // Main content area - layout depends on terminal size
                 let main_area = outer_layout[1];
 
-                if layout_cfg.single_column {
-                    // Narrow terminals: stack hotspots + timeline vertically
+                let timeline_focused = app.view_mode == ViewMode::Timeline
+                    || (app.view_mode == ViewMode::Bookmark
+                        && app.bookmark_return == ViewMode::Timeline);
+                let timeline =
+                    timeline_focused.then_some((&app.timeline, app.bookmarks.as_slice()));
+                if let (Some(view), Some(butterfly)) = (&app.butterfly_view, &app.butterfly) {
+                    // Butterfly takes the whole main area
+                    view.render(f, main_area, butterfly);
+                } else if show_flame {
+                    // Flame graph takes the whole main area
+                    let tree = app.window_tree.as_ref().unwrap_or(&app.call_tree);
+                    app.flame_view.render(f, main_area, tree);
+                } else if layout_cfg.single_column {
+                    // Narrow terminals: stack hotspots + activity vertically
                     let rows = Layout::default()
                         .direction(Direction::Vertical)
                         .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
                         .split(main_area);
 
                     if let Some(ref hv) = app.hotspot_view {
-                        hv.render(f, rows[0], &trace_data);
+                        hv.render(f, rows[0], &view_data);
                     }
-                    timeline_view.render(f, rows[1], &trace_data);
+                    render_activity_or_tasks(
+                        f,
+                        rows[1],
+                        &activity_view,
+                        tasks_panel.as_ref(),
+                        timeline,
+                        &trace_data,
+                        &view_data,
+                    );
                 } else if layout_cfg.show_workers_panel {
                     // Full layout: 2x2 grid
                     let rows = Layout::default()
@@ -1342,15 +1881,23 @@ pub fn run_live(event_rx: Receiver<TraceEvent>, pid: Option<i32>, window_secs: u
                         .split(rows[1]);
 
                     if layout_cfg.show_status_panel {
-                        status_panel.render(f, top_cols[0], &trace_data);
+                        status_panel.render(f, top_cols[0], &view_data);
                     }
                     if let Some(ref hv) = app.hotspot_view {
-                        hv.render(f, top_cols[1], &trace_data);
+                        hv.render(f, top_cols[1], &view_data);
                     }
-                    workers_panel.render(f, bottom_cols[0], &trace_data);
-                    timeline_view.render(f, bottom_cols[1], &trace_data);
+                    workers_panel.render(f, bottom_cols[0], &view_data);
+                    render_activity_or_tasks(
+                        f,
+                        bottom_cols[1],
+                        &activity_view,
+                        tasks_panel.as_ref(),
+                        timeline,
+                        &trace_data,
+                        &view_data,
+                    );
                 } else {
-                    // Compact/minimal: hotspots + timeline, optionally with status panel
+                    // Compact/minimal: hotspots + activity, optionally with status panel
                     let rows = Layout::default()
                         .direction(Direction::Vertical)
                         .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
@@ -1362,15 +1909,23 @@ pub fn run_live(event_rx: Receiver<TraceEvent>, pid: Option<i32>, window_secs: u
                             .constraints(layout_cfg.col_constraints())
                             .split(rows[0]);
 
-                        status_panel.render(f, top_cols[0], &trace_data);
+                        status_panel.render(f, top_cols[0], &view_data);
                         if let Some(ref hv) = app.hotspot_view {
-                            hv.render(f, top_cols[1], &trace_data);
+                            hv.render(f, top_cols[1], &view_data);
                         }
                     } else if let Some(ref hv) = app.hotspot_view {
                         // Minimal: hotspots take full width
-                        hv.render(f, rows[0], &trace_data);
+                        hv.render(f, rows[0], &view_data);
                     }
-                    timeline_view.render(f, rows[1], &trace_data);
+                    render_activity_or_tasks(
+                        f,
+                        rows[1],
+                        &activity_view,
+                        tasks_panel.as_ref(),
+                        timeline,
+                        &trace_data,
+                        &view_data,
+                    );
                 }
 
                 // Search overlay
@@ -1378,6 +1933,19 @@ pub fn run_live(event_rx: Receiver<TraceEvent>, pid: Option<i32>, window_secs: u
                     render_search_overlay(f, area, &app.search_query);
                 }
 
+                if app.view_mode == ViewMode::FlameSearch {
+                    render_search_overlay(f, area, &app.flame_query);
+                }
+
+                if app.view_mode == ViewMode::Bookmark {
+                    render_input_overlay(
+                        f,
+                        area,
+                        "[ BOOKMARK ] Enter=add Esc=cancel",
+                        &format!("Label: {}_", app.bookmark_label),
+                    );
+                }
+
                 // Help overlay
                 if app.view_mode == ViewMode::Help {
                     render_help_overlay(f, area);
@@ -1418,6 +1986,8 @@ pub fn run_live(event_rx: Receiver<TraceEvent>, pid: Option<i32>, window_secs: u
                             Span::styled(":Close ", STYLE_DIM),
                             Span::styled("Y", STYLE_KEY),
                             Span::styled(":Yank ", STYLE_DIM),
+                            Span::styled("B", STYLE_KEY),
+                            Span::styled(":Callers ", STYLE_DIM),
                             Span::styled("[Detail]", Style::new().fg(CAUTION_AMBER)),
                         ]),
                         ViewMode::FileDrillDown => Line::from(vec![
@@ -1431,7 +2001,61 @@ pub fn run_live(event_rx: Receiver<TraceEvent>, pid: Option<i32>, window_secs: u
                             Span::styled(":Yank ", STYLE_DIM),
                             Span::styled("[File]", Style::new().fg(CAUTION_AMBER)),
                         ]),
-                        ViewMode::Search => Line::from(vec![
+                        ViewMode::FlameGraph => Line::from(vec![
+                            Span::styled("ESC", STYLE_KEY),
+                            Span::styled(":Close ", STYLE_DIM),
+                            Span::styled("↑↓←→", STYLE_KEY),
+                            Span::styled(":Nav ", STYLE_DIM),
+                            Span::styled("Enter", STYLE_KEY),
+                            Span::styled(":Zoom ", STYLE_DIM),
+                            Span::styled("Bksp", STYLE_KEY),
+                            Span::styled(":Out ", STYLE_DIM),
+                            Span::styled("/", STYLE_KEY),
+                            Span::styled(":Highlight ", STYLE_DIM),
+                            Span::styled("C", STYLE_KEY),
+                            Span::styled(":Clear ", STYLE_DIM),
+                            Span::styled("B", STYLE_KEY),
+                            Span::styled(":Callers ", STYLE_DIM),
+                            Span::styled("[Flame]", Style::new().fg(CAUTION_AMBER)),
+                        ]),
+                        ViewMode::Timeline => Line::from(vec![
+                            Span::styled("ESC", STYLE_KEY),
+                            Span::styled(":Close ", STYLE_DIM),
+                            Span::styled("←→", STYLE_KEY),
+                            Span::styled(":Cursor ", STYLE_DIM),
+                            Span::styled("Space", STYLE_KEY),
+                            Span::styled(":Mark range ", STYLE_DIM),
+                            Span::styled("[]", STYLE_KEY),
+                            Span::styled(":Slide ", STYLE_DIM),
+                            Span::styled("+-", STYLE_KEY),
+                            Span::styled(":Zoom ", STYLE_DIM),
+                            Span::styled("C", STYLE_KEY),
+                            Span::styled(":Clear ", STYLE_DIM),
+                            Span::styled("P", STYLE_KEY),
+                            Span::styled(":Pause ", STYLE_DIM),
+                            Span::styled("M", STYLE_KEY),
+                            Span::styled(":Bookmark ", STYLE_DIM),
+                            Span::styled("[Timeline]", Style::new().fg(CAUTION_AMBER)),
+                        ]),
+                        ViewMode::Butterfly => Line::from(vec![
+                            Span::styled("ESC", STYLE_KEY),
+                            Span::styled(":Close ", STYLE_DIM),
+                            Span::styled("↑↓", STYLE_KEY),
+                            Span::styled(":Nav ", STYLE_DIM),
+                            Span::styled("Enter", STYLE_KEY),
+                            Span::styled(":Center ", STYLE_DIM),
+                            Span::styled("Bksp", STYLE_KEY),
+                            Span::styled(":Back ", STYLE_DIM),
+                            Span::styled("[Butterfly]", Style::new().fg(CAUTION_AMBER)),
+                        ]),
+                        ViewMode::Bookmark => Line::from(vec![
+                            Span::styled("ESC", STYLE_KEY),
+                            Span::styled(":Cancel ", STYLE_DIM),
+                            Span::styled("Enter", STYLE_KEY),
+                            Span::styled(":Add ", STYLE_DIM),
+                            Span::styled("[Bookmark]", Style::new().fg(CAUTION_AMBER)),
+                        ]),
+                        ViewMode::Search | ViewMode::FlameSearch => Line::from(vec![
                             Span::styled("ESC", STYLE_KEY),
                             Span::styled(":Cancel ", STYLE_DIM),
                             Span::styled("Enter", STYLE_KEY),
@@ -1439,24 +2063,46 @@ pub fn run_live(event_rx: Receiver<TraceEvent>, pid: Option<i32>, window_secs: u
                             Span::styled("[Search]", Style::new().fg(CAUTION_AMBER)),
                         ]),
                         _ => {
-                            let mode = if has_events {
+                            let mode = if app.paused.is_some() {
+                                Span::styled("[Paused]", Style::new().fg(CAUTION_AMBER))
+                            } else if has_events {
                                 Span::styled("[Live]", Style::new().fg(CRITICAL_RED))
                             } else {
                                 Span::styled("[Waiting]", STYLE_DIM)
                             };
-                            Line::from(vec![
+                            let mut keys = vec![
                                 Span::styled("Q", STYLE_KEY),
                                 Span::styled(":Quit ", STYLE_DIM),
                                 Span::styled("Enter", STYLE_KEY),
                                 Span::styled(":Detail ", STYLE_DIM),
                                 Span::styled("G", STYLE_KEY),
                                 Span::styled(":Group ", STYLE_DIM),
+                                Span::styled("A", STYLE_KEY),
+                                Span::styled(":Rank ", STYLE_DIM),
+                                Span::styled("T", STYLE_KEY),
+                                Span::styled(":Tasks ", STYLE_DIM),
+                                Span::styled("L", STYLE_KEY),
+                                Span::styled(":Timeline ", STYLE_DIM),
+                                Span::styled("F", STYLE_KEY),
+                                Span::styled(":Flame ", STYLE_DIM),
+                                Span::styled("B", STYLE_KEY),
+                                Span::styled(":Callers ", STYLE_DIM),
+                                Span::styled("P", STYLE_KEY),
+                                Span::styled(":Pause ", STYLE_DIM),
+                                Span::styled("M", STYLE_KEY),
+                                Span::styled(":Bookmark ", STYLE_DIM),
                                 Span::styled("/", STYLE_KEY),
                                 Span::styled(":Search ", STYLE_DIM),
                                 Span::styled("?", STYLE_KEY),
                                 Span::styled(":Help ", STYLE_DIM),
-                                mode,
-                            ])
+                            ];
+                            // A range limits every panel until cleared
+                            if app.timeline.range().is_some() {
+                                keys.push(Span::styled("ESC", STYLE_KEY));
+                                keys.push(Span::styled(":All time ", STYLE_DIM));
+                            }
+                            keys.push(mode);
+                            Line::from(keys)
                         }
                     };
 
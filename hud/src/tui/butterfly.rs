@@ -0,0 +1,226 @@
+//! Butterfly view - callers and callees of one function.
+//!
+//! # What This Shows
+//!
+//! Every direct caller of the function above it and every direct callee
+//! below, aggregated over all sampled stacks (see
+//! [`analyze_butterfly`](crate::analysis::analyze_butterfly)):
+//!
+//! ```text
+//! [ BUTTERFLY ]  serde_json::from_slice  212 samples  18.4% of all
+//! CALLERS
+//!   41.2%  [||||      ]  myapp::handlers::upload
+//!   33.0%  [|||       ]  myapp::handlers::import
+//! ▶ serde_json::from_slice
+//! CALLEES
+//!   61.3%  [||||||    ]  serde_json::de::Deserializer::parse_value
+//!   22.1%  [||        ]  (self)
+//! ```
+//!
+//! # Navigation
+//!
+//! - **↑/↓**: move through callers, then callees
+//! - **Enter**: re-center on the selected caller or callee
+//! - **Backspace**: back to the previously centered function
+
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{block::BorderType, Block, Borders, Paragraph},
+    Frame,
+};
+
+use super::flamegraph::origin_color;
+use super::theme::{gauge_bar, CAUTION_AMBER, HUD_CYAN, HUD_GREEN, INFO_DIM};
+use crate::analysis::{Butterfly, Neighbor};
+
+/// Butterfly navigation state
+#[derive(Debug)]
+pub struct ButterflyView {
+    /// Functions centered so far; the last one is shown
+    history: Vec<String>,
+    /// Row among callers followed by callees
+    selected: usize,
+}
+
+impl ButterflyView {
+    pub fn new(function: String) -> Self {
+        Self { history: vec![function], selected: 0 }
+    }
+
+    /// The centered function
+    pub fn function(&self) -> &str {
+        self.history.last().map_or("", String::as_str)
+    }
+
+    pub fn scroll_up(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn scroll_down(&mut self, butterfly: &Butterfly) {
+        let rows = butterfly.callers.len() + butterfly.callees.len();
+        self.selected = (self.selected + 1).min(rows.saturating_sub(1));
+    }
+
+    /// Caller or callee under the cursor
+    fn selected_neighbor<'a>(&self, butterfly: &'a Butterfly) -> Option<&'a Neighbor> {
+        butterfly
+            .callers
+            .get(self.selected)
+            .or_else(|| butterfly.callees.get(self.selected - butterfly.callers.len()))
+    }
+
+    /// Center on the selected caller or callee
+    pub fn recenter(&mut self, butterfly: &Butterfly) {
+        if let Some(neighbor) = self.selected_neighbor(butterfly) {
+            self.history.push(neighbor.name.clone());
+            self.selected = 0;
+        }
+    }
+
+    /// Center on the previous function, if any
+    pub fn back(&mut self) {
+        if self.history.len() > 1 {
+            self.history.pop();
+            self.selected = 0;
+        }
+    }
+
+    pub fn render(&self, f: &mut Frame, area: Rect, butterfly: &Butterfly) {
+        let title = format!(
+            "[ BUTTERFLY ]  {}  {} samples  {:.1}% of all",
+            butterfly.function, butterfly.count, butterfly.percentage
+        );
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Plain)
+            .title(title)
+            .border_style(Style::default().fg(HUD_GREEN));
+        let inner = block.inner(area);
+        f.render_widget(block, area);
+
+        let halves = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Fill(1), Constraint::Length(1), Constraint::Fill(1)])
+            .split(inner);
+
+        let selected = self
+            .selected
+            .min((butterfly.callers.len() + butterfly.callees.len()).saturating_sub(1));
+        let (above_sel, below_sel) = if selected < butterfly.callers.len() {
+            (Some(selected), None)
+        } else {
+            (None, Some(selected - butterfly.callers.len()))
+        };
+
+        let mut above = butterfly.callers.clone();
+        if butterfly.root_count > 0 {
+            above.push(pseudo_row("(root)", butterfly.root_count, butterfly.count));
+        }
+        render_list(f, halves[0], "CALLERS", &above, above_sel);
+
+        let center = Line::from(vec![
+            Span::styled("▶ ", Style::default().fg(CAUTION_AMBER)),
+            Span::styled(
+                butterfly.function.clone(),
+                Style::default().fg(HUD_CYAN).add_modifier(Modifier::BOLD),
+            ),
+        ]);
+        f.render_widget(Paragraph::new(center), halves[1]);
+
+        let mut below = butterfly.callees.clone();
+        if butterfly.self_count > 0 {
+            below.push(pseudo_row("(self)", butterfly.self_count, butterfly.count));
+        }
+        render_list(f, halves[2], "CALLEES", &below, below_sel);
+    }
+}
+
+/// A row for samples with no caller or callee. It goes after the real
+/// neighbors, outside the selectable range, as there is nothing to center on.
+fn pseudo_row(name: &str, count: usize, total: usize) -> Neighbor {
+    Neighbor {
+        name: name.to_string(),
+        origin: crate::classification::FrameOrigin::Unknown,
+        count,
+        percentage: if total > 0 { count as f64 / total as f64 * 100.0 } else { 0.0 },
+    }
+}
+
+/// Heading plus one row per neighbor, scrolled to keep the selection visible
+fn render_list(
+    f: &mut Frame,
+    area: Rect,
+    heading: &str,
+    rows: &[Neighbor],
+    selected: Option<usize>,
+) {
+    let visible = usize::from(area.height.saturating_sub(1));
+    let offset = selected.map_or(0, |s| (s + 1).saturating_sub(visible));
+
+    let mut lines = vec![Line::from(Span::styled(
+        heading.to_string(),
+        Style::default().fg(INFO_DIM).add_modifier(Modifier::BOLD),
+    ))];
+    if rows.is_empty() {
+        lines.push(Line::from(Span::styled("  none sampled", Style::default().fg(INFO_DIM))));
+    }
+    for (i, row) in rows.iter().enumerate().skip(offset).take(visible) {
+        let is_selected = selected == Some(i);
+        let name_style = if is_selected {
+            Style::default().fg(HUD_CYAN).add_modifier(Modifier::REVERSED)
+        } else {
+            Style::default().fg(HUD_GREEN)
+        };
+        lines.push(Line::from(vec![
+            Span::styled(if is_selected { "> " } else { "  " }, name_style),
+            Span::styled(format!("{:>5.1}%  ", row.percentage), Style::default().fg(HUD_GREEN)),
+            Span::styled(gauge_bar(row.percentage, 10), Style::default().fg(INFO_DIM)),
+            Span::raw(" "),
+            Span::styled("■ ", Style::default().fg(origin_color(row.origin))),
+            Span::styled(row.name.clone(), name_style),
+            Span::styled(format!("  {}", row.count), Style::default().fg(INFO_DIM)),
+        ]));
+    }
+    f.render_widget(Paragraph::new(lines), area);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::classification::FrameOrigin;
+
+    fn neighbor(name: &str) -> Neighbor {
+        Neighbor {
+            name: name.to_string(),
+            origin: FrameOrigin::UserCode,
+            count: 1,
+            percentage: 50.0,
+        }
+    }
+
+    #[test]
+    fn test_navigation_crosses_from_callers_to_callees() {
+        let butterfly = Butterfly {
+            function: "from_slice".into(),
+            count: 2,
+            self_count: 0,
+            root_count: 0,
+            percentage: 100.0,
+            callers: vec![neighbor("upload"), neighbor("import")],
+            callees: vec![neighbor("parse_value")],
+        };
+        let mut view = ButterflyView::new("from_slice".into());
+        for _ in 0..5 {
+            view.scroll_down(&butterfly);
+        }
+        assert_eq!(view.selected, 2);
+        view.recenter(&butterfly);
+        assert_eq!(view.function(), "parse_value");
+        view.back();
+        assert_eq!(view.function(), "from_slice");
+        view.back();
+        assert_eq!(view.function(), "from_slice");
+    }
+}
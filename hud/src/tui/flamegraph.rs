@@ -0,0 +1,485 @@
+//! Flame graph view - every sampled call stack merged into one call tree.
+//!
+//! # What This Shows
+//!
+//! An icicle: the outermost caller on top, callees below, each frame as wide
+//! as the samples that passed through it. Where the drilldown shows one
+//! representative stack of one function, this shows the whole shape of the
+//! blocking, including which callers lead to a hot leaf.
+//!
+//! ```text
+//! [ FLAME GRAPH ]  1234 samples
+//! all                                                                  |
+//! main                                                                 |
+//! tokio::runtime::...::run                                             |
+//! app::handler                          |app::db::query        |
+//! app::hash          |serde_json::...   |std::net::...         |
+//! ```
+//!
+//! # Navigation
+//!
+//! - **↑/↓**: caller / heaviest callee of the selected frame
+//! - **←/→**: previous / next sibling
+//! - **Enter**: zoom so the selected frame spans the full width
+//! - **Backspace**: zoom out one level
+//! - **/**: highlight frames whose name contains the query
+//!
+//! Selection and zoom are kept as paths of function names, so they survive
+//! the tree being rebuilt as new samples arrive.
+//!
+//! # Colors
+//!
+//! Frames are colored by `FrameOrigin`: user code green, std cyan, runtime
+//! blue, third-party crates violet, system libraries amber, kernel red.
+
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{block::BorderType, Block, Borders, Paragraph},
+    Frame,
+};
+use std::collections::HashMap;
+
+use super::theme::{CAUTION_AMBER, CRITICAL_RED, CYAN_DIM, HUD_CYAN, HUD_GREEN, INFO_DIM};
+use super::truncate;
+use crate::classification::FrameOrigin;
+use crate::trace_data::TraceEvent;
+
+/// Name of the root frame that holds every sample
+const ROOT: &str = "all";
+
+/// Background of frames matching the search query
+const SEARCH_MATCH: Color = Color::Rgb(255, 0, 255);
+
+// =============================================================================
+// CALL TREE
+// =============================================================================
+
+/// One frame under one caller
+#[derive(Debug, Clone)]
+struct Node {
+    name: String,
+    origin: FrameOrigin,
+    /// Samples whose stack passes through this frame
+    total: u64,
+    children: Vec<usize>,
+}
+
+/// Call tree merged from sampled stacks, outermost frame at the root.
+///
+/// Like `HotspotStats`, it can be fed one event at a time as the session
+/// streams in, or built in one go from a (windowed) `TraceData` snapshot.
+#[derive(Debug, Clone)]
+pub struct CallTree {
+    /// Arena of nodes; index 0 is the root
+    nodes: Vec<Node>,
+    /// Child index by (parent index, function name)
+    index: HashMap<(usize, String), usize>,
+}
+
+impl Default for CallTree {
+    fn default() -> Self {
+        Self {
+            nodes: vec![Node {
+                name: ROOT.to_string(),
+                origin: FrameOrigin::Unknown,
+                total: 0,
+                children: Vec::new(),
+            }],
+            index: HashMap::new(),
+        }
+    }
+}
+
+impl CallTree {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build a tree from every sample in `events`
+    pub fn from_events(events: &[TraceEvent]) -> Self {
+        let mut tree = Self::new();
+        for event in events {
+            tree.record_event(event);
+        }
+        tree
+    }
+
+    /// Add one event's call stack. Scheduler latency events and samples
+    /// without a stack are skipped.
+    pub fn record_event(&mut self, event: &TraceEvent) {
+        if event.duration_ns.is_some() {
+            return;
+        }
+        let Some(ref stack) = event.call_stack else {
+            return;
+        };
+        if stack.is_empty() {
+            return;
+        }
+
+        self.nodes[0].total += 1;
+        let mut parent = 0;
+        // Stacks are innermost first; the tree grows from the outermost caller
+        for frame in stack.iter().rev() {
+            let next = self.nodes.len();
+            let idx = *self.index.entry((parent, frame.function.clone())).or_insert(next);
+            if idx == next {
+                self.nodes.push(Node {
+                    name: frame.function.clone(),
+                    origin: frame.origin,
+                    total: 0,
+                    children: Vec::new(),
+                });
+                self.nodes[parent].children.push(idx);
+            }
+            self.nodes[idx].total += 1;
+            parent = idx;
+        }
+    }
+
+    /// Samples in the tree
+    pub fn total_samples(&self) -> u64 {
+        self.nodes[0].total
+    }
+
+    /// Children of `idx`, heaviest first (ties by name, so order is stable)
+    fn sorted_children(&self, idx: usize) -> Vec<usize> {
+        let mut children = self.nodes[idx].children.clone();
+        children.sort_by(|&a, &b| {
+            let (a, b) = (&self.nodes[a], &self.nodes[b]);
+            b.total.cmp(&a.total).then_with(|| a.name.cmp(&b.name))
+        });
+        children
+    }
+
+    /// Node at `path` (function names below the root), or at its longest
+    /// prefix that still exists, with that prefix's length
+    fn resolve(&self, path: &[String]) -> (usize, usize) {
+        let mut idx = 0;
+        for (depth, name) in path.iter().enumerate() {
+            match self.index.get(&(idx, name.clone())) {
+                Some(&child) => idx = child,
+                None => return (idx, depth),
+            }
+        }
+        (idx, path.len())
+    }
+}
+
+// =============================================================================
+// VIEW
+// =============================================================================
+
+/// A frame placed on screen: columns `[x0, x1)` of one row
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Cell {
+    x0: u16,
+    x1: u16,
+    node: usize,
+}
+
+/// Flame graph navigation state
+#[derive(Debug, Default)]
+pub struct FlameGraphView {
+    /// Path of the zoomed frame; it spans the full width
+    focus: Vec<String>,
+    /// Path of the selected frame; always starts with `focus`
+    selected: Vec<String>,
+    /// Highlight frames containing this text
+    search: Option<String>,
+}
+
+impl FlameGraphView {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Drop path elements that no longer exist (e.g. the window moved on)
+    fn sync(&mut self, tree: &CallTree) {
+        let (_, focus_len) = tree.resolve(&self.focus);
+        self.focus.truncate(focus_len);
+        let (_, selected_len) = tree.resolve(&self.selected);
+        self.selected.truncate(selected_len);
+        if !self.selected.starts_with(&self.focus) {
+            self.selected.clone_from(&self.focus);
+        }
+    }
+
+    /// Select the caller; from the zoomed frame, also zoom out one level
+    pub fn ascend(&mut self, tree: &CallTree) {
+        self.sync(tree);
+        if self.selected.len() == self.focus.len() {
+            self.focus.pop();
+        }
+        self.selected.pop();
+    }
+
+    /// Select the heaviest callee
+    pub fn descend(&mut self, tree: &CallTree) {
+        self.sync(tree);
+        let (idx, _) = tree.resolve(&self.selected);
+        if let Some(&child) = tree.sorted_children(idx).first() {
+            self.selected.push(tree.nodes[child].name.clone());
+        }
+    }
+
+    /// Select the next (`forward`) or previous sibling, in screen order
+    pub fn sibling(&mut self, tree: &CallTree, forward: bool) {
+        self.sync(tree);
+        // Siblings of the zoomed frame are off screen
+        if self.selected.len() <= self.focus.len() {
+            return;
+        }
+        let (parent, _) = tree.resolve(&self.selected[..self.selected.len() - 1]);
+        let siblings = tree.sorted_children(parent);
+        let Some(name) = self.selected.last() else {
+            return;
+        };
+        let Some(pos) = siblings.iter().position(|&s| tree.nodes[s].name == *name) else {
+            return;
+        };
+        let next = if forward { pos + 1 } else { pos.wrapping_sub(1) };
+        if let Some(&sibling) = siblings.get(next) {
+            if let Some(last) = self.selected.last_mut() {
+                last.clone_from(&tree.nodes[sibling].name);
+            }
+        }
+    }
+
+    /// Function name of the selected frame, unless it is the root
+    pub fn selected_function(&self, tree: &CallTree) -> Option<String> {
+        let (_, len) = tree.resolve(&self.selected);
+        self.selected[..len].last().cloned()
+    }
+
+    /// Zoom to the selected frame
+    pub fn focus_selected(&mut self, tree: &CallTree) {
+        self.sync(tree);
+        self.focus.clone_from(&self.selected);
+    }
+
+    /// Zoom out one level, keeping the selection
+    pub fn zoom_out(&mut self, tree: &CallTree) {
+        self.sync(tree);
+        self.focus.pop();
+    }
+
+    /// Highlight frames whose name contains `query` (empty clears it)
+    pub fn set_search(&mut self, query: &str) {
+        self.search = (!query.is_empty()).then(|| query.to_string());
+    }
+
+    /// Lay out the zoomed subtree: one row of cells per depth, `width`
+    /// columns wide, at most `max_rows` rows. Children split their parent's
+    /// columns by sample count, heaviest first; the remainder is the
+    /// parent's own time.
+    fn layout(&self, tree: &CallTree, width: u16, max_rows: usize) -> Vec<Vec<Cell>> {
+        let (focus, _) = tree.resolve(&self.focus);
+        let mut rows = vec![vec![Cell { x0: 0, x1: width, node: focus }]];
+
+        while rows.len() < max_rows {
+            let mut next = Vec::new();
+            for cell in rows.last().into_iter().flatten() {
+                let parent_total = tree.nodes[cell.node].total.max(1);
+                let span = u64::from(cell.x1 - cell.x0);
+                let mut before = 0;
+                for child in tree.sorted_children(cell.node) {
+                    let after = before + tree.nodes[child].total;
+                    let x0 = cell.x0 + (before * span / parent_total) as u16;
+                    let x1 = cell.x0 + (after * span / parent_total) as u16;
+                    before = after;
+                    // Too narrow to draw
+                    if x1 > x0 {
+                        next.push(Cell { x0, x1, node: child });
+                    }
+                }
+            }
+            if next.is_empty() {
+                break;
+            }
+            rows.push(next);
+        }
+        rows
+    }
+
+    pub fn render(&self, f: &mut Frame, area: Rect, tree: &CallTree) {
+        let mut view = FlameGraphView {
+            focus: self.focus.clone(),
+            selected: self.selected.clone(),
+            search: self.search.clone(),
+        };
+        view.sync(tree);
+
+        let width = area.width.saturating_sub(2);
+        // Borders, the two info lines and the legend
+        let max_rows = usize::from(area.height.saturating_sub(5)).max(1);
+        let (selected, _) = tree.resolve(&view.selected);
+        let (focus, _) = tree.resolve(&view.focus);
+        let total = tree.total_samples().max(1);
+
+        let mut lines = Vec::new();
+
+        // Zoom path and selected frame
+        let zoom = if view.focus.is_empty() {
+            ROOT.to_string()
+        } else {
+            format!("{ROOT} > {}", view.focus.join(" > "))
+        };
+        lines.push(Line::from(vec![
+            Span::styled("Zoom ", Style::default().fg(INFO_DIM)),
+            Span::styled(truncate(&zoom, usize::from(width).saturating_sub(5)), HUD_CYAN),
+        ]));
+        let node = &tree.nodes[selected];
+        let of_focus = node.total as f64 / tree.nodes[focus].total.max(1) as f64 * 100.0;
+        lines.push(Line::from(vec![
+            Span::styled("Sel  ", Style::default().fg(INFO_DIM)),
+            Span::styled(
+                format!("{} ", truncate(&node.name, usize::from(width).saturating_sub(45))),
+                Style::default().fg(origin_color(node.origin)).add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                format!(
+                    "{} samples  {:.1}% of all  {of_focus:.1}% of zoom",
+                    node.total,
+                    node.total as f64 / total as f64 * 100.0
+                ),
+                Style::default().fg(HUD_GREEN),
+            ),
+        ]));
+
+        if tree.total_samples() == 0 {
+            lines.push(Line::from(Span::styled(
+                "No call stacks sampled yet",
+                Style::default().fg(INFO_DIM),
+            )));
+        } else {
+            for row in view.layout(tree, width, max_rows) {
+                lines.push(self.render_row(tree, &row, selected));
+            }
+        }
+
+        let title = format!("[ FLAME GRAPH ]  {} samples", tree.total_samples());
+        let mut legend = Vec::new();
+        for (origin, label) in [
+            (FrameOrigin::UserCode, "user"),
+            (FrameOrigin::StdLib, "std"),
+            (FrameOrigin::RuntimeLib, "runtime"),
+            (FrameOrigin::ThirdParty, "crates"),
+            (FrameOrigin::SystemLib, "system"),
+            (FrameOrigin::Kernel, "kernel"),
+        ] {
+            legend.push(Span::styled(" ", Style::default().bg(origin_color(origin))));
+            legend.push(Span::styled(format!(" {label} "), Style::default().fg(INFO_DIM)));
+        }
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Plain)
+            .title(title)
+            .title_bottom(Line::from(legend))
+            .border_style(Style::default().fg(HUD_GREEN));
+        f.render_widget(Paragraph::new(lines).block(block), area);
+    }
+
+    /// One icicle row: each cell the frame's name on its origin color
+    fn render_row(&self, tree: &CallTree, row: &[Cell], selected: usize) -> Line<'static> {
+        let mut spans = Vec::new();
+        let mut x = 0;
+        for cell in row {
+            if cell.x0 > x {
+                spans.push(Span::raw(" ".repeat(usize::from(cell.x0 - x))));
+            }
+            let node = &tree.nodes[cell.node];
+            let width = usize::from(cell.x1 - cell.x0);
+            // Last column left blank so neighbouring frames stay apart
+            let label = format!("{:<width$}", truncate(&node.name, width.saturating_sub(1)));
+
+            let matches = self.search.as_ref().is_some_and(|q| node.name.contains(q.as_str()));
+            let bg = if matches { SEARCH_MATCH } else { origin_color(node.origin) };
+            let mut style = Style::default().fg(Color::Black).bg(bg);
+            if cell.node == selected {
+                style = style.add_modifier(Modifier::REVERSED | Modifier::BOLD);
+            }
+            spans.push(Span::styled(label, style));
+            x = cell.x1;
+        }
+        Line::from(spans)
+    }
+}
+
+/// Background color of a frame
+pub(super) fn origin_color(origin: FrameOrigin) -> Color {
+    match origin {
+        FrameOrigin::UserCode => HUD_GREEN,
+        FrameOrigin::StdLib => CYAN_DIM,
+        FrameOrigin::RuntimeLib => Color::Rgb(60, 130, 255),
+        FrameOrigin::ThirdParty => Color::Rgb(170, 120, 255),
+        FrameOrigin::SystemLib => CAUTION_AMBER,
+        FrameOrigin::Kernel => CRITICAL_RED,
+        FrameOrigin::Unknown => Color::Rgb(130, 130, 130),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::stack_sample;
+
+    fn tree() -> CallTree {
+        let mut events = vec![stack_sample(&["main", "serve", "hash"]); 6];
+        events.extend(vec![stack_sample(&["main", "serve", "parse"]); 2]);
+        events.extend(vec![stack_sample(&["main", "log"]); 2]);
+        CallTree::from_events(&events)
+    }
+
+    #[test]
+    fn test_tree_merges_common_callers() {
+        let tree = tree();
+        assert_eq!(tree.total_samples(), 10);
+        let (serve, depth) = tree.resolve(&["main".into(), "serve".into()]);
+        assert_eq!(depth, 2);
+        assert_eq!(tree.nodes[serve].total, 8);
+        assert_eq!(tree.nodes[serve].children.len(), 2);
+    }
+
+    #[test]
+    fn test_navigation_follows_heaviest_path() {
+        let tree = tree();
+        let mut view = FlameGraphView::new();
+        for _ in 0..3 {
+            view.descend(&tree);
+        }
+        assert_eq!(view.selected, ["main", "serve", "hash"]);
+
+        view.sibling(&tree, true);
+        assert_eq!(view.selected, ["main", "serve", "parse"]);
+        view.sibling(&tree, true);
+        assert_eq!(view.selected, ["main", "serve", "parse"]);
+
+        view.ascend(&tree);
+        view.focus_selected(&tree);
+        assert_eq!(view.focus, ["main", "serve"]);
+        // Ascending from the zoomed frame zooms out with it
+        view.ascend(&tree);
+        assert_eq!(view.focus, ["main"]);
+        assert_eq!(view.selected, ["main"]);
+    }
+
+    #[test]
+    fn test_layout_splits_width_by_samples() {
+        let tree = tree();
+        let mut view = FlameGraphView::new();
+        let rows = view.layout(&tree, 100, 10);
+        assert_eq!(rows.len(), 4);
+        // serve (8 of 10) then log (2 of 10) under main
+        assert_eq!((rows[2][0].x0, rows[2][0].x1), (0, 80));
+        assert_eq!((rows[2][1].x0, rows[2][1].x1), (80, 100));
+
+        // Zoomed to serve: hash takes 6 of its 8 samples
+        view.focus = vec!["main".into(), "serve".into()];
+        let rows = view.layout(&tree, 80, 10);
+        assert_eq!((rows[1][0].x0, rows[1][0].x1), (0, 60));
+    }
+}
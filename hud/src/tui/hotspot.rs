@@ -6,6 +6,9 @@
 //! - **Functions view**: Individual functions ranked by CPU% (default)
 //! - **Files view**: Functions grouped by source file (toggle with 'G' key)
 //!
+//! Either view can rank by first user frame, leaf frame or inclusive samples
+//! (cycle with 'A' key, see [`Attribution`]).
+//!
 //! # Data Flow
 //!
 //! ```text
@@ -40,9 +43,9 @@ use ratatui::{
     Frame,
 };
 
-use super::format_duration_human;
 use super::theme::{severity_marker, CAUTION_AMBER, HUD_GREEN, INFO_DIM, SEL_LEFT, SEL_RIGHT};
-use crate::analysis::{analyze_hotspots, FunctionHotspot};
+use super::{format_duration_human, format_latency, truncate};
+use crate::analysis::{analyze_hotspots, Attribution, FunctionHotspot};
 use crate::trace_data::TraceData;
 
 /// View mode for hotspot display
@@ -113,15 +116,6 @@ fn group_by_file(hotspots: &[FunctionHotspot]) -> Vec<FileGroup> {
 
 // Pure data operations (filtering logic separated from UI state)
 
-/// Truncate a string for display, adding "..." if too long
-fn truncate_for_display(s: &str, max_len: usize) -> String {
-    if s.len() > max_len {
-        format!("{}...", &s[..max_len.saturating_sub(3)])
-    } else {
-        s.to_string()
-    }
-}
-
 /// Calculate scroll offset to keep selected item visible
 fn visible_scroll_offset(selected: usize, current_offset: usize, visible_count: usize) -> usize {
     // Scroll down if selected is beyond visible window
@@ -213,6 +207,8 @@ pub struct HotspotView {
     view_mode: ViewMode,
     /// Pre-computed file groupings (rebuilt when hotspots change)
     file_groups: Vec<FileGroup>,
+    /// How the hotspots were ranked (shown in the title)
+    attribution: Attribution,
 }
 
 impl HotspotView {
@@ -230,6 +226,7 @@ impl HotspotView {
             filter_active: false,
             view_mode: ViewMode::default(),
             file_groups,
+            attribution: Attribution::default(),
         }
     }
 
@@ -245,6 +242,7 @@ impl HotspotView {
             filter_active: false,
             view_mode: ViewMode::default(),
             file_groups,
+            attribution: Attribution::default(),
         }
     }
 
@@ -265,6 +263,11 @@ impl HotspotView {
         }
     }
 
+    /// Record how the hotspots were ranked
+    pub fn set_attribution(&mut self, attribution: Attribution) {
+        self.attribution = attribution;
+    }
+
     /// Get the current view mode
     #[must_use]
     pub fn view_mode(&self) -> ViewMode {
@@ -332,8 +335,12 @@ impl HotspotView {
         // Format duration for title
         let duration_str = format_duration_human(data.duration);
         let view_indicator = match self.view_mode {
-            ViewMode::Functions => "",
-            ViewMode::Files => " FILES",
+            ViewMode::Functions => String::new(),
+            ViewMode::Files => " FILES".to_string(),
+        } + &match self.attribution {
+            // The default ranking goes unmarked
+            Attribution::UserFrame => String::new(),
+            other => format!(" {}", other.label().to_uppercase()),
         };
         let title = if self.filter_active {
             let shown = self.hotspots.len();
@@ -368,7 +375,7 @@ impl HotspotView {
         {
             let is_selected = scroll_offset + display_idx == self.selected_index;
             let (marker, severity_color) = severity_marker(hotspot.percentage);
-            let display_name = truncate_for_display(&hotspot.name, max_name_len);
+            let display_name = truncate(&hotspot.name, max_name_len);
 
             // Line 1: <marker name percentage>
             lines.push(render_item_line(
@@ -394,10 +401,30 @@ impl HotspotView {
                 })
                 .unwrap_or_else(|| format!("{} samples", hotspot.count));
 
-            lines.push(Line::from(vec![
-                Span::raw("        "),
-                Span::styled(detail, Style::default().fg(INFO_DIM)),
-            ]));
+            let mut detail_spans =
+                vec![Span::raw("        "), Span::styled(detail, Style::default().fg(INFO_DIM))];
+            // Inclusive shares overlap; the self share is what the function
+            // itself spent on the CPU
+            if self.attribution == Attribution::Inclusive && hotspot.count > 0 {
+                let self_pct =
+                    hotspot.percentage * hotspot.self_count as f64 / hotspot.count as f64;
+                detail_spans.push(Span::styled(
+                    format!("  self {self_pct:.1}%"),
+                    Style::default().fg(INFO_DIM),
+                ));
+            }
+            // Worker waits this function caused as a scheduler latency blocker
+            if hotspot.sched_waits > 0 {
+                detail_spans.push(Span::styled(
+                    format!(
+                        "  ▲{} waits {}",
+                        hotspot.sched_waits,
+                        format_latency(hotspot.sched_wait_ns)
+                    ),
+                    Style::default().fg(CAUTION_AMBER),
+                ));
+            }
+            lines.push(Line::from(detail_spans));
         }
 
         lines
@@ -423,7 +450,7 @@ impl HotspotView {
                 .file_name()
                 .and_then(|n| n.to_str())
                 .unwrap_or(&group.file);
-            let display_name = truncate_for_display(display_file, max_name_len);
+            let display_name = truncate(display_file, max_name_len);
 
             // Line 1: <marker filename percentage>
             lines.push(render_item_line(
@@ -458,11 +485,14 @@ mod tests {
         FunctionHotspot {
             name: name.to_string(),
             count: 100,
+            self_count: 100,
             percentage,
             file: file.map(String::from),
             line: Some(10),
             workers: std::collections::HashMap::new(),
             call_stacks: vec![],
+            sched_waits: 0,
+            sched_wait_ns: 0,
         }
     }
 
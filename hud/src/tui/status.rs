@@ -9,8 +9,9 @@
 //!  [!] CAUTION          <- Blinks amber if issues detected
 //!
 //!  Events  1234         <- Total samples captured
-//!  Workers 4            <- Number of Tokio worker threads
+//!  Workers 4 +2 -1      <- Registered Tokio workers (recent changes)
 //!  Debug   85%          <- % of frames with debug info
+//!  Waits   3 ▲37ms      <- Scheduler latency events (shown once any arrive)
 //!
 //!  Hottest W2           <- Worker with most blocking
 //!  [||||      ] 45%     <- Its blocking percentage
@@ -43,7 +44,7 @@ use std::collections::HashMap;
 use super::theme::{
     gauge_bar, status_color, warning_color, CAUTION_AMBER, HUD_CYAN, HUD_GREEN, INFO_DIM,
 };
-use super::TraceData;
+use super::{format_latency, TraceData};
 use crate::classification::diagnostics;
 
 /// Master Status panel - tactical system overview.
@@ -57,25 +58,35 @@ pub struct StatusPanel {
     busiest_worker: Option<(u32, f64)>,
     /// Total samples captured
     total_events: usize,
-    /// Number of unique worker threads seen
+    /// Number of unique worker threads seen (or registered, when known)
     worker_count: usize,
+    /// Workers added/removed by the latest re-discovery, while still recent
+    worker_change: Option<(usize, usize)>,
     /// Percentage of frames with debug info (0-100)
     debug_info_coverage: f64,
     /// True if debug coverage < 50%
     low_debug_coverage: bool,
+    /// Scheduler latency events (off-CPU wait over threshold)
+    sched_waits: usize,
+    /// Longest scheduler-measured off-CPU wait
+    max_wait_ns: u64,
 }
 
 impl StatusPanel {
     pub fn new(data: &TraceData) -> Self {
         // Aggregate worker activity: (total_samples, samples_with_function_names)
-        let worker_activity = data.events.iter().fold(HashMap::new(), |mut acc, event| {
-            let entry = acc.entry(event.worker_id).or_insert((0usize, 0usize));
-            entry.0 += 1;
-            if event.name != "execution" {
-                entry.1 += 1;
-            }
-            acc
-        });
+        // Scheduler latency events are intervals, not samples, so they are excluded
+        let worker_activity = data.events.iter().filter(|e| !e.is_scheduler_event()).fold(
+            HashMap::new(),
+            |mut acc, event| {
+                let entry = acc.entry(event.worker_id).or_insert((0usize, 0usize));
+                entry.0 += 1;
+                if event.name != "execution" {
+                    entry.1 += 1;
+                }
+                acc
+            },
+        );
 
         // Find busiest worker by percentage of samples with function names
         let busiest = worker_activity
@@ -92,16 +103,31 @@ impl StatusPanel {
         let low_debug_coverage = debug_info_coverage < 50.0;
         let has_warnings = busiest.is_some_and(|(_, pct)| pct > 50.0) || low_debug_coverage;
 
+        let sched_waits = data.events.iter().filter(|e| e.is_scheduler_event()).count();
+        let max_wait_ns = data.events.iter().filter_map(|e| e.duration_ns).max().unwrap_or(0);
+
         Self {
             has_warnings,
             busiest_worker: busiest,
             total_events: data.events.len(),
             worker_count: data.workers.len(),
+            worker_change: None,
             debug_info_coverage,
             low_debug_coverage,
+            sched_waits,
+            max_wait_ns,
         }
     }
 
+    /// Show the registered worker count from re-discovery instead of the
+    /// workers seen in the data, with the latest change if still recent.
+    #[must_use]
+    pub fn with_live_workers(mut self, active: usize, change: Option<(usize, usize)>) -> Self {
+        self.worker_count = active;
+        self.worker_change = change;
+        self
+    }
+
     pub fn render(&self, f: &mut Frame, area: Rect, _data: &TraceData) {
         // System status line with appropriate styling
         let (status_text, status_style) = if self.has_warnings {
@@ -124,10 +150,17 @@ impl StatusPanel {
                 Span::styled(" Events  ", Style::default().fg(INFO_DIM)),
                 Span::styled(self.total_events.to_string(), Style::default().fg(HUD_GREEN)),
             ]),
-            Line::from(vec![
-                Span::styled(" Workers ", Style::default().fg(INFO_DIM)),
-                Span::styled(self.worker_count.to_string(), Style::default().fg(HUD_GREEN)),
-            ]),
+            Line::from(
+                [
+                    Span::styled(" Workers ", Style::default().fg(INFO_DIM)),
+                    Span::styled(self.worker_count.to_string(), Style::default().fg(HUD_GREEN)),
+                ]
+                .into_iter()
+                .chain(self.worker_change.map(|(added, removed)| {
+                    Span::styled(format!(" +{added} -{removed}"), Style::default().fg(HUD_CYAN))
+                }))
+                .collect::<Vec<_>>(),
+            ),
             Line::from(vec![
                 Span::styled(" Debug   ", Style::default().fg(INFO_DIM)),
                 Span::styled(
@@ -135,9 +168,21 @@ impl StatusPanel {
                     Style::default().fg(debug_color),
                 ),
             ]),
-            Line::from(""),
         ];
 
+        // Scheduler latency summary (only once the hook has reported something)
+        if self.sched_waits > 0 {
+            lines.push(Line::from(vec![
+                Span::styled(" Waits   ", Style::default().fg(INFO_DIM)),
+                Span::styled(self.sched_waits.to_string(), Style::default().fg(CAUTION_AMBER)),
+                Span::styled(
+                    format!(" ▲{}", format_latency(self.max_wait_ns)),
+                    Style::default().fg(CAUTION_AMBER),
+                ),
+            ]));
+        }
+        lines.push(Line::from(""));
+
         // Busiest worker with gauge (if any workers active)
         if let Some((worker_id, percentage)) = self.busiest_worker {
             let bar_color = warning_color(percentage);
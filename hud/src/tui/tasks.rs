@@ -0,0 +1,113 @@
+//! Tasks panel - ranks Tokio tasks by blocking samples.
+//!
+//! # What This Shows
+//!
+//! Which async tasks were running when blocking samples were taken:
+//!
+//! ```text
+//! [ TASKS 2/14 ]
+//! Task       Samples  Share  Top function
+//! #4127          312  61.2%  myapp::handlers::resize_image
+//! #88             41   8.0%  myapp::db::query
+//! no task         97  19.0%
+//! ```
+//!
+//! # Reading It
+//!
+//! - **One task dominates**: a single runaway task (e.g., one request doing
+//!   CPU-heavy work). Fix that code path.
+//! - **Share spread thin**: many tasks hit the same blocking code. Look at the
+//!   hotspots instead; the problem is in shared code.
+//! - **"no task" dominates**: task ID tracking is unavailable (the uprobe
+//!   could not attach) or blocking happens outside tasks.
+
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{block::BorderType, Block, Borders, Paragraph},
+    Frame,
+};
+
+use super::theme::{warning_color, CAUTION_AMBER, HUD_CYAN, HUD_GREEN, INFO_DIM};
+use crate::analysis::{TaskHotspot, TaskStats};
+
+/// Tasks panel - per-task blocking breakdown.
+pub struct TasksPanel {
+    /// Tasks sorted by blocking samples (descending)
+    tasks: Vec<TaskHotspot>,
+    /// Blocking samples with no task ID
+    unattributed: u64,
+    /// All blocking samples (denominator for the "no task" share)
+    total: u64,
+}
+
+impl TasksPanel {
+    pub fn new(stats: &TaskStats) -> Self {
+        Self {
+            tasks: stats.to_tasks(),
+            unattributed: stats.unattributed_samples(),
+            total: stats.total_samples(),
+        }
+    }
+
+    pub fn render(&self, f: &mut Frame, area: Rect) {
+        let mut lines = vec![Line::from(Span::styled(
+            "Task       Samples  Share  Top function",
+            Style::default().fg(INFO_DIM).add_modifier(Modifier::BOLD),
+        ))];
+
+        // Header + borders + "no task" row
+        let visible = (area.height as usize).saturating_sub(4);
+        let max_name_len = (area.width as usize).saturating_sub(32);
+
+        if self.tasks.is_empty() {
+            lines.push(Line::from(Span::styled(
+                "No task IDs captured (see preflight output)",
+                Style::default().fg(INFO_DIM),
+            )));
+        }
+
+        lines.extend(self.tasks.iter().take(visible).map(|task| {
+            let color = warning_color(task.percentage);
+            let name = if task.top_function.len() > max_name_len {
+                format!("{}…", &task.top_function[..max_name_len.saturating_sub(1)])
+            } else {
+                task.top_function.clone()
+            };
+            let mut spans = vec![
+                Span::styled(format!("#{:<9}", task.task_id), Style::default().fg(HUD_CYAN)),
+                Span::styled(format!("{:>7}", task.count), Style::default().fg(HUD_GREEN)),
+                Span::styled(format!(" {:>5.1}%  ", task.percentage), Style::default().fg(color)),
+                Span::styled(name, Style::default().fg(HUD_GREEN)),
+            ];
+            if task.sched_waits > 0 {
+                spans.push(Span::styled(
+                    format!(" ▲{}", task.sched_waits),
+                    Style::default().fg(CAUTION_AMBER),
+                ));
+            }
+            Line::from(spans)
+        }));
+
+        if self.unattributed > 0 {
+            let pct = (self.unattributed as f64 / self.total.max(1) as f64) * 100.0;
+            lines.push(Line::from(vec![
+                Span::styled("no task   ", Style::default().fg(INFO_DIM)),
+                Span::styled(format!("{:>7}", self.unattributed), Style::default().fg(INFO_DIM)),
+                Span::styled(format!(" {pct:>5.1}%"), Style::default().fg(INFO_DIM)),
+            ]));
+        }
+
+        let title = format!("[ TASKS {}/{} ]", self.tasks.len().min(visible), self.tasks.len());
+        let paragraph = Paragraph::new(lines).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Plain)
+                .title(title)
+                .border_style(Style::default().fg(HUD_GREEN)),
+        );
+
+        f.render_widget(paragraph, area);
+    }
+}
@@ -1,144 +1,437 @@
-//! Activity panel - detailed per-worker statistics with thread IDs.
+//! Timeline panel - per-worker blocking heatmap over time.
 //!
 //! # What This Shows
 //!
-//! A tabular view of each worker thread with more detail than the Workers panel:
+//! One row per worker, one column per time bucket, newest on the right. A
+//! cell's shade is how many blocking samples the worker had in that bucket,
+//! relative to the busiest cell on screen. `▲` marks buckets with a
+//! scheduler latency event over the threshold, red when it is the longest
+//! wait on screen. `◆` under a column is a bookmark; its label shows in the
+//! title while the cursor is on it.
 //!
 //! ```text
-//! [ ACTIVITY ]
-//! Duration 45.2s  Events 1234
-//! ID  TID      Samples  Load
-//! W0  12345    100/250  [||||      ] 40%
-//! W1  12346     50/250  [||        ] 20%
+//! [ TIMELINE 1s/col  5s selected  ▲max 48ms ]
+//! W0  ░░▒▓██▓▒░     ░░░░▲▒▒
+//! W1  ░  ░▒▓▓▒░      ░░▲██▓
+//!        ◆     ^
 //! ```
 //!
-//! # Columns
+//! # Range Selection
 //!
-//! - **ID**: Worker ID (W0, W1, W2...) assigned by Tokio
-//! - **TID**: OS thread ID (useful for correlating with `htop`, `perf`, etc.)
-//! - **Samples**: blocking/total samples for this worker
-//! - **Load**: Visual gauge + percentage of blocking time
+//! - **←/→**: move the cursor one bucket (the view follows the newest data
+//!   until the cursor moves)
+//! - **Space**: mark the start of a range, then the end; while a range is
+//!   selected, every panel covers only that range
+//! - **[/]**: slide the range back or forward by its own length
+//! - **+/-**: zoom (seconds per column)
 //!
-//! # Color Thresholds
-//!
-//! - Green (< 20%): Healthy, mostly yielding
-//! - Amber (20-50%): Some blocking, worth investigating
-//! - Red (> 50%): Significant blocking, needs attention
+//! Cursor and range are kept as timestamps, so they stay put as the
+//! timeline scrolls and when zooming.
 
 use ratatui::{
     layout::Rect,
-    style::{Modifier, Style},
+    style::{Color, Modifier, Style},
     text::{Line, Span},
     widgets::{block::BorderType, Block, Borders, Paragraph},
     Frame,
 };
 use std::collections::HashMap;
+use std::fmt::Write as _;
 
-use super::theme::{
-    gauge_bar, CAUTION_AMBER, CRITICAL_RED, CYAN_DIM, HUD_CYAN, HUD_GREEN, INFO_DIM,
-};
-use super::TraceData;
+use super::format_latency;
+use super::theme::{CAUTION_AMBER, CRITICAL_RED, HUD_CYAN, HUD_GREEN, INFO_DIM};
+use crate::trace_data::{Bookmark, TraceData};
 
-/// Timeline view - detailed per-worker statistics with OS thread IDs.
-pub struct TimelineView {
-    /// Per-worker statistics, keyed by worker ID
-    worker_stats: HashMap<u32, WorkerStats>,
-}
+/// Seconds per column, selectable with +/-
+const ZOOM_LEVELS: [f64; 9] = [0.1, 0.25, 0.5, 1.0, 2.0, 5.0, 10.0, 30.0, 60.0];
+
+/// Default zoom: one second per column
+const DEFAULT_ZOOM: usize = 3;
+
+/// Shades from one sample up to the busiest cell
+const SHADES: [&str; 4] = ["░", "▒", "▓", "█"];
 
-/// Detailed statistics for a single worker thread.
+/// Width of the "W12 " row label
+const LABEL_WIDTH: u16 = 4;
+
+// =============================================================================
+// CURSOR AND SELECTION
+// =============================================================================
+
+/// Timeline cursor, zoom and selected range
 #[derive(Debug, Clone)]
-struct WorkerStats {
-    /// Total samples captured for this worker
-    total_samples: usize,
-    /// Samples with actual blocking function names
-    samples_with_functions: usize,
-    /// OS thread ID (from /proc or gettid)
-    tid: u32,
+pub struct TimelineState {
+    zoom: usize,
+    /// Cursor timestamp; `None` follows the newest bucket
+    cursor: Option<f64>,
+    /// Start of a range being selected
+    anchor: Option<f64>,
+    /// Committed range `[start, end)`, in event timestamps
+    range: Option<(f64, f64)>,
 }
 
-impl WorkerStats {
-    /// Calculate load percentage (samples with function names / total)
-    fn load_percentage(&self) -> f64 {
-        if self.total_samples > 0 {
-            (self.samples_with_functions as f64 / self.total_samples as f64) * 100.0
-        } else {
-            0.0
-        }
+impl Default for TimelineState {
+    fn default() -> Self {
+        Self { zoom: DEFAULT_ZOOM, cursor: None, anchor: None, range: None }
     }
+}
 
-    /// Get severity color based on load percentage
-    fn load_color(&self) -> ratatui::style::Color {
-        match self.load_percentage() {
-            r if r > 50.0 => CRITICAL_RED,
-            r if r > 20.0 => CAUTION_AMBER,
-            _ => HUD_GREEN,
+impl TimelineState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Seconds per column
+    pub fn bucket_secs(&self) -> f64 {
+        ZOOM_LEVELS[self.zoom]
+    }
+
+    /// Selected range, if any
+    pub fn range(&self) -> Option<(f64, f64)> {
+        self.range
+    }
+
+    /// Cursor timestamp, once the cursor has moved off the newest bucket
+    pub fn cursor(&self) -> Option<f64> {
+        self.cursor
+    }
+
+    /// Move the cursor `columns` buckets, staying within `data`
+    pub fn move_cursor(&mut self, columns: i32, data: &TraceData) {
+        let Some((first, last)) = time_span(data) else {
+            return;
+        };
+        let from = self.cursor.unwrap_or(last);
+        let to = (from + f64::from(columns) * self.bucket_secs()).clamp(first, last);
+        self.cursor = Some(to);
+    }
+
+    /// Finer buckets
+    pub fn zoom_in(&mut self) {
+        self.zoom = self.zoom.saturating_sub(1);
+    }
+
+    /// Coarser buckets
+    pub fn zoom_out(&mut self) {
+        self.zoom = (self.zoom + 1).min(ZOOM_LEVELS.len() - 1);
+    }
+
+    /// Mark a range end at the cursor: the first mark starts a range, the
+    /// second commits it
+    pub fn mark(&mut self, data: &TraceData) {
+        let Some(cursor) = self.cursor.or_else(|| time_span(data).map(|(_, last)| last)) else {
+            return;
+        };
+        match self.anchor.take() {
+            None => {
+                self.anchor = Some(cursor);
+                self.cursor = Some(cursor);
+                self.range = None;
+            }
+            Some(anchor) => {
+                let bucket = self.bucket_secs();
+                let start = (anchor.min(cursor) / bucket).floor() * bucket;
+                let end = (anchor.max(cursor) / bucket).floor() * bucket + bucket;
+                self.range = Some((start, end));
+            }
         }
     }
+
+    /// Slide the selected range by its own length, `steps` times (negative
+    /// is back in time), staying within `data`. The cursor goes to its end.
+    pub fn shift_range(&mut self, steps: i32, data: &TraceData) {
+        let (Some((start, end)), Some((first, last))) = (self.range, time_span(data)) else {
+            return;
+        };
+        let bucket = self.bucket_secs();
+        let len = end - start;
+        let earliest = (first / bucket).floor() * bucket;
+        let latest = ((last / bucket).floor() * bucket + bucket - len).max(earliest);
+        let start = (start + f64::from(steps) * len).clamp(earliest, latest);
+        self.range = Some((start, start + len));
+        self.cursor = Some((start + len - bucket).clamp(first, last));
+    }
+
+    /// Leave the timeline: drop a half-made range and the cursor, keep the
+    /// selected range
+    pub fn close(&mut self) {
+        self.anchor = None;
+        self.cursor = None;
+    }
+
+    /// Drop the selected range and any half-made one; the cursor follows
+    /// the newest data again
+    pub fn clear(&mut self) {
+        self.anchor = None;
+        self.range = None;
+        self.cursor = None;
+    }
+}
+
+/// First and last event timestamp
+fn time_span(data: &TraceData) -> Option<(f64, f64)> {
+    Some((data.events.first()?.timestamp, data.events.last()?.timestamp))
+}
+
+// =============================================================================
+// HEATMAP
+// =============================================================================
+
+/// One worker's buckets
+#[derive(Debug, Default)]
+struct Row {
+    samples: Vec<u32>,
+    /// Longest scheduler wait per bucket, nanoseconds (0 = none)
+    waits: Vec<u64>,
+}
+
+/// Timeline panel - per-worker blocking samples bucketed over time
+pub struct TimelineView {
+    bucket_secs: f64,
+    /// Bucket index (timestamp / `bucket_secs`) of the rightmost column
+    last_bucket: i64,
+    columns: u16,
+    rows: Vec<(u32, Row)>,
+    /// Most samples in any cell, for shading
+    max_samples: u32,
+    /// Longest wait in any cell
+    max_wait_ns: u64,
 }
 
 impl TimelineView {
-    pub fn new(data: &TraceData) -> Self {
-        let worker_stats = data.events.iter().fold(HashMap::new(), |mut acc, event| {
-            let stats = acc.entry(event.worker_id).or_insert(WorkerStats {
-                total_samples: 0,
-                samples_with_functions: 0,
-                tid: event.tid,
-            });
-            stats.total_samples += 1;
-            if event.name != "execution" {
-                stats.samples_with_functions += 1;
-            }
-            acc
+    /// Bucket `data` into columns ending at the cursor or the newest event,
+    /// as many as fit in `area`
+    #[must_use]
+    pub fn new(data: &TraceData, state: &TimelineState, area: Rect) -> Self {
+        let bucket_secs = state.bucket_secs();
+        let columns = area.width.saturating_sub(2 + LABEL_WIDTH).max(1);
+        let newest = time_span(data).map_or(0.0, |(_, last)| last);
+        // Scroll just enough to keep the cursor on screen
+        let newest_bucket = bucket_index(newest, bucket_secs);
+        let last_bucket = state.cursor.map_or(newest_bucket, |cursor| {
+            let cursor_bucket = bucket_index(cursor, bucket_secs);
+            newest_bucket.min(cursor_bucket + i64::from(columns / 2)).max(cursor_bucket)
         });
+        let first_bucket = last_bucket - i64::from(columns) + 1;
+
+        let mut rows: HashMap<u32, Row> = data
+            .workers
+            .iter()
+            .map(|&w| {
+                let cells = usize::from(columns);
+                (w, Row { samples: vec![0; cells], waits: vec![0; cells] })
+            })
+            .collect();
+        for event in data.events.iter() {
+            let bucket = bucket_index(event.timestamp, bucket_secs);
+            if bucket < first_bucket || bucket > last_bucket {
+                continue;
+            }
+            let col = (bucket - first_bucket) as usize;
+            let Some(row) = rows.get_mut(&event.worker_id) else {
+                continue;
+            };
+            if let Some(wait_ns) = event.duration_ns {
+                row.waits[col] = row.waits[col].max(wait_ns);
+            } else if event.name != "execution" {
+                row.samples[col] += 1;
+            }
+        }
+
+        let max_samples = rows.values().flat_map(|r| r.samples.iter().copied()).max().unwrap_or(0);
+        let max_wait_ns = rows.values().flat_map(|r| r.waits.iter().copied()).max().unwrap_or(0);
+        let mut rows: Vec<(u32, Row)> = rows.into_iter().collect();
+        rows.sort_unstable_by_key(|(w, _)| *w);
+
+        Self { bucket_secs, last_bucket, columns, rows, max_samples, max_wait_ns }
+    }
+
+    /// Column of a timestamp, if it is on screen
+    fn column_of(&self, timestamp: f64) -> Option<usize> {
+        let offset = bucket_index(timestamp, self.bucket_secs) - self.last_bucket
+            + i64::from(self.columns)
+            - 1;
+        usize::try_from(offset).ok().filter(|&c| c < usize::from(self.columns))
+    }
+
+    /// Glyph and color of one cell
+    fn cell(&self, samples: u32, wait_ns: u64) -> (&'static str, Color) {
+        if wait_ns > 0 {
+            let color = if wait_ns == self.max_wait_ns { CRITICAL_RED } else { CAUTION_AMBER };
+            return ("▲", color);
+        }
+        if samples == 0 {
+            return (" ", INFO_DIM);
+        }
+        let level = (samples as usize * SHADES.len()).div_ceil(self.max_samples.max(1) as usize);
+        let level = level.clamp(1, SHADES.len());
+        let color = match level {
+            4 => CRITICAL_RED,
+            3 => CAUTION_AMBER,
+            _ => HUD_GREEN,
+        };
+        (SHADES[level - 1], color)
+    }
 
-        Self { worker_stats }
-    }
-
-    pub fn render(&self, f: &mut Frame, area: Rect, data: &TraceData) {
-        let mut lines = vec![];
-
-        // Header stats
-        lines.push(Line::from(vec![
-            Span::styled("Duration ", Style::default().fg(INFO_DIM)),
-            Span::styled(format!("{:.1}s", data.duration), Style::default().fg(HUD_CYAN)),
-            Span::raw("  "),
-            Span::styled("Events ", Style::default().fg(INFO_DIM)),
-            Span::styled(format!("{}", data.events.len()), Style::default().fg(HUD_GREEN)),
-        ]));
-
-        // Column header
-        lines.push(Line::from(vec![Span::styled(
-            "ID  TID      Samples  Load",
-            Style::default().fg(INFO_DIM).add_modifier(Modifier::BOLD),
-        )]));
-
-        // Worker rows - use filter_map to skip workers without stats
-        lines.extend(data.workers.iter().filter_map(|worker_id| {
-            self.worker_stats.get(worker_id).map(|stats| {
-                let rate = stats.load_percentage();
-                let color = stats.load_color();
-
-                Line::from(vec![
-                    Span::styled(format!("W{worker_id:<2} "), Style::default().fg(HUD_CYAN)),
-                    Span::styled(format!("{:<8} ", stats.tid), Style::default().fg(CYAN_DIM)),
-                    Span::styled(
-                        format!("{:>4}/{:<4} ", stats.samples_with_functions, stats.total_samples),
-                        Style::default().fg(HUD_GREEN),
-                    ),
-                    Span::styled(gauge_bar(rate, 12), Style::default().fg(color)),
-                    Span::styled(format!(" {rate:>3.0}%"), Style::default().fg(color)),
-                ])
+    pub fn render(&self, f: &mut Frame, area: Rect, state: &TimelineState, bookmarks: &[Bookmark]) {
+        let mut lines = Vec::new();
+        let visible_rows = usize::from(area.height.saturating_sub(3));
+
+        // Columns inside the selected (or being selected) range
+        let selected = |col: u16| {
+            let range = state.range.or_else(|| {
+                let anchor = state.anchor?;
+                let cursor = state.cursor?;
+                Some((anchor.min(cursor), anchor.max(cursor)))
+            });
+            range.is_some_and(|(start, end)| {
+                let bucket = self.last_bucket - i64::from(self.columns - 1 - col);
+                let t = bucket as f64 * self.bucket_secs;
+                t + self.bucket_secs > start && t < end
             })
-        }));
+        };
+
+        for (worker, row) in self.rows.iter().take(visible_rows) {
+            let mut spans =
+                vec![Span::styled(format!("W{worker:<3}"), Style::default().fg(HUD_CYAN))];
+            for col in 0..self.columns {
+                let i = usize::from(col);
+                let (glyph, color) = self.cell(row.samples[i], row.waits[i]);
+                let mut style = Style::default().fg(color);
+                if selected(col) {
+                    style = style.add_modifier(Modifier::REVERSED);
+                }
+                spans.push(Span::styled(glyph, style));
+            }
+            lines.push(Line::from(spans));
+        }
+
+        // Bookmarks and the cursor under the rows
+        let cursor_col =
+            state.cursor.and_then(|c| self.column_of(c)).unwrap_or(usize::from(self.columns) - 1);
+        let mut markers = vec![Span::raw(" ".repeat(usize::from(LABEL_WIDTH)))];
+        let mut cursor_bookmark = None;
+        for col in 0..usize::from(self.columns) {
+            let bookmark =
+                bookmarks.iter().rev().find(|b| self.column_of(b.timestamp) == Some(col));
+            if col == cursor_col {
+                cursor_bookmark = bookmark;
+                markers.push(Span::styled("^", Style::default().fg(CAUTION_AMBER)));
+            } else if bookmark.is_some() {
+                markers.push(Span::styled("◆", Style::default().fg(HUD_CYAN)));
+            } else {
+                markers.push(Span::raw(" "));
+            }
+        }
+        lines.push(Line::from(markers));
+
+        let mut title = format!("[ TIMELINE {}/col", format_secs(self.bucket_secs));
+        if let Some((start, end)) = state.range {
+            let _ = write!(title, "  {} selected", format_secs(end - start));
+        } else if state.anchor.is_some() {
+            title += "  selecting";
+        }
+        if self.max_wait_ns > 0 {
+            let _ = write!(title, "  ▲max {}", format_latency(self.max_wait_ns));
+        }
+        if let Some(bookmark) = cursor_bookmark {
+            let _ = write!(title, "  ◆ {}", bookmark.label);
+        }
+        title += " ]";
 
         let paragraph = Paragraph::new(lines).block(
             Block::default()
                 .borders(Borders::ALL)
                 .border_type(BorderType::Plain)
-                .title("[ ACTIVITY ]")
-                .border_style(Style::default().fg(HUD_GREEN)),
+                .title(title)
+                .border_style(Style::default().fg(CAUTION_AMBER)),
         );
-
         f.render_widget(paragraph, area);
     }
 }
+
+fn bucket_index(timestamp: f64, bucket_secs: f64) -> i64 {
+    (timestamp / bucket_secs).floor() as i64
+}
+
+/// "0.25s", "2s", "90s"
+fn format_secs(secs: f64) -> String {
+    if secs < 1.0 {
+        format!("{secs:.2}s")
+    } else {
+        format!("{secs:.0}s")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::sample;
+    use crate::trace_data::TraceEvent;
+    use std::sync::Arc;
+
+    fn event(worker_id: u32, timestamp: f64, duration_ns: Option<u64>) -> TraceEvent {
+        TraceEvent { timestamp, duration_ns, ..sample("work", worker_id) }
+    }
+
+    fn data() -> TraceData {
+        let mut events = Vec::new();
+        for i in 0..10 {
+            events.push(event(0, 100.0 + f64::from(i), None));
+        }
+        events.push(event(1, 105.5, None));
+        events.push(event(1, 105.6, Some(30_000_000)));
+        events.push(event(0, 109.2, None));
+        TraceData { events: Arc::new(events), workers: Arc::new(vec![0, 1]), duration: 9.2 }
+    }
+
+    #[test]
+    fn test_buckets_end_at_newest_event() {
+        let view = TimelineView::new(&data(), &TimelineState::new(), Rect::new(0, 0, 16, 6));
+        // 10 columns, one second each, ending at bucket 109
+        assert_eq!(view.columns, 10);
+        assert_eq!(view.rows[0].1.samples, vec![1, 1, 1, 1, 1, 1, 1, 1, 1, 2]);
+        assert_eq!(view.rows[1].1.samples[5], 1);
+        assert_eq!(view.rows[1].1.waits[5], 30_000_000);
+        assert_eq!(view.max_samples, 2);
+    }
+
+    #[test]
+    fn test_marking_selects_whole_buckets() {
+        let data = data();
+        let mut state = TimelineState::new();
+        state.move_cursor(-4, &data);
+        state.mark(&data);
+        state.move_cursor(2, &data);
+        assert_eq!(state.range(), None);
+        state.mark(&data);
+        // Cursor 105.2 to 107.2 covers buckets 105-107
+        assert_eq!(state.range(), Some((105.0, 108.0)));
+
+        state.clear();
+        assert_eq!(state.range(), None);
+    }
+
+    #[test]
+    fn test_shifting_range_stays_within_data() {
+        let data = data();
+        let mut state = TimelineState::new();
+        state.move_cursor(-8, &data);
+        state.mark(&data);
+        state.move_cursor(1, &data);
+        state.mark(&data);
+        assert_eq!(state.range(), Some((101.0, 103.0)));
+
+        state.shift_range(1, &data);
+        assert_eq!(state.range(), Some((103.0, 105.0)));
+        // Cannot slide past the first or last bucket
+        state.shift_range(-5, &data);
+        assert_eq!(state.range(), Some((100.0, 102.0)));
+        state.shift_range(5, &data);
+        assert_eq!(state.range(), Some((108.0, 110.0)));
+
+        state.close();
+        assert_eq!(state.range(), Some((108.0, 110.0)));
+        assert_eq!(state.cursor(), None);
+    }
+}
@@ -7,8 +7,8 @@
 //!
 //! ```text
 //! [ WORKERS ]
-//! W0 [||||      ] 40%   <- 40% of samples were blocking calls
-//! W1 [||        ] 20%
+//! W0 [||||      ] 40% ▲37ms  <- 40% of samples were blocking calls,
+//! W1 [||        ] 20%          worst scheduler wait was 37ms
 //! W2 [|         ] 10%
 //! ```
 //!
@@ -16,6 +16,7 @@
 //!
 //! - **High %** (amber/red): Worker is frequently blocked, not yielding to scheduler
 //! - **Low %** (green): Worker is mostly idle or yielding properly at `.await` points
+//! - **▲ latency**: Longest off-CPU wait measured by the scheduler hook (if any)
 //!
 //! A healthy async app should show low percentages. High percentages indicate
 //! blocking operations that should be moved to `spawn_blocking()` or made async.
@@ -29,8 +30,8 @@ use ratatui::{
 };
 use std::collections::HashMap;
 
-use super::theme::{gauge_bar, warning_color, HUD_CYAN, HUD_GREEN};
-use super::TraceData;
+use super::theme::{gauge_bar, warning_color, CAUTION_AMBER, HUD_CYAN, HUD_GREEN};
+use super::{format_latency, TraceData};
 
 /// Workers panel - tactical thread load display.
 ///
@@ -48,6 +49,8 @@ struct WorkerStats {
     total_samples: usize,
     /// Samples where we captured a blocking function (not "execution")
     samples_with_functions: usize,
+    /// Longest scheduler-measured off-CPU wait (0 = none seen)
+    max_wait_ns: u64,
 }
 
 impl WorkerStats {
@@ -67,9 +70,15 @@ impl WorkersPanel {
     /// We count "execution" events separately - these represent the worker
     /// running the async scheduler (polling futures), not blocking user code.
     /// Only events with actual function names count toward blocking percentage.
+    /// Scheduler latency events are intervals, not samples: they only feed the
+    /// worst-wait marker.
     pub fn new(data: &TraceData) -> Self {
         let worker_stats = data.events.iter().fold(HashMap::new(), |mut acc, event| {
             let stats: &mut WorkerStats = acc.entry(event.worker_id).or_default();
+            if let Some(wait_ns) = event.duration_ns {
+                stats.max_wait_ns = stats.max_wait_ns.max(wait_ns);
+                return acc;
+            }
             stats.total_samples += 1;
             // "execution" = scheduler overhead, not blocking user code
             if event.name != "execution" {
@@ -89,14 +98,21 @@ impl WorkersPanel {
                 self.worker_stats.get(worker_id).map(|stats| {
                     let percentage = stats.blocking_percentage();
                     let bar_color = warning_color(percentage);
-                    Line::from(vec![
+                    let mut spans = vec![
                         Span::styled(format!("W{worker_id} "), Style::default().fg(HUD_CYAN)),
                         Span::styled(gauge_bar(percentage, 10), Style::default().fg(bar_color)),
                         Span::styled(
                             format!(" {percentage:>3.0}%"),
                             Style::default().fg(bar_color),
                         ),
-                    ])
+                    ];
+                    if stats.max_wait_ns > 0 {
+                        spans.push(Span::styled(
+                            format!(" ▲{}", format_latency(stats.max_wait_ns)),
+                            Style::default().fg(CAUTION_AMBER),
+                        ));
+                    }
+                    Line::from(spans)
                 })
             })
             .collect();
@@ -1,14 +1,17 @@
-use hud::export::TraceEventExporter;
-use hud::symbolization::Symbolizer;
+use hud::classification::FrameOrigin;
+use hud::diff::{write_differential_folded, Capture, CaptureDiff, Change};
+use hud::export::{
+    Exporter, FoldedExporter, ProfileGroup, SpeedscopeExporter, StackOptions, TraceEventExporter,
+};
+use hud::testing::{frame, worker_event};
+use hud::trace_data::{Blocker, Bookmark, StackFrame};
+use hud_common::{EVENT_SCHEDULER_DETECTED, TRACE_EXECUTION_START};
+use std::sync::Arc;
 
 #[test]
 fn test_export_creates_valid_json() {
-    // Create a symbolizer (we can use any binary for testing)
-    let binary_path = env!("CARGO_BIN_EXE_hud");
-    let symbolizer = Symbolizer::new(binary_path).expect("Failed to create symbolizer");
-
     // Create an exporter and export to an in-memory buffer
-    let exporter = TraceEventExporter::new(symbolizer);
+    let exporter = TraceEventExporter::new();
     let mut buffer = Vec::new();
 
     exporter.export(&mut buffer).expect("Failed to export trace");
@@ -22,3 +25,326 @@ fn test_export_creates_valid_json() {
     assert!(parsed.get("displayTimeUnit").is_some());
     assert_eq!(parsed["displayTimeUnit"], "ms");
 }
+
+#[test]
+fn test_export_scheduler_event_as_complete_interval() {
+    let mut exporter = TraceEventExporter::new();
+
+    // First event anchors the trace at t=0; the scheduler event fires 50ms later
+    // after the worker waited 37ms for the CPU
+    exporter.add_event(&worker_event(TRACE_EXECUTION_START, 1_000_000_000, 0), None);
+    exporter.add_event(&worker_event(EVENT_SCHEDULER_DETECTED, 1_050_000_000, 37_000_000), None);
+
+    let mut buffer = Vec::new();
+    exporter.export(&mut buffer).expect("Failed to export trace");
+    let parsed: serde_json::Value = serde_json::from_slice(&buffer).expect("Invalid JSON");
+
+    let waits: Vec<_> =
+        parsed["traceEvents"].as_array().unwrap().iter().filter(|e| e["ph"] == "X").collect();
+    assert_eq!(waits.len(), 1);
+    assert_eq!(waits[0]["cat"], "scheduler");
+    assert_eq!(waits[0]["ts"], 13_000.0); // wait began 13ms after trace start
+    assert_eq!(waits[0]["dur"], 37_000.0);
+    assert_eq!(waits[0]["args"]["duration_ms"], 37.0);
+}
+
+#[test]
+fn test_export_scheduler_event_names_blocker() {
+    let mut exporter = TraceEventExporter::new();
+
+    let blocker = Blocker {
+        tid: 7712,
+        samples: 3,
+        comm: Some("bcrypt-pool".to_string()),
+        kernel_stack: None,
+    };
+    exporter.add_scheduler_event(
+        &worker_event(EVENT_SCHEDULER_DETECTED, 1_050_000_000, 37_000_000),
+        Some((&blocker, "bcrypt::hash")),
+    );
+
+    let mut buffer = Vec::new();
+    exporter.export(&mut buffer).expect("Failed to export trace");
+    let parsed: serde_json::Value = serde_json::from_slice(&buffer).expect("Invalid JSON");
+
+    let args = &parsed["traceEvents"][0]["args"];
+    assert_eq!(args["blocker_tid"], 7712);
+    assert_eq!(args["blocker_samples"], 3);
+    assert_eq!(args["blocker"], "bcrypt::hash");
+    assert_eq!(args["blocker_comm"], "bcrypt-pool");
+}
+
+#[test]
+fn test_export_samples_reference_call_stacks() {
+    let mut exporter = TraceEventExporter::new();
+
+    // Two call paths sharing `main -> app::handle`, innermost frame first
+    let hash = Arc::new(vec![
+        frame("app::hash", FrameOrigin::UserCode),
+        frame("app::handle", FrameOrigin::UserCode),
+        frame("main", FrameOrigin::UserCode),
+    ]);
+    let parse = Arc::new(vec![
+        frame("serde_json::from_str", FrameOrigin::ThirdParty),
+        frame("app::handle", FrameOrigin::UserCode),
+        frame("main", FrameOrigin::UserCode),
+    ]);
+    let mut event = worker_event(TRACE_EXECUTION_START, 1_000_000_000, 0);
+    event.stack_id = 1;
+    exporter.add_event(&event, Some(&hash));
+    event.stack_id = 2;
+    exporter.add_event(&event, Some(&parse));
+
+    let mut buffer = Vec::new();
+    exporter.export(&mut buffer).expect("Failed to export trace");
+    let parsed: serde_json::Value = serde_json::from_slice(&buffer).expect("Invalid JSON");
+
+    let frames = parsed["stackFrames"].as_object().unwrap();
+    assert_eq!(frames.len(), 4); // main, app::handle, and the two leaves
+
+    // Walk each sample's `sf` back to the root
+    let path = |sample: &serde_json::Value| {
+        let mut names = Vec::new();
+        let mut id = sample["sf"].to_string();
+        while let Some(frame) = frames.get(&id) {
+            names.push(frame["name"].as_str().unwrap().to_string());
+            id = frame["parent"].to_string();
+        }
+        names
+    };
+    let samples: Vec<_> =
+        parsed["traceEvents"].as_array().unwrap().iter().filter(|e| e["ph"] == "P").collect();
+    assert_eq!(samples[0]["name"], "app::hash");
+    assert_eq!(path(samples[0]), ["app::hash", "app::handle", "main"]);
+    assert_eq!(path(samples[1]), ["serde_json::from_str", "app::handle", "main"]);
+    assert_eq!(frames[&samples[1]["sf"].to_string()]["category"], "third_party");
+}
+
+#[test]
+fn test_export_samples_are_instants_with_counter_tracks() {
+    let mut exporter = TraceEventExporter::new();
+
+    // Two samples in the first second (one without a stack), a 40ms wait in the third
+    exporter.add_event(&worker_event(TRACE_EXECUTION_START, 1_000_000_000, 0), None);
+    exporter.add_event(&worker_event(TRACE_EXECUTION_START, 1_500_000_000, 0), None);
+    exporter.add_event(&worker_event(EVENT_SCHEDULER_DETECTED, 3_200_000_000, 40_000_000), None);
+
+    let mut buffer = Vec::new();
+    exporter.export(&mut buffer).expect("Failed to export trace");
+    let parsed: serde_json::Value = serde_json::from_slice(&buffer).expect("Invalid JSON");
+    let events = parsed["traceEvents"].as_array().unwrap();
+
+    // No unmatched begin slices
+    assert!(events.iter().all(|e| e["ph"] != "B" && e["ph"] != "E"));
+    let instants: Vec<_> = events.iter().filter(|e| e["ph"] == "i").collect();
+    assert_eq!(instants.len(), 2);
+    assert_eq!(instants[0]["s"], "t");
+
+    // One counter per second from the first sample until after the last wait
+    let counters: Vec<_> = events.iter().filter(|e| e["ph"] == "C").collect();
+    assert_eq!(counters.len(), 4);
+    assert!(counters.iter().all(|c| c["name"] == "Worker 0 blocking"));
+    assert_eq!(counters[0]["args"]["samples_per_sec"], 2);
+    assert_eq!(counters[1]["args"]["samples_per_sec"], 0);
+    assert_eq!(counters[2]["ts"], 2_000_000.0);
+    assert_eq!(counters[2]["args"]["wait_ms_per_sec"], 40.0);
+    assert_eq!(counters[3]["args"]["wait_ms_per_sec"], 0.0);
+}
+
+#[test]
+fn test_export_bookmarks_as_global_instants() {
+    let mut exporter = TraceEventExporter::new();
+    exporter.add_event(&worker_event(TRACE_EXECUTION_START, 1_000_000_000, 0), None);
+    exporter.add_bookmark(&Bookmark { timestamp: 1.25, label: "deployed here".to_string() });
+
+    let mut buffer = Vec::new();
+    exporter.export(&mut buffer).expect("Failed to export trace");
+    let parsed: serde_json::Value = serde_json::from_slice(&buffer).expect("Invalid JSON");
+    let bookmarks: Vec<_> = parsed["traceEvents"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|e| e["cat"] == "bookmark")
+        .collect();
+    assert_eq!(bookmarks.len(), 1);
+    assert_eq!(bookmarks[0]["name"], "deployed here");
+    assert_eq!(bookmarks[0]["ph"], "i");
+    assert_eq!(bookmarks[0]["s"], "g");
+    assert_eq!(bookmarks[0]["ts"], 250_000.0);
+}
+
+/// `main -> tokio poll -> app::hash`, innermost frame first
+fn runtime_stack() -> Arc<Vec<StackFrame>> {
+    Arc::new(vec![
+        frame("app::hash", FrameOrigin::UserCode),
+        frame("tokio::runtime::task::harness::poll", FrameOrigin::RuntimeLib),
+        frame("main", FrameOrigin::UserCode),
+    ])
+}
+
+#[test]
+fn test_export_folded_stacks_per_worker() {
+    let options = StackOptions { group_by: ProfileGroup::Worker, hide_runtime: true };
+    let mut exporter = FoldedExporter::new(options);
+    let stack = runtime_stack();
+
+    let mut event = worker_event(TRACE_EXECUTION_START, 1_000_000_000, 0);
+    exporter.add_event(&event, Some(&stack));
+    exporter.add_event(&event, Some(&stack));
+    event.worker_id = 1;
+    exporter.add_event(&event, Some(&stack));
+    // Waits and stackless samples are not part of a flame graph
+    exporter.add_event(&worker_event(EVENT_SCHEDULER_DETECTED, 2_000_000_000, 9_000_000), None);
+    exporter.add_event(&event, None);
+    assert_eq!(exporter.event_count(), 3);
+
+    let mut buffer = Vec::new();
+    exporter.export(&mut buffer).expect("Failed to export folded stacks");
+    assert_eq!(
+        String::from_utf8(buffer).unwrap(),
+        "Worker 0;main;app::hash 2\nWorker 1;main;app::hash 1\n"
+    );
+}
+
+#[test]
+fn test_export_speedscope_sampled_profiles() {
+    let mut exporter = SpeedscopeExporter::new(StackOptions::default());
+    let stack = runtime_stack();
+    let event = worker_event(TRACE_EXECUTION_START, 1_000_000_000, 0);
+    exporter.add_event(&event, Some(&stack));
+    exporter.add_event(&event, Some(&stack));
+
+    let mut buffer = Vec::new();
+    exporter.export(&mut buffer).expect("Failed to export speedscope profile");
+    let parsed: serde_json::Value = serde_json::from_slice(&buffer).expect("Invalid JSON");
+
+    let frames = parsed["shared"]["frames"].as_array().unwrap();
+    assert_eq!(frames.len(), 3);
+    let profiles = parsed["profiles"].as_array().unwrap();
+    assert_eq!(profiles.len(), 1);
+    assert_eq!(profiles[0]["type"], "sampled");
+    assert_eq!(profiles[0]["endValue"], 2);
+    assert_eq!(profiles[0]["weights"], serde_json::json!([1, 1]));
+
+    // Samples list frame indices outermost first
+    let names: Vec<_> = profiles[0]["samples"][0]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|i| frames[usize::try_from(i.as_u64().unwrap()).unwrap()]["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["main", "tokio::runtime::task::harness::poll", "app::hash"]);
+}
+
+#[test]
+fn test_export_streams_drained_events_into_one_document() {
+    let mut exporter = TraceEventExporter::new();
+    let mut buffer = Vec::new();
+
+    exporter.add_event(&worker_event(TRACE_EXECUTION_START, 1_000_000_000, 0), None);
+    exporter.add_event(&worker_event(TRACE_EXECUTION_START, 1_100_000_000, 0), None);
+    exporter.drain(&mut buffer).expect("Failed to drain trace");
+    // Nothing held after a drain
+    exporter.drain(&mut buffer).expect("Failed to drain trace");
+    let drained_len = buffer.len();
+
+    // A file cut off after a drain is repaired by dropping the last line
+    let mut partial = String::from_utf8(buffer.clone()).unwrap();
+    partial.truncate(partial.trim_end().rfind('\n').unwrap() + 1);
+    partial.push_str("]}");
+    let partial: serde_json::Value = serde_json::from_str(&partial).expect("Invalid JSON");
+    assert_eq!(partial["traceEvents"].as_array().unwrap().len(), 1);
+
+    exporter.add_event(&worker_event(EVENT_SCHEDULER_DETECTED, 2_000_000_000, 9_000_000), None);
+    assert_eq!(exporter.event_count(), 3);
+    exporter.export(&mut buffer).expect("Failed to export trace");
+    assert!(buffer.len() > drained_len);
+
+    let parsed: serde_json::Value = serde_json::from_slice(&buffer).expect("Invalid JSON");
+    let events = parsed["traceEvents"].as_array().unwrap();
+    assert_eq!(events.iter().filter(|e| e["cat"] == "execution").count(), 2);
+    assert_eq!(events.iter().filter(|e| e["cat"] == "scheduler").count(), 1);
+    assert!(events.iter().any(|e| e["ph"] == "C"));
+    assert!(events.iter().any(|e| e["name"] == "thread_name"));
+    assert_eq!(parsed["displayTimeUnit"], "ms");
+}
+
+#[test]
+fn test_checkpoint_repairs_stacks_of_a_cut_short_export() {
+    let mut exporter = TraceEventExporter::new();
+    let mut buffer = Vec::new();
+    let sample = |stack: &Arc<Vec<StackFrame>>, timestamp_ns| {
+        let mut event = worker_event(TRACE_EXECUTION_START, timestamp_ns, 0);
+        event.stack_id = 1;
+        (event, Arc::clone(stack))
+    };
+    let first = Arc::new(vec![frame("app::hash", FrameOrigin::UserCode)]);
+    let second = Arc::new(vec![frame("app::parse", FrameOrigin::UserCode)]);
+
+    // Drained the way the session loop does: checkpoint, then events
+    let (event, stack) = sample(&first, 1_000_000_000);
+    exporter.add_event(&event, Some(&stack));
+    assert!(exporter.checkpoint().unwrap().is_some());
+    exporter.drain(&mut buffer).unwrap();
+    let (event, stack) = sample(&first, 1_100_000_000);
+    exporter.add_event(&event, Some(&stack));
+    assert!(exporter.checkpoint().unwrap().is_none(), "no new frames");
+    exporter.drain(&mut buffer).unwrap();
+    let (event, stack) = sample(&second, 1_200_000_000);
+    exporter.add_event(&event, Some(&stack));
+    let checkpoint = exporter.checkpoint().unwrap().unwrap();
+    exporter.drain(&mut buffer).unwrap();
+
+    // Killed here: drop the last line, close the array, merge the checkpoint
+    let mut repaired = String::from_utf8(buffer).unwrap();
+    repaired.truncate(repaired.trim_end().rfind('\n').unwrap() + 1);
+    repaired.push_str("]}");
+    let mut repaired: serde_json::Value = serde_json::from_str(&repaired).unwrap();
+    let checkpoint: serde_json::Value = serde_json::from_slice(&checkpoint).unwrap();
+    repaired["stackFrames"] = checkpoint["stackFrames"].clone();
+
+    let events = repaired["traceEvents"].as_array().unwrap();
+    assert_eq!(events.len(), 2);
+    for event in events {
+        let sf = event["sf"].as_u64().unwrap().to_string();
+        assert_eq!(repaired["stackFrames"][sf]["name"], "app::hash");
+    }
+    assert_eq!(repaired["stackFrames"].as_object().unwrap().len(), 2);
+}
+
+/// Chrome export of `samples` perf samples, one every 100ms on `stack`
+fn chrome_capture(stack: &Arc<Vec<StackFrame>>, samples: u64) -> Capture {
+    let mut exporter = TraceEventExporter::new();
+    for i in 0..samples {
+        let mut event = worker_event(TRACE_EXECUTION_START, 1_000_000_000 + i * 100_000_000, 0);
+        event.stack_id = 1;
+        exporter.add_event(&event, Some(stack));
+    }
+    let mut buffer = Vec::new();
+    exporter.export(&mut buffer).expect("Failed to export trace");
+    Capture::from_chrome_trace(buffer.as_slice()).expect("Failed to load export")
+}
+
+#[test]
+fn test_diff_matches_chrome_exports_by_user_frame() {
+    // The export names samples after the innermost frame (`write`); diff
+    // matches them by the user frame, as the TUI ranks them
+    let stack = Arc::new(vec![
+        frame("write", FrameOrigin::SystemLib),
+        frame("app::flush", FrameOrigin::UserCode),
+        frame("main", FrameOrigin::UserCode),
+    ]);
+    let before = chrome_capture(&stack, 100);
+    let after = chrome_capture(&stack, 20);
+    assert_eq!(before.stacks["main;app::flush;write"], 100);
+
+    let diff = CaptureDiff::new("before.json", &before, "after.json", &after);
+    assert_eq!(diff.functions.len(), 1);
+    assert_eq!(diff.functions[0].name, "app::flush");
+    // 100 samples in 9.9s against 20 in 1.9s: rates barely move
+    assert_eq!(diff.functions[0].change, Change::Unchanged);
+
+    let mut out = Vec::new();
+    write_differential_folded(&before, &after, &mut out).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), "main;app::flush;write 19 20\n");
+}